@@ -0,0 +1,492 @@
+use rust_ray_tracing::engine::integrators::IntegratorKind;
+use rust_ray_tracing::engine::samplers::SamplerKind;
+use rust_ray_tracing::engine::filters::FilterKind;
+use rust_ray_tracing::consts::ADAPTIVE_MAX_SAMPLES_FACTOR;
+use rust_ray_tracing::engine::{AdaptiveSampling, ProgressiveSettings, RenderSettings};
+use rust_ray_tracing::utils::{
+    EXRPixelType, EXRWriter, HDRWriter, ImageEncoder, PFMWriter, PGMWriter, PNGBitDepth, PNGWriter, PPMWriter,
+};
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+const DEFAULT_OUTPUT_PATH: &str = "result.ppm";
+const DEFAULT_SCENE: &str = "random";
+
+/// The image formats the result can be saved in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    Ppm,
+    PpmAscii,
+    Pgm,
+    Pfm,
+    Png,
+    Png16,
+    Hdr,
+    Exr,
+    ExrFloat,
+}
+
+/// What the binary was asked to do.
+pub enum Command {
+    Help,
+    Render(Box<Arguments>),
+}
+
+/// The parsed command line. Render settings which aren't set are taken from
+/// the scene file, or from the defaults for built-in scenes.
+pub struct Arguments {
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub samples_per_pixel: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub integrator: Option<IntegratorKind>,
+
+    /// Overrides the distance of the ambient occlusion integrator.
+    pub ambient_occlusion_distance: Option<f64>,
+    pub sampler: Option<SamplerKind>,
+    pub filter: Option<FilterKind>,
+    pub threads_count: Option<usize>,
+    pub seed: Option<u64>,
+
+    /// Enables adaptive sampling with this noise threshold.
+    pub adaptive_threshold: Option<f64>,
+
+    /// Overrides the most samples a pixel may take with adaptive sampling.
+    pub max_samples: Option<usize>,
+
+    /// Set when the image should be rendered in passes (see `render_progressive`).
+    pub progressive: Option<ProgressiveSettings>,
+
+    /// Save the intermediate image of a progressive render after every this many passes.
+    pub save_every: Option<usize>,
+    pub output: String,
+
+    /// Where to save an image of the amount of samples every pixel took.
+    pub heatmap: Option<String>,
+    pub format: OutputFormat,
+
+    /// Either the name of a built-in scene, or a path to a scene file.
+    pub scene: String,
+}
+
+
+const INTEGRATOR_NAMES: [(&str, IntegratorKind); 4] = [
+    ("path_tracer", IntegratorKind::PathTracer),
+    ("naive_path_tracer", IntegratorKind::NaivePathTracer),
+    ("direct_lighting", IntegratorKind::DirectLighting),
+    ("ambient_occlusion", IntegratorKind::AmbientOcclusion { distance: f64::INFINITY }),
+];
+
+const SAMPLER_NAMES: [(&str, SamplerKind); 5] = [
+    ("independent", SamplerKind::Independent),
+    ("stratified", SamplerKind::Stratified),
+    ("halton", SamplerKind::Halton),
+    ("sobol", SamplerKind::Sobol),
+    ("blue_noise", SamplerKind::BlueNoise),
+];
+
+
+impl OutputFormat {
+    const NAMES: [(&'static str, OutputFormat); 9] = [
+        ("ppm", OutputFormat::Ppm),
+        ("ppm-ascii", OutputFormat::PpmAscii),
+        ("pgm", OutputFormat::Pgm),
+        ("pfm", OutputFormat::Pfm),
+        ("png", OutputFormat::Png),
+        ("png16", OutputFormat::Png16),
+        ("hdr", OutputFormat::Hdr),
+        ("exr", OutputFormat::Exr),
+        ("exr-float", OutputFormat::ExrFloat),
+    ];
+
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        OutputFormat::NAMES.iter()
+            .find(|(format_name, _)| *format_name == name)
+            .map(|(_, format)| *format)
+    }
+
+    /// Guess the format out of the file extension.
+    pub fn from_path(path: &str) -> Option<OutputFormat> {
+        let extension = Path::new(path).extension()?.to_str()?.to_lowercase();
+        OutputFormat::from_name(&extension)
+    }
+
+    pub fn encoder(&self) -> Box<dyn ImageEncoder> {
+        match self {
+            OutputFormat::Ppm => Box::new(PPMWriter::binary()),
+            OutputFormat::PpmAscii => Box::new(PPMWriter::new()),
+            OutputFormat::Pgm => Box::new(PGMWriter::default()),
+            OutputFormat::Pfm => Box::new(PFMWriter::new()),
+            OutputFormat::Png => Box::new(PNGWriter::new(PNGBitDepth::Eight)),
+            OutputFormat::Png16 => Box::new(PNGWriter::new(PNGBitDepth::Sixteen)),
+            OutputFormat::Hdr => Box::new(HDRWriter::new()),
+            OutputFormat::Exr => Box::new(EXRWriter::new(EXRPixelType::Half)),
+            OutputFormat::ExrFloat => Box::new(EXRWriter::new(EXRPixelType::Float)),
+        }
+    }
+}
+
+impl Arguments {
+    /// Override the settings with the ones given in the command line. When only one
+    /// dimension of the image is given, the other one keeps the original aspect ratio.
+    pub fn apply_to(&self, settings: &mut RenderSettings) {
+        let aspect_ratio = settings.width as f64 / settings.height as f64;
+
+        match (self.width, self.height) {
+            (Some(width), Some(height)) => {
+                settings.width = width;
+                settings.height = height;
+            }
+            (Some(width), None) => {
+                settings.width = width;
+                settings.height = ((width as f64 / aspect_ratio) as usize).max(2);
+            }
+            (None, Some(height)) => {
+                settings.width = ((height as f64 * aspect_ratio) as usize).max(2);
+                settings.height = height;
+            }
+            (None, None) => {}
+        }
+
+        settings.samples_per_pixel = self.samples_per_pixel.unwrap_or(settings.samples_per_pixel);
+        settings.max_depth = self.max_depth.unwrap_or(settings.max_depth);
+        settings.integrator = self.integrator.unwrap_or(settings.integrator);
+        if let (IntegratorKind::AmbientOcclusion { distance }, Some(new_distance)) =
+            (&mut settings.integrator, self.ambient_occlusion_distance) {
+            *distance = new_distance;
+        }
+        settings.sampler = self.sampler.unwrap_or(settings.sampler);
+        settings.filter = self.filter.unwrap_or(settings.filter);
+        settings.threads_count = self.threads_count.unwrap_or(settings.threads_count);
+        settings.seed = self.seed.or(settings.seed);
+
+        if let Some(noise_threshold) = self.adaptive_threshold {
+            settings.adaptive = Some(AdaptiveSampling {
+                noise_threshold,
+                max_samples: settings.samples_per_pixel * ADAPTIVE_MAX_SAMPLES_FACTOR,
+            });
+        }
+        if let (Some(adaptive), Some(max_samples)) = (&mut settings.adaptive, self.max_samples) {
+            adaptive.max_samples = max_samples;
+        }
+    }
+
+    /// Make sure the options which only tweak a feature aren't silently ignored because the
+    /// feature is disabled. Called on the final settings, since the scene file may enable it.
+    pub fn check_applied(&self, settings: &RenderSettings) -> Result<(), String> {
+        if self.max_samples.is_some() && settings.adaptive.is_none() {
+            return Err("'--max-samples' requires adaptive sampling, enabled by '--adaptive' \
+                or by a 'noise_threshold' in the scene file".to_string());
+        }
+
+        let is_ambient_occlusion = matches!(settings.integrator, IntegratorKind::AmbientOcclusion { .. });
+        if self.ambient_occlusion_distance.is_some() && !is_ambient_occlusion {
+            return Err("'--ao-distance' requires the ambient_occlusion integrator".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+pub fn usage() -> String {
+    let format_names: Vec<&str> = OutputFormat::NAMES.iter().map(|(name, _)| *name).collect();
+    let integrator_names: Vec<&str> = INTEGRATOR_NAMES.iter().map(|(name, _)| *name).collect();
+    let sampler_names: Vec<&str> = SAMPLER_NAMES.iter().map(|(name, _)| *name).collect();
+
+    format!("\
+Renders a scene into an image.
+
+Usage: rust_ray_tracing [OPTIONS]
+
+Options:
+      --scene <SCENE>       A scene file, or the name of a built-in scene [default: {scene}]
+                            Built-in scenes: {builtin_scenes}
+  -o, --output <PATH>       Where to save the image [default: {output}]
+  -f, --format <FORMAT>     The image format, guessed from the output extension by default
+                            Formats: {formats}
+      --width <PIXELS>      The width of the image
+      --height <PIXELS>     The height of the image. When only one of width and height is
+                            given, the other one keeps the aspect ratio of the scene
+  -s, --samples <COUNT>     Samples per pixel
+  -d, --max-depth <COUNT>   Maximum amount of times a ray may scatter
+  -i, --integrator <NAME>   The rendering algorithm [default: path_tracer]
+                            Integrators: {integrators}
+      --ao-distance <DIST>  How far ambient occlusion looks for occluders [default: infinite]
+      --sampler <NAME>      How the random numbers of a pixel's samples are spread out
+                            [default: independent]
+                            Samplers: {samplers}
+      --filter <NAME>       How samples are weighted into the pixels around them [default: box]
+                            Filters: {filters}
+      --seed <SEED>         Render the exact same image for the same seed
+  -j, --threads <COUNT>     Amount of rendering threads [default: available cores]
+      --heatmap <PATH>      Also save an image of how many samples every pixel took,
+                            in the same format as the output
+
+Adaptive sampling:
+      --adaptive <ERROR>    After the regular samples, keep sampling pixels whose relative
+                            error is above this, such as 0.01
+      --max-samples <COUNT> The most samples a pixel may take [default: {max_samples_factor}x samples]
+
+Progressive rendering (implied by any of the options below):
+      --progressive         Render in passes, each adding a few samples to every pixel
+      --pass-samples <COUNT>
+                            Samples added to each pixel by every pass [default: {pass_samples}]
+      --time-limit <SECS>   Stop after the pass during which this much time has passed
+      --noise-threshold <ERROR>
+                            Stop sampling pixels whose relative error is below this, such as 0.01
+      --save-every <PASSES> Save the intermediate image after every this many passes
+
+      --help                Print this message
+",
+        scene = DEFAULT_SCENE,
+        builtin_scenes = crate::scenes::BUILTIN_SCENES.join(", "),
+        output = DEFAULT_OUTPUT_PATH,
+        formats = format_names.join(", "),
+        integrators = integrator_names.join(", "),
+        samplers = sampler_names.join(", "),
+        filters = FilterKind::NAMES.join(", "),
+        max_samples_factor = ADAPTIVE_MAX_SAMPLES_FACTOR,
+        pass_samples = ProgressiveSettings::default().samples_per_pass,
+    )
+}
+
+/// Parse the command line arguments, not including the program name.
+/// Both '--option value' and '--option=value' are accepted.
+pub fn parse_arguments<I: Iterator<Item = String>>(arguments: I) -> Result<Command, String> {
+    let mut parsed = Arguments {
+        width: None,
+        height: None,
+        samples_per_pixel: None,
+        max_depth: None,
+        integrator: None,
+        ambient_occlusion_distance: None,
+        sampler: None,
+        filter: None,
+        threads_count: None,
+        seed: None,
+        adaptive_threshold: None,
+        max_samples: None,
+        progressive: None,
+        save_every: None,
+        output: DEFAULT_OUTPUT_PATH.to_string(),
+        heatmap: None,
+        format: OutputFormat::Ppm,
+        scene: DEFAULT_SCENE.to_string(),
+    };
+    let mut format = None;
+
+    let mut arguments = arguments.peekable();
+    while let Some(argument) = arguments.next() {
+        let (option, inline_value) = match argument.split_once('=') {
+            Some((option, value)) if option.starts_with("--") => (option.to_string(), Some(value.to_string())),
+            _ => (argument.clone(), None),
+        };
+
+        if option == "--help" {
+            return Ok(Command::Help);
+        }
+
+        let mut value = || -> Result<String, String> {
+            match inline_value.clone() {
+                Some(value) => Ok(value),
+                None => arguments.next().ok_or_else(|| format!("'{}' requires a value", option)),
+            }
+        };
+
+        match option.as_str() {
+            "--scene" => parsed.scene = value()?,
+            "-o" | "--output" => parsed.output = value()?,
+            "-f" | "--format" => {
+                let name = value()?;
+                format = Some(OutputFormat::from_name(&name).ok_or_else(|| format!("Unknown format '{}'", name))?);
+            }
+            "--width" => parsed.width = Some(parse_number(&option, &value()?, 2)?),
+            "--height" => parsed.height = Some(parse_number(&option, &value()?, 2)?),
+            "-s" | "--samples" => parsed.samples_per_pixel = Some(parse_number(&option, &value()?, 1)?),
+            "-d" | "--max-depth" => parsed.max_depth = Some(parse_number(&option, &value()?, 1)?),
+            "-i" | "--integrator" => {
+                let name = value()?;
+                let integrator = INTEGRATOR_NAMES.iter()
+                    .find(|(integrator_name, _)| *integrator_name == name)
+                    .map(|(_, integrator)| *integrator)
+                    .ok_or_else(|| format!("Unknown integrator '{}'", name))?;
+                parsed.integrator = Some(integrator);
+            }
+            "--ao-distance" => {
+                let text = value()?;
+                let distance = text.parse::<f64>().ok()
+                    .filter(|distance| *distance > 0.0)
+                    .ok_or_else(|| format!("'{}' expects a positive number, got '{}'", option, text))?;
+                parsed.ambient_occlusion_distance = Some(distance);
+            }
+            "--sampler" => {
+                let name = value()?;
+                let sampler = SAMPLER_NAMES.iter()
+                    .find(|(sampler_name, _)| *sampler_name == name)
+                    .map(|(_, sampler)| *sampler)
+                    .ok_or_else(|| format!("Unknown sampler '{}'", name))?;
+                parsed.sampler = Some(sampler);
+            }
+            "--filter" => {
+                let name = value()?;
+                parsed.filter = Some(FilterKind::from_name(&name).ok_or_else(|| format!("Unknown filter '{}'", name))?);
+            }
+            "-j" | "--threads" => parsed.threads_count = Some(parse_number(&option, &value()?, 1)?),
+            "--seed" => parsed.seed = Some(parse_number(&option, &value()?, 0)?),
+            "--heatmap" => parsed.heatmap = Some(value()?),
+            "--adaptive" => parsed.adaptive_threshold = Some(parse_positive(&option, &value()?)?),
+            "--max-samples" => parsed.max_samples = Some(parse_number(&option, &value()?, 1)?),
+            "--progressive" => {
+                parsed.progressive.get_or_insert_with(ProgressiveSettings::default);
+            }
+            "--pass-samples" => {
+                let samples_per_pass = parse_number(&option, &value()?, 1)?;
+                parsed.progressive.get_or_insert_with(ProgressiveSettings::default).samples_per_pass = samples_per_pass;
+            }
+            "--time-limit" => {
+                let seconds = parse_positive(&option, &value()?)?;
+                parsed.progressive.get_or_insert_with(ProgressiveSettings::default).time_limit = Some(Duration::from_secs_f64(seconds));
+            }
+            "--noise-threshold" => {
+                let threshold = parse_positive(&option, &value()?)?;
+                parsed.progressive.get_or_insert_with(ProgressiveSettings::default).noise_threshold = Some(threshold);
+            }
+            "--save-every" => {
+                parsed.save_every = Some(parse_number(&option, &value()?, 1)?);
+                parsed.progressive.get_or_insert_with(ProgressiveSettings::default);
+            }
+            _ if option.starts_with('-') => return Err(format!("Unknown option '{}'", option)),
+            _ => return Err(format!("Unexpected argument '{}'", argument)),
+        }
+    }
+
+    if parsed.progressive.is_some() && (parsed.adaptive_threshold.is_some() || parsed.max_samples.is_some()) {
+        return Err("Adaptive sampling can't be combined with progressive rendering, use '--noise-threshold' instead".to_string());
+    }
+
+    parsed.format = match format {
+        Some(format) => format,
+        None => OutputFormat::from_path(&parsed.output)
+            .ok_or_else(|| format!("Cannot guess the format of '{}', please pass '--format'", parsed.output))?,
+    };
+
+    Ok(Command::Render(Box::new(parsed)))
+}
+
+fn parse_number<T: FromStr + PartialOrd + From<u8>>(option: &str, value: &str, minimum: u8) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(number) if number >= T::from(minimum) => Ok(number),
+        _ => Err(format!("'{}' expects a whole number of at least {}, got '{}'", option, minimum, value)),
+    }
+}
+
+/// Parse a positive number, which must be finite since it's used for durations and thresholds.
+fn parse_positive(option: &str, value: &str) -> Result<f64, String> {
+    value.parse::<f64>().ok()
+        .filter(|number| *number > 0.0 && number.is_finite())
+        .ok_or_else(|| format!("'{}' expects a positive number, got '{}'", option, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Command, String> {
+        parse_arguments(arguments.iter().map(|argument| argument.to_string()))
+    }
+
+    fn parsed(arguments: &[&str]) -> Arguments {
+        match parse(arguments) {
+            Ok(Command::Render(parsed)) => *parsed,
+            Ok(Command::Help) => panic!("{:?} asked for help", arguments),
+            Err(error) => panic!("{:?} were rejected: {}", arguments, error),
+        }
+    }
+
+    fn error(arguments: &[&str]) -> String {
+        match parse(arguments) {
+            Err(error) => error,
+            Ok(_) => panic!("{:?} were accepted", arguments),
+        }
+    }
+
+    /// Apply the arguments to the default settings, and check they all took effect.
+    fn applied_settings(arguments: &[&str]) -> Result<RenderSettings, String> {
+        let parsed = parsed(arguments);
+        let mut settings = RenderSettings::default();
+        parsed.apply_to(&mut settings);
+        parsed.check_applied(&settings).map(|_| settings)
+    }
+
+    #[test]
+    fn values_may_follow_an_equals_sign() {
+        let parsed = parsed(&["--width=320", "--samples=8", "--output=out.png", "-d", "4", "--scene=cornell_box"]);
+
+        assert_eq!(parsed.width, Some(320));
+        assert_eq!(parsed.samples_per_pixel, Some(8));
+        assert_eq!(parsed.max_depth, Some(4));
+        assert_eq!(parsed.output, "out.png");
+        assert_eq!(parsed.scene, "cornell_box");
+        assert_eq!(parsed.format, OutputFormat::Png);
+    }
+
+    #[test]
+    fn values_are_required() {
+        assert_eq!(error(&["--width"]), "'--width' requires a value");
+        assert!(matches!(parse(&["--width", "320", "--help"]), Ok(Command::Help)));
+        assert_eq!(error(&["--width=abc"]), "'--width' expects a whole number of at least 2, got 'abc'");
+    }
+
+    #[test]
+    fn numbers_must_be_at_least_their_minimum() {
+        assert_eq!(parsed(&["--width", "2", "--height", "2"]).height, Some(2));
+        assert_eq!(error(&["--height", "1"]), "'--height' expects a whole number of at least 2, got '1'");
+        for option in ["--samples", "--max-depth", "--threads", "--max-samples", "--pass-samples", "--save-every"] {
+            assert_eq!(error(&[option, "0"]), format!("'{}' expects a whole number of at least 1, got '0'", option));
+        }
+        assert_eq!(parsed(&["--seed", "0"]).seed, Some(0));
+
+        for option in ["--adaptive", "--time-limit", "--noise-threshold", "--ao-distance"] {
+            for value in ["0", "-1"] {
+                assert_eq!(error(&[option, value]), format!("'{}' expects a positive number, got '{}'", option, value));
+            }
+        }
+        assert!(error(&["--time-limit", "inf"]).contains("expects a positive number"));
+    }
+
+    #[test]
+    fn format_is_guessed_from_the_output_extension() {
+        assert_eq!(parsed(&[]).format, OutputFormat::Ppm);
+        assert_eq!(parsed(&["-o", "image.PNG"]).format, OutputFormat::Png);
+        assert_eq!(parsed(&["-o", "renders/image.exr"]).format, OutputFormat::Exr);
+        assert_eq!(parsed(&["-o", "image.txt", "--format", "png16"]).format, OutputFormat::Png16);
+
+        assert_eq!(error(&["-o", "image.txt"]), "Cannot guess the format of 'image.txt', please pass '--format'");
+        assert_eq!(error(&["-o", "image"]), "Cannot guess the format of 'image', please pass '--format'");
+        assert_eq!(error(&["--format", "jpeg"]), "Unknown format 'jpeg'");
+    }
+
+    #[test]
+    fn progressive_rendering_rejects_adaptive_sampling() {
+        let message = "Adaptive sampling can't be combined with progressive rendering, use '--noise-threshold' instead";
+        assert_eq!(error(&["--progressive", "--adaptive", "0.05"]), message);
+        assert_eq!(error(&["--adaptive", "0.05", "--time-limit", "10"]), message);
+        assert_eq!(error(&["--max-samples", "100", "--pass-samples", "8"]), message);
+
+        let progressive = parsed(&["--pass-samples", "8", "--noise-threshold", "0.05"]).progressive.unwrap();
+        assert_eq!(progressive.samples_per_pass, 8);
+        assert_eq!(progressive.noise_threshold, Some(0.05));
+    }
+
+    #[test]
+    fn options_of_disabled_features_are_rejected() {
+        assert_eq!(applied_settings(&["--ao-distance", "2"]).unwrap_err(), "'--ao-distance' requires the ambient_occlusion integrator");
+        let settings = applied_settings(&["--ao-distance", "2", "-i", "ambient_occlusion"]).unwrap();
+        assert_eq!(settings.integrator, IntegratorKind::AmbientOcclusion { distance: 2.0 });
+
+        assert!(applied_settings(&["--max-samples", "100"]).unwrap_err().starts_with("'--max-samples' requires adaptive sampling"));
+        let settings = applied_settings(&["--max-samples", "100", "--adaptive", "0.05"]).unwrap();
+        assert_eq!(settings.adaptive.unwrap().max_samples, 100);
+    }
+}
@@ -6,7 +6,19 @@ pub const NEAR_ZERO_THRESHOLD: f64 = 1e-8;
 pub const AIR_REFRACTION_INDEX: f64 = 1.0;
 
 
+/// Hits closer than this are ignored, so rays don't hit the surface they were scattered from.
+pub const HIT_DISTANCE_THRESHOLD: f64 = 0.001;
+
+
 // Camera consts
 pub const VIEWPORT_HEIGHT: f64 = 2.0;
 pub const FOCAL_LENGTH: f64 = 1.0;
-pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
\ No newline at end of file
+pub const ASPECT_RATIO: f64 = 16.0 / 9.0;
+
+/// The smallest width and height of a rendered image, since pixels are mapped
+/// onto the camera by dividing their coordinates by the size minus 1.
+pub const MIN_IMAGE_SIZE: usize = 2;
+
+/// Unless told otherwise, adaptive sampling lets a pixel take up to this many
+/// times the regular amount of samples per pixel.
+pub const ADAPTIVE_MAX_SAMPLES_FACTOR: usize = 4;
@@ -0,0 +1,107 @@
+use crate::vectors::{Point, Transform, Vector};
+use crate::engine::Ray;
+
+/// Boxes thinner than this along some axis are padded, since rays can't
+/// hit a box without any volume.
+const MIN_EXTENT: f64 = 1e-4;
+
+/// Axis-aligned bounding box, represented by its minimum and maximum corners.
+/// Used for quickly rejecting rays that can't hit the objects inside it.
+#[derive(Copy, Clone, Debug)]
+pub struct AABB {
+    pub minimum: Point,
+    pub maximum: Point,
+}
+
+
+impl AABB {
+    pub fn new(minimum: Point, maximum: Point) -> AABB {
+        AABB { minimum, maximum }
+    }
+
+    /// Get the smallest box which contains both of the given boxes.
+    pub fn surrounding_box(box0: AABB, box1: AABB) -> AABB {
+        AABB::new(box0.minimum.min(box1.minimum), box0.maximum.max(box1.maximum))
+    }
+
+    /// Get the smallest box which contains the given box and point.
+    pub fn surrounding_point(bounding_box: AABB, point: Point) -> AABB {
+        AABB::new(bounding_box.minimum.min(point), bounding_box.maximum.max(point))
+    }
+
+    /// Get a copy of the box which is at least MIN_EXTENT thick along each axis.
+    /// Required for flat objects, such as an axis-aligned triangle.
+    pub fn padded(&self) -> AABB {
+        let extent = self.maximum - self.minimum;
+        let padding = Vector::new(
+            if extent.x < MIN_EXTENT { MIN_EXTENT / 2.0 } else { 0.0 },
+            if extent.y < MIN_EXTENT { MIN_EXTENT / 2.0 } else { 0.0 },
+            if extent.z < MIN_EXTENT { MIN_EXTENT / 2.0 } else { 0.0 },
+        );
+
+        AABB::new(self.minimum - padding, self.maximum + padding)
+    }
+
+    /// Get the smallest axis-aligned box which contains the transformed box,
+    /// by transforming all 8 of its corners.
+    pub fn transformed(&self, transform: &Transform) -> AABB {
+        let corner = |x: bool, y: bool, z: bool| transform.point(Point::new(
+            if x { self.maximum.x } else { self.minimum.x },
+            if y { self.maximum.y } else { self.minimum.y },
+            if z { self.maximum.z } else { self.minimum.z },
+        ));
+
+        let first = corner(false, false, false);
+        let mut result = AABB::new(first, first);
+        for index in 1..8 {
+            result = AABB::surrounding_point(result, corner(index & 1 != 0, index & 2 != 0, index & 4 != 0));
+        }
+
+        result
+    }
+
+    pub fn centroid(&self) -> Point {
+        (self.minimum + self.maximum) / 2.0
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let extent = self.maximum - self.minimum;
+        2.0 * (extent.x * extent.y + extent.y * extent.z + extent.z * extent.x)
+    }
+
+    /// Get the index of the axis along which the box is the longest (0 => x, 1 => y, 2 => z).
+    pub fn longest_axis(&self) -> usize {
+        let extent = self.maximum - self.minimum;
+        if extent.x > extent.y && extent.x > extent.z {
+            return 0;
+        }
+        if extent.y > extent.z {
+            return 1;
+        }
+
+        2
+    }
+
+    /// Check whether the ray hits the box somewhere in the (t_min, t_max) range,
+    /// using the slab method.
+    pub fn hit(&self, ray: &Ray, mut t_min: f64, mut t_max: f64) -> bool {
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / ray.direction[axis];
+            let mut t0 = (self.minimum[axis] - ray.origin[axis]) * inverse_direction;
+            let mut t1 = (self.maximum[axis] - ray.origin[axis]) * inverse_direction;
+
+            if inverse_direction < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+
+            if t_max <= t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+}
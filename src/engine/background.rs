@@ -0,0 +1,44 @@
+use crate::engine::Ray;
+use crate::vectors::Color;
+
+/// The color a ray gets when it doesn't hit anything in the scene.
+#[derive(Copy, Clone, Debug)]
+pub enum Background {
+    /// Blend between two colors according to the height the ray points at.
+    Gradient { bottom: Color, top: Color },
+
+    /// The same color in every direction.
+    Solid(Color),
+}
+
+
+impl Background {
+    /// White at the horizon, blending into light blue upwards.
+    pub fn sky() -> Background {
+        Background::Gradient {
+            bottom: Color::new(1.0, 1.0, 1.0),
+            top: Color::new(0.5, 0.7, 1.0),
+        }
+    }
+
+    /// No light comes from the background, so the scene is lit only by its emissive objects.
+    pub fn black() -> Background {
+        Background::Solid(Color::zeroes())
+    }
+
+    pub fn color(&self, ray: &Ray) -> Color {
+        match *self {
+            Background::Gradient { bottom, top } => {
+                let t = (ray.direction.unit().y + 1.0) / 2.0;
+                (1.0 - t) * bottom + t * top
+            }
+            Background::Solid(color) => color
+        }
+    }
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background::sky()
+    }
+}
@@ -1,5 +1,6 @@
 use crate::vectors::{Point, Vector};
-use crate::engine::Ray;
+use crate::engine::samplers::SampleDimensions;
+use crate::engine::{Ray, Rng};
 use crate::engine::utils::degrees_to_radians;
 use crate::consts::VIEWPORT_HEIGHT;
 
@@ -10,7 +11,11 @@ pub struct Camera {
     lower_left_corner: Vector,
     u: Vector,
     v: Vector,
-    lens_radius: f64
+    lens_radius: f64,
+
+    /// Rays are spread over the time between opening and closing the shutter.
+    shutter_open: f64,
+    shutter_close: f64
 }
 
 
@@ -46,18 +51,37 @@ impl Camera {
             vertical,
             lower_left_corner,
             u, v,
-            lens_radius
+            lens_radius,
+            shutter_open: 0.0,
+            shutter_close: 0.0
         }
     }
 
+    /// Keep the shutter open between the given times, so that objects which move
+    /// during that time are blurred. By default the shutter is open only at time 0.
+    pub fn with_shutter(mut self, shutter_open: f64, shutter_close: f64) -> Camera {
+        self.shutter_open = shutter_open;
+        self.shutter_close = shutter_close;
+        self
+    }
+
     /// Get a ray from the camera to the (s, t) location in the viewport.
-    pub fn get_ray(&self, s: f64, t: f64) -> Ray {
-        let random_vector = self.lens_radius * Vector::random_in_unit_disk();
+    pub fn get_ray(&self, s: f64, t: f64, rng: &mut Rng) -> Ray {
+        rng.use_dimensions(SampleDimensions::Lens);
+        let random_vector = self.lens_radius * Vector::random_in_unit_disk(rng);
         let offset = random_vector.x * self.u + random_vector.y * self.v;
 
         let origin = self.origin + offset;
         let direction = self.lower_left_corner + s * self.horizontal + t * self.vertical - origin;
 
-        Ray::new(origin, direction)
+        // No random number is drawn for an instant shutter, so still renders don't change
+        let time = if self.shutter_close > self.shutter_open {
+            rng.use_dimensions(SampleDimensions::Time);
+            rng.range(self.shutter_open, self.shutter_close)
+        } else {
+            self.shutter_open
+        };
+
+        Ray::with_time(origin, direction, time)
     }
 }
\ No newline at end of file
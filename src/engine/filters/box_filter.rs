@@ -0,0 +1,26 @@
+use crate::engine::filters::Filter;
+
+/// Gives every sample within the radius the same weight. With a radius of 0.5, every pixel is
+/// the plain average of its own samples. The range is half-open, so a sample on the edge
+/// between two pixels only counts for one of them.
+pub struct BoxFilter {
+    radius: f64
+}
+
+
+impl BoxFilter {
+    pub fn new(radius: f64) -> BoxFilter {
+        BoxFilter {radius}
+    }
+}
+
+
+impl Filter for BoxFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn weight(&self, offset: f64) -> f64 {
+        if -self.radius <= offset && offset < self.radius { 1.0 } else { 0.0 }
+    }
+}
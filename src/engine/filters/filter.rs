@@ -0,0 +1,125 @@
+use crate::engine::filters::{BoxFilter, GaussianFilter, LanczosFilter, MitchellFilter, TentFilter};
+
+/// Decides how much a sample contributes to each of the pixels around it. Every pixel is the
+/// weighted average of the samples within the filter's radius from its center, so a sample
+/// can contribute to its neighbours as well as to the pixel it was taken in.
+///
+/// Filters are separable: the weight of a sample at an offset (x, y) from a pixel's center is
+/// `weight(x) * weight(y)`. The weights don't need to be normalized, since every pixel is
+/// divided by the sum of the weights of its samples. Filters are shared between the rendering threads.
+pub trait Filter: Send + Sync {
+    /// How far from a pixel's center, in pixels, samples still have a weight.
+    fn radius(&self) -> f64;
+
+    /// The weight of a sample at the given offset (in pixels) from a pixel's center, along one axis.
+    fn weight(&self, offset: f64) -> f64;
+}
+
+/// The built-in filters.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FilterKind {
+    /// Every sample counts only for the pixel it was taken in (with a radius of 0.5).
+    Box { radius: f64 },
+
+    /// Weights fall linearly to 0 at the radius.
+    Tent { radius: f64 },
+    Gaussian { radius: f64, sigma: f64 },
+
+    /// A cubic with small negative lobes, which stays sharp without ringing much.
+    /// B and C are its parameters, where B + 2C = 1 gives the best balance.
+    Mitchell { radius: f64, b: f64, c: f64 },
+
+    /// A windowed sinc, which is the sharpest, but may ring next to strong edges.
+    Lanczos { radius: f64 },
+}
+
+
+impl FilterKind {
+    /// The names of the filters, as accepted by `from_name`.
+    pub const NAMES: [&'static str; 5] = ["box", "tent", "gaussian", "mitchell", "lanczos"];
+
+    /// Get a filter with its usual parameters out of its name.
+    pub fn from_name(name: &str) -> Option<FilterKind> {
+        match name {
+            "box" => Some(FilterKind::Box { radius: 0.5 }),
+            "tent" => Some(FilterKind::Tent { radius: 1.0 }),
+            "gaussian" => Some(FilterKind::Gaussian { radius: 1.5, sigma: 0.5 }),
+            "mitchell" => Some(FilterKind::Mitchell { radius: 2.0, b: 1.0 / 3.0, c: 1.0 / 3.0 }),
+            "lanczos" => Some(FilterKind::Lanczos { radius: 2.0 }),
+            _ => None,
+        }
+    }
+
+    pub fn radius(&self) -> f64 {
+        match *self {
+            FilterKind::Box { radius }
+            | FilterKind::Tent { radius }
+            | FilterKind::Gaussian { radius, .. }
+            | FilterKind::Mitchell { radius, .. }
+            | FilterKind::Lanczos { radius } => radius,
+        }
+    }
+
+    pub fn create(&self) -> Box<dyn Filter> {
+        match *self {
+            FilterKind::Box { radius } => Box::new(BoxFilter::new(radius)),
+            FilterKind::Tent { radius } => Box::new(TentFilter::new(radius)),
+            FilterKind::Gaussian { radius, sigma } => Box::new(GaussianFilter::new(radius, sigma)),
+            FilterKind::Mitchell { radius, b, c } => Box::new(MitchellFilter::new(radius, b, c)),
+            FilterKind::Lanczos { radius } => Box::new(LanczosFilter::new(radius)),
+        }
+    }
+}
+
+impl Default for FilterKind {
+    fn default() -> Self {
+        FilterKind::Box { radius: 0.5 }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filters() -> Vec<(&'static str, Box<dyn Filter>)> {
+        FilterKind::NAMES.iter()
+            .map(|&name| (name, FilterKind::from_name(name).unwrap().create()))
+            .collect()
+    }
+
+    #[test]
+    fn box_filter_counts_samples_only_for_their_own_pixel() {
+        let filter = FilterKind::default().create();
+
+        assert_eq!(filter.radius(), 0.5);
+        for offset in [-0.5, -0.25, 0.0, 0.25, 0.499] {
+            assert_eq!(filter.weight(offset), 1.0, "offset {}", offset);
+        }
+        for offset in [-1.0, -0.501, 0.5, 1.0] {
+            assert_eq!(filter.weight(offset), 0.0, "offset {}", offset);
+        }
+    }
+
+    #[test]
+    fn weights_are_0_outside_of_the_radius() {
+        for (name, filter) in filters() {
+            let radius = filter.radius();
+            for offset in [radius + 1e-9, radius + 0.25, radius + 1.0, 2.0 * radius + 3.0] {
+                assert_eq!(filter.weight(offset), 0.0, "{} at {}", name, offset);
+                assert_eq!(filter.weight(-offset), 0.0, "{} at {}", name, -offset);
+            }
+        }
+    }
+
+    #[test]
+    fn weights_without_negative_lobes_are_never_negative() {
+        for (name, filter) in filters().into_iter().filter(|(name, _)| ["box", "tent", "gaussian"].contains(name)) {
+            let radius = filter.radius();
+            for step in 0..=100 {
+                let offset = radius * (step as f64 / 50.0 - 1.0);
+                assert!(filter.weight(offset) >= 0.0, "{} at {}", name, offset);
+            }
+            assert!(filter.weight(0.0) > 0.0, "{}", name);
+        }
+    }
+}
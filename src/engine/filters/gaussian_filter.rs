@@ -0,0 +1,35 @@
+use crate::engine::filters::Filter;
+
+/// A Gaussian with the given standard deviation (in pixels), lowered by its value at the
+/// radius, so it falls smoothly to 0 there instead of being cut off.
+pub struct GaussianFilter {
+    radius: f64,
+    sigma: f64,
+    value_at_radius: f64,
+}
+
+
+impl GaussianFilter {
+    pub fn new(radius: f64, sigma: f64) -> GaussianFilter {
+        GaussianFilter {
+            radius,
+            sigma,
+            value_at_radius: gaussian(radius, sigma),
+        }
+    }
+}
+
+
+impl Filter for GaussianFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn weight(&self, offset: f64) -> f64 {
+        (gaussian(offset, self.sigma) - self.value_at_radius).max(0.0)
+    }
+}
+
+fn gaussian(x: f64, sigma: f64) -> f64 {
+    (-x * x / (2.0 * sigma * sigma)).exp()
+}
@@ -0,0 +1,39 @@
+use crate::engine::filters::Filter;
+use std::f64::consts::PI;
+
+/// A sinc, which is the ideal low-pass filter, windowed by a wider sinc that reaches 0
+/// at the radius (which is the amount of lobes on each side).
+pub struct LanczosFilter {
+    radius: f64
+}
+
+
+impl LanczosFilter {
+    pub fn new(radius: f64) -> LanczosFilter {
+        LanczosFilter {radius}
+    }
+}
+
+
+impl Filter for LanczosFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn weight(&self, offset: f64) -> f64 {
+        if offset.abs() >= self.radius {
+            return 0.0;
+        }
+
+        sinc(offset) * sinc(offset / self.radius)
+    }
+}
+
+/// The normalized sinc, sin(pi x) / (pi x).
+fn sinc(x: f64) -> f64 {
+    if x.abs() < 1e-5 {
+        return 1.0;
+    }
+
+    (PI * x).sin() / (PI * x)
+}
@@ -0,0 +1,39 @@
+use crate::engine::filters::Filter;
+
+/// The Mitchell-Netravali filter: a piecewise cubic which is stretched to fit the radius.
+/// B = 1, C = 0 is the blurry cubic B-spline, B = 0, C = 0.5 is the sharp Catmull-Rom spline,
+/// and B = C = 1/3 is the compromise recommended by Mitchell and Netravali.
+pub struct MitchellFilter {
+    radius: f64,
+    b: f64,
+    c: f64,
+}
+
+
+impl MitchellFilter {
+    pub fn new(radius: f64, b: f64, c: f64) -> MitchellFilter {
+        MitchellFilter { radius, b, c }
+    }
+}
+
+
+impl Filter for MitchellFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn weight(&self, offset: f64) -> f64 {
+        let (b, c) = (self.b, self.c);
+        // The cubic is defined for distances up to 2
+        let x = 2.0 * offset.abs() / self.radius;
+
+        if x < 1.0 {
+            ((12.0 - 9.0 * b - 6.0 * c) * x.powi(3) + (-18.0 + 12.0 * b + 6.0 * c) * x.powi(2) + (6.0 - 2.0 * b)) / 6.0
+        } else if x < 2.0 {
+            ((-b - 6.0 * c) * x.powi(3) + (6.0 * b + 30.0 * c) * x.powi(2) + (-12.0 * b - 48.0 * c) * x
+                + (8.0 * b + 24.0 * c)) / 6.0
+        } else {
+            0.0
+        }
+    }
+}
@@ -0,0 +1,13 @@
+pub mod filter;
+pub mod box_filter;
+pub mod tent_filter;
+pub mod gaussian_filter;
+pub mod mitchell_filter;
+pub mod lanczos_filter;
+
+pub use filter::{Filter, FilterKind};
+pub use box_filter::BoxFilter;
+pub use tent_filter::TentFilter;
+pub use gaussian_filter::GaussianFilter;
+pub use mitchell_filter::MitchellFilter;
+pub use lanczos_filter::LanczosFilter;
@@ -0,0 +1,24 @@
+use crate::engine::filters::Filter;
+
+/// Weights samples by how close they are to the pixel's center, falling linearly to 0 at the radius.
+pub struct TentFilter {
+    radius: f64
+}
+
+
+impl TentFilter {
+    pub fn new(radius: f64) -> TentFilter {
+        TentFilter {radius}
+    }
+}
+
+
+impl Filter for TentFilter {
+    fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    fn weight(&self, offset: f64) -> f64 {
+        (self.radius - offset.abs()).max(0.0)
+    }
+}
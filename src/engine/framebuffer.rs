@@ -0,0 +1,270 @@
+use crate::utils::Image;
+use crate::vectors::Color;
+
+/// The relative error of pixels darker than this is measured against this luminance instead,
+/// otherwise nearly black pixels would never look converged.
+const MIN_ERROR_LUMINANCE: f64 = 0.01;
+
+/// The colors of the samples count heatmap, from the fewest samples to the most.
+/// They are given as displayed, and squared into linear colors since the image
+/// encoders apply gamma correction.
+const HEATMAP_COLORS: [(f64, f64, f64); 5] = [
+    (0.0, 0.0, 0.0),
+    (0.1, 0.1, 0.8),
+    (0.8, 0.1, 0.4),
+    (1.0, 0.8, 0.0),
+    (1.0, 1.0, 1.0),
+];
+
+/// The samples taken so far for a single pixel, with the running sums needed for
+/// estimating how noisy the pixel's average still is.
+#[derive(Copy, Clone, Debug)]
+pub struct PixelStatistics {
+    pub sum: Color,
+    pub count: usize,
+    luminance_sum: f64,
+    luminance_squared_sum: f64,
+}
+
+/// The filtered samples which landed on a pixel, both its own and its neighbours'
+/// (see `Filter`). The pixel's color is their weighted average.
+#[derive(Copy, Clone, Debug)]
+pub struct FilteredPixel {
+    pub weighted_sum: Color,
+    pub weight_sum: f64,
+}
+
+/// The film: accumulates the samples of every pixel across several passes, for progressive
+/// and adaptive rendering. Every pixel keeps the statistics of the samples taken inside of
+/// it, for estimating its noise, and separately the filtered samples which make up its color.
+/// Pixels are stored row by row, starting at the top-left corner.
+pub struct Framebuffer {
+    width: usize,
+    height: usize,
+    pixels: Vec<PixelStatistics>,
+    filtered_pixels: Vec<FilteredPixel>,
+}
+
+
+impl PixelStatistics {
+    pub fn new() -> PixelStatistics {
+        PixelStatistics {
+            sum: Color::zeroes(),
+            count: 0,
+            luminance_sum: 0.0,
+            luminance_squared_sum: 0.0,
+        }
+    }
+
+    pub fn add(&mut self, color: Color) {
+        let luminance = color.luminance();
+
+        self.sum = self.sum + color;
+        self.count += 1;
+        self.luminance_sum += luminance;
+        self.luminance_squared_sum += luminance * luminance;
+    }
+
+    /// The average of the samples, which is the color of the pixel.
+    pub fn mean(&self) -> Color {
+        if self.count == 0 {
+            return Color::zeroes();
+        }
+
+        self.sum / self.count as f64
+    }
+
+    /// The sample variance of the luminance of the samples.
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+
+        let count = self.count as f64;
+        let mean = self.luminance_sum / count;
+        ((self.luminance_squared_sum - count * mean * mean) / (count - 1.0)).max(0.0)
+    }
+
+    /// The estimated error of the pixel's luminance (the standard error of the mean),
+    /// relative to the luminance itself. Infinite before there are at least 2 samples.
+    pub fn relative_error(&self) -> f64 {
+        if self.count < 2 {
+            return f64::INFINITY;
+        }
+
+        let standard_error = (self.variance() / self.count as f64).sqrt();
+        let mean = self.luminance_sum / self.count as f64;
+        standard_error / mean.max(MIN_ERROR_LUMINANCE)
+    }
+}
+
+impl Default for PixelStatistics {
+    fn default() -> Self {
+        PixelStatistics::new()
+    }
+}
+
+impl FilteredPixel {
+    pub fn new() -> FilteredPixel {
+        FilteredPixel { weighted_sum: Color::zeroes(), weight_sum: 0.0 }
+    }
+
+    pub fn add(&mut self, color: Color, weight: f64) {
+        self.weighted_sum = self.weighted_sum + weight * color;
+        self.weight_sum += weight;
+    }
+
+    /// Add the samples which another part of the film collected for the same pixel.
+    pub fn merge(&mut self, other: &FilteredPixel) {
+        self.weighted_sum = self.weighted_sum + other.weighted_sum;
+        self.weight_sum += other.weight_sum;
+    }
+
+    /// The weighted average of the samples. Filters with negative lobes can make the sum of
+    /// the weights 0, in which case the pixel is black.
+    pub fn color(&self) -> Color {
+        if self.weight_sum == 0.0 {
+            return Color::zeroes();
+        }
+
+        self.weighted_sum / self.weight_sum
+    }
+}
+
+impl Default for FilteredPixel {
+    fn default() -> Self {
+        FilteredPixel::new()
+    }
+}
+
+impl Framebuffer {
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![PixelStatistics::new(); width * height],
+            filtered_pixels: vec![FilteredPixel::new(); width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> &PixelStatistics {
+        &self.pixels[self.get_index(x, y)]
+    }
+
+    pub fn pixel_mut(&mut self, x: usize, y: usize) -> &mut PixelStatistics {
+        let index = self.get_index(x, y);
+        &mut self.pixels[index]
+    }
+
+    pub fn filtered_pixel(&self, x: usize, y: usize) -> &FilteredPixel {
+        &self.filtered_pixels[self.get_index(x, y)]
+    }
+
+    pub fn filtered_pixel_mut(&mut self, x: usize, y: usize) -> &mut FilteredPixel {
+        let index = self.get_index(x, y);
+        &mut self.filtered_pixels[index]
+    }
+
+    /// All of the pixels, row by row from the top-left corner.
+    pub fn pixels(&self) -> &[PixelStatistics] {
+        &self.pixels
+    }
+
+    /// The total amount of samples taken for all of the pixels.
+    pub fn samples_count(&self) -> usize {
+        self.pixels.iter().map(|pixel| pixel.count).sum()
+    }
+
+    /// The image made of the filtered color of every pixel.
+    pub fn to_image(&self) -> Image {
+        Image::from_pixels(self.width, self.height, self.filtered_pixels.iter().map(FilteredPixel::color).collect())
+    }
+
+    /// An image of how many samples every pixel took, for debugging adaptive and progressive
+    /// rendering. The counts are relative to the largest one, so pixels without samples are black
+    /// and the pixels with the most samples are white.
+    pub fn samples_heatmap(&self) -> Image {
+        let max_count = self.pixels.iter().map(|pixel| pixel.count).max().unwrap_or(0).max(1);
+        let colors = self.pixels.iter()
+            .map(|pixel| heatmap_color(pixel.count as f64 / max_count as f64))
+            .collect();
+
+        Image::from_pixels(self.width, self.height, colors)
+    }
+
+    fn get_index(&self, x: usize, y: usize) -> usize {
+        if x >= self.width || y >= self.height {
+            panic!("Pixel ({}, {}) is out of the framebuffer bounds ({}x{}).", x, y, self.width, self.height);
+        }
+
+        y * self.width + x
+    }
+}
+
+/// Interpolate between the heatmap colors, for a value between 0 and 1.
+fn heatmap_color(value: f64) -> Color {
+    let position = value.clamp(0.0, 1.0) * (HEATMAP_COLORS.len() - 1) as f64;
+    let index = (position as usize).min(HEATMAP_COLORS.len() - 2);
+    let t = position - index as f64;
+
+    let to_linear = |(r, g, b): (f64, f64, f64)| Color::new(r * r, g * g, b * b);
+    (1.0 - t) * to_linear(HEATMAP_COLORS[index]) + t * to_linear(HEATMAP_COLORS[index + 1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pixel with gray samples, whose luminance is their value.
+    fn pixel(values: &[f64]) -> PixelStatistics {
+        let mut pixel = PixelStatistics::new();
+        for &value in values {
+            pixel.add(Color::new(value, value, value));
+        }
+        pixel
+    }
+
+    #[test]
+    fn error_is_unknown_before_2_samples() {
+        assert_eq!(pixel(&[]).variance(), f64::INFINITY);
+        assert_eq!(pixel(&[0.5]).variance(), f64::INFINITY);
+        assert_eq!(pixel(&[0.5]).relative_error(), f64::INFINITY);
+    }
+
+    #[test]
+    fn variance_and_relative_error_of_the_samples() {
+        // The mean is 0.5, so the variance is (0.3² + 0.1² + 0.4²) / 2 = 0.13,
+        // and the relative error is sqrt(0.13 / 3) / 0.5.
+        let pixel = pixel(&[0.2, 0.4, 0.9]);
+
+        assert!((pixel.mean().red - 0.5).abs() < 1e-12);
+        assert!((pixel.variance() - 0.13).abs() < 1e-12);
+        assert!((pixel.relative_error() - (0.13f64 / 3.0).sqrt() / 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn error_of_dark_pixels_is_relative_to_the_minimum_luminance() {
+        // The variance is 2 * 0.001² and the standard error 0.001, measured against 0.01
+        // instead of the mean of 0.002.
+        let pixel = pixel(&[0.001, 0.003]);
+
+        assert!((pixel.variance() - 2e-6).abs() < 1e-15);
+        assert!((pixel.relative_error() - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn identical_samples_have_no_error() {
+        let pixel = pixel(&[0.7; 5]);
+
+        assert!(pixel.variance() < 1e-12);
+        assert!(pixel.relative_error() < 1e-6);
+    }
+}
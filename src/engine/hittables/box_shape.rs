@@ -0,0 +1,143 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::hittables::hittable_collection::HittableCollection;
+use crate::engine::hittables::quad::Quad;
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::{Ray, Rng};
+use crate::vectors::{Point, Vector};
+use std::sync::Arc;
+
+/// An axis-aligned box between two opposite corners, made of six quads
+/// whose normals all point out of the box.
+pub struct BoxShape {
+    sides: HittableCollection<'static>,
+}
+
+
+impl BoxShape {
+    pub fn new(a: Point, b: Point, material: Arc<dyn Material>) -> BoxShape {
+        let minimum = a.min(b);
+        let maximum = a.max(b);
+
+        let dx = Vector::new(maximum.x - minimum.x, 0.0, 0.0);
+        let dy = Vector::new(0.0, maximum.y - minimum.y, 0.0);
+        let dz = Vector::new(0.0, 0.0, maximum.z - minimum.z);
+
+        let mut sides = HittableCollection::new();
+        // Front and back
+        sides.add(Box::new(Quad::new(Point::new(minimum.x, minimum.y, maximum.z), dx, dy, Arc::clone(&material))));
+        sides.add(Box::new(Quad::new(Point::new(maximum.x, minimum.y, minimum.z), -dx, dy, Arc::clone(&material))));
+        // Right and left
+        sides.add(Box::new(Quad::new(Point::new(maximum.x, minimum.y, maximum.z), -dz, dy, Arc::clone(&material))));
+        sides.add(Box::new(Quad::new(Point::new(minimum.x, minimum.y, minimum.z), dz, dy, Arc::clone(&material))));
+        // Top and bottom
+        sides.add(Box::new(Quad::new(Point::new(minimum.x, maximum.y, maximum.z), dx, -dz, Arc::clone(&material))));
+        sides.add(Box::new(Quad::new(Point::new(minimum.x, minimum.y, minimum.z), dx, dz, material)));
+
+        BoxShape { sides }
+    }
+}
+
+
+impl<'a> Hittable<'a> for BoxShape {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.sides.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        self.sides.bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        self.sides.random(origin, rng)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::materials::Lambertian;
+    use crate::vectors::Color;
+    use std::f64::consts::PI;
+
+    /// A box between (-1, -2, -3) and (1, 2, 3), given by corners in no particular order.
+    fn box_shape() -> BoxShape {
+        BoxShape::new(Point::new(1.0, -2.0, 3.0), Point::new(-1.0, 2.0, -3.0), Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))))
+    }
+
+    fn axes() -> [Vector; 6] {
+        [
+            Vector::new(1.0, 0.0, 0.0), Vector::new(-1.0, 0.0, 0.0),
+            Vector::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0),
+            Vector::new(0.0, 0.0, 1.0), Vector::new(0.0, 0.0, -1.0),
+        ]
+    }
+
+    #[test]
+    fn every_side_faces_out_of_the_box() {
+        let box_shape = box_shape();
+        for axis in axes() {
+            let ray = Ray::new(10.0 * axis, -axis);
+            let record = box_shape.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+            let half_size = (Vector::new(1.0, 2.0, 3.0) * axis).size();
+            assert!((record.t - (10.0 - half_size)).abs() < 1e-12);
+            assert!(record.front_face);
+            assert!((record.normal - axis).size() < 1e-12);
+
+            // The center of every side is in the middle of its surface coordinates.
+            assert!((record.uv.0 - 0.5).abs() < 1e-12 && (record.uv.1 - 0.5).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn rays_from_inside_hit_the_back_of_the_sides() {
+        let box_shape = box_shape();
+        for axis in axes() {
+            let record = box_shape.hit(&Ray::new(Point::zeroes(), axis), 0.0, f64::INFINITY).unwrap();
+
+            assert!(!record.front_face);
+            assert!((record.normal + axis).size() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn rays_beside_the_box_are_missed() {
+        let ray = Ray::new(Point::new(1.01, 0.0, 10.0), Vector::new(0.0, 0.0, -1.0));
+        assert!(box_shape().hit(&ray, 0.0, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn density_matches_the_sampled_directions() {
+        let box_shape = box_shape();
+        let origin = Point::new(2.0, 3.0, 5.0);
+        let rng = &mut Rng::new(6);
+
+        // The integral of the density over all directions, and the solid angle of the box.
+        let samples_count = 200_000;
+        let (mut density_sum, mut hits_count) = (0.0, 0);
+        for _ in 0..samples_count {
+            let pdf = box_shape.pdf_value(&origin, &Vector::random_unit_vector(rng));
+            density_sum += pdf;
+            hits_count += (pdf > 0.0) as usize;
+        }
+        let integral = 4.0 * PI * density_sum / samples_count as f64;
+        let solid_angle = 4.0 * PI * hits_count as f64 / samples_count as f64;
+        assert!((integral - 1.0).abs() < 0.03, "The density integrates to {}", integral);
+
+        // Every sampled direction hits the box, so the average of 1 / pdf over them is its solid angle.
+        let inverse_density_sum: f64 = (0..samples_count)
+            .map(|_| {
+                let direction = box_shape.random(&origin, rng).unwrap();
+                assert!(box_shape.hit(&Ray::new(origin, direction), 0.0, f64::INFINITY).is_some());
+                1.0 / box_shape.pdf_value(&origin, &direction)
+            })
+            .sum();
+        let sampled_solid_angle = inverse_density_sum / samples_count as f64;
+        assert!((sampled_solid_angle - solid_angle).abs() < 0.03 * solid_angle, "{} != {}", sampled_solid_angle, solid_angle);
+    }
+}
@@ -0,0 +1,323 @@
+use crate::engine::hittables::hittable::{Hittable, HitRecord};
+use crate::engine::hittables::hittable_collection::HittableCollection;
+use crate::engine::aabb::AABB;
+use crate::engine::Ray;
+
+/// Number of buckets the centroids are binned into when evaluating
+/// the surface area heuristic.
+const SAH_BUCKETS_COUNT: usize = 12;
+
+/// Under this amount of objects, splitting by the median is good enough
+/// and evaluating the surface area heuristic isn't worth it.
+const SAH_MIN_OBJECTS_COUNT: usize = 4;
+
+/// Determines how the objects are divided between the two children of each node.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BvhSplitMethod {
+    /// Sort the objects along the longest axis and split them into two equal halves.
+    Median,
+
+    /// Choose the split which minimizes the expected cost of intersecting a ray
+    /// with the node, estimated by the surface area of its children.
+    SurfaceAreaHeuristic,
+}
+
+/// A node in a bounding volume hierarchy.
+/// Each node bounds its children, so a ray which misses the node's box can
+/// skip all of the objects inside it, instead of testing each one of them.
+pub struct BvhNode<'a> {
+    left: Box<dyn Hittable<'a> + 'a>,
+    right: Option<Box<dyn Hittable<'a> + 'a>>,
+    bounding_box: AABB,
+}
+
+/// An object which is waiting to be placed in the hierarchy, along with its bounds.
+struct BvhPrimitive<'a> {
+    hittable: Box<dyn Hittable<'a> + 'a>,
+    bounding_box: AABB,
+    centroid_axis_value: f64,
+}
+
+
+impl<'a> BvhNode<'a> {
+    /// Build a hierarchy out of the collection's objects, using the surface area heuristic.
+    /// Returns None if the collection is empty, or if one of its objects has no bounding box.
+    pub fn new(collection: HittableCollection<'a>) -> Option<BvhNode<'a>> {
+        BvhNode::with_split_method(collection, BvhSplitMethod::SurfaceAreaHeuristic)
+    }
+
+    pub fn with_split_method(collection: HittableCollection<'a>, split_method: BvhSplitMethod) -> Option<BvhNode<'a>> {
+        let mut primitives = Vec::with_capacity(collection.len());
+        for hittable in collection.into_vec() {
+            let bounding_box = hittable.bounding_box()?;
+            primitives.push(BvhPrimitive { hittable, bounding_box, centroid_axis_value: 0.0 });
+        }
+
+        BvhNode::from_primitives(primitives, split_method)
+    }
+
+    /// Build a hierarchy out of the objects of the collection which have a bounding box. The
+    /// objects which don't (for example an empty mesh) can't be placed in the hierarchy, so
+    /// they're kept next to it in a plain collection, which is tested against every ray.
+    pub fn with_unbounded_objects(collection: HittableCollection<'a>) -> HittableCollection<'a> {
+        let mut primitives = Vec::with_capacity(collection.len());
+        let mut result = HittableCollection::new();
+        for hittable in collection.into_vec() {
+            match hittable.bounding_box() {
+                Some(bounding_box) => primitives.push(BvhPrimitive { hittable, bounding_box, centroid_axis_value: 0.0 }),
+                None => result.add(hittable)
+            }
+        }
+
+        if let Some(node) = BvhNode::from_primitives(primitives, BvhSplitMethod::SurfaceAreaHeuristic) {
+            result.add(Box::new(node));
+        }
+
+        result
+    }
+
+    fn from_primitives(mut primitives: Vec<BvhPrimitive<'a>>, split_method: BvhSplitMethod) -> Option<BvhNode<'a>> {
+        match primitives.len() {
+            0 => None,
+            1 => {
+                let primitive = primitives.pop().unwrap();
+                Some(BvhNode {
+                    left: primitive.hittable,
+                    right: None,
+                    bounding_box: primitive.bounding_box,
+                })
+            }
+            _ => Some(BvhNode::build_node(primitives, split_method))
+        }
+    }
+
+    /// Build a subtree out of the given primitives. Single objects are placed in the
+    /// tree as is, without wrapping them with a node.
+    fn build(mut primitives: Vec<BvhPrimitive<'a>>, split_method: BvhSplitMethod) -> Box<dyn Hittable<'a> + 'a> {
+        if primitives.len() == 1 {
+            return primitives.pop().unwrap().hittable;
+        }
+
+        Box::new(BvhNode::build_node(primitives, split_method))
+    }
+
+    fn build_node(mut primitives: Vec<BvhPrimitive<'a>>, split_method: BvhSplitMethod) -> BvhNode<'a> {
+        let bounding_box = primitives.iter()
+            .map(|primitive| primitive.bounding_box)
+            .reduce(AABB::surrounding_box)
+            .unwrap();
+
+        let centroid_bounds = primitives.iter()
+            .map(|primitive| primitive.bounding_box.centroid())
+            .fold(AABB::new(primitives[0].bounding_box.centroid(), primitives[0].bounding_box.centroid()),
+                  AABB::surrounding_point);
+
+        let axis = centroid_bounds.longest_axis();
+        for primitive in primitives.iter_mut() {
+            primitive.centroid_axis_value = primitive.bounding_box.centroid()[axis];
+        }
+        primitives.sort_by(|a, b| a.centroid_axis_value.total_cmp(&b.centroid_axis_value));
+
+        let mut split_index = primitives.len() / 2;
+        if split_method == BvhSplitMethod::SurfaceAreaHeuristic && primitives.len() > SAH_MIN_OBJECTS_COUNT {
+            let axis_min = centroid_bounds.minimum[axis];
+            let axis_extent = centroid_bounds.maximum[axis] - axis_min;
+
+            // All centroids are at the same spot, there's nothing better than the median.
+            if axis_extent > 0.0 {
+                split_index = BvhNode::find_sah_split(&primitives, axis_min, axis_extent);
+            }
+        }
+
+        let right_primitives = primitives.split_off(split_index);
+
+        BvhNode {
+            left: BvhNode::build(primitives, split_method),
+            right: Some(BvhNode::build(right_primitives, split_method)),
+            bounding_box,
+        }
+    }
+
+    /// Bin the (sorted) primitives into buckets along the split axis, and find the bucket
+    /// boundary with the lowest estimated cost. Returns the index to split the primitives at.
+    fn find_sah_split(primitives: &[BvhPrimitive<'a>], axis_min: f64, axis_extent: f64) -> usize {
+        let bucket_of = |primitive: &BvhPrimitive<'a>| {
+            let relative_position = (primitive.centroid_axis_value - axis_min) / axis_extent;
+            ((relative_position * SAH_BUCKETS_COUNT as f64) as usize).min(SAH_BUCKETS_COUNT - 1)
+        };
+
+        let mut bucket_counts = [0usize; SAH_BUCKETS_COUNT];
+        let mut bucket_boxes: [Option<AABB>; SAH_BUCKETS_COUNT] = [None; SAH_BUCKETS_COUNT];
+        for primitive in primitives {
+            let bucket = bucket_of(primitive);
+            bucket_counts[bucket] += 1;
+            bucket_boxes[bucket] = Some(match bucket_boxes[bucket] {
+                Some(bucket_box) => AABB::surrounding_box(bucket_box, primitive.bounding_box),
+                None => primitive.bounding_box
+            });
+        }
+
+        // The cost of splitting after bucket i is proportional to
+        // area(left) * count(left) + area(right) * count(right).
+        let mut best_cost = f64::INFINITY;
+        let mut best_split_index = primitives.len() / 2;
+        let mut left_count = 0;
+
+        for split_bucket in 0..(SAH_BUCKETS_COUNT - 1) {
+            left_count += bucket_counts[split_bucket];
+            let right_count = primitives.len() - left_count;
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            let left_box = bucket_boxes[..=split_bucket].iter().flatten().copied().reduce(AABB::surrounding_box);
+            let right_box = bucket_boxes[(split_bucket + 1)..].iter().flatten().copied().reduce(AABB::surrounding_box);
+
+            if let (Some(left_box), Some(right_box)) = (left_box, right_box) {
+                let cost = left_box.surface_area() * left_count as f64
+                    + right_box.surface_area() * right_count as f64;
+
+                if cost < best_cost {
+                    best_cost = cost;
+                    best_split_index = left_count;
+                }
+            }
+        }
+
+        best_split_index
+    }
+}
+
+
+impl<'a> Hittable<'a> for BvhNode<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        if !self.bounding_box.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        let left_hit = self.left.hit(ray, t_min, t_max);
+
+        // Anything behind the left hit is hidden by it, so there's no need to look for it.
+        let closest_distance = left_hit.as_ref().map_or(t_max, |record| record.t);
+        let right_hit = match &self.right {
+            Some(right) => right.hit(ray, t_min, closest_distance),
+            None => None
+        };
+
+        right_hit.or(left_hit)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        Some(self.bounding_box)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::{Sphere, Triangle};
+    use crate::engine::materials::{Lambertian, Material};
+    use crate::engine::Rng;
+    use crate::vectors::{Color, Point, Vector};
+    use std::sync::Arc;
+
+    /// The plane y = 0, which has no bounding box.
+    struct Plane {
+        material: Arc<dyn Material>,
+    }
+
+    impl<'a> Hittable<'a> for Plane {
+        fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+            let t = -ray.origin.y / ray.direction.y;
+            if !(t_min..t_max).contains(&t) {
+                return None;
+            }
+
+            Some(HitRecord::from_ray(ray, t, Vector::new(0.0, 1.0, 0.0), Arc::clone(&self.material)))
+        }
+
+        fn bounding_box(&self) -> Option<AABB> {
+            None
+        }
+    }
+
+    fn materials(count: usize) -> Vec<Arc<dyn Material>> {
+        (0..count).map(|_| Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))) as Arc<dyn Material>).collect()
+    }
+
+    /// Every object has its own material, which tells which object was hit.
+    fn material_id(material: &Arc<dyn Material>) -> usize {
+        Arc::as_ptr(material) as *const () as usize
+    }
+
+    /// The same random spheres and triangles every time, including overlapping ones.
+    fn random_objects<'a>(materials: &[Arc<dyn Material>]) -> HittableCollection<'a> {
+        let rng = &mut Rng::new(5);
+        let mut collection = HittableCollection::new();
+        for (index, material) in materials.iter().enumerate() {
+            let v0 = Point::random(rng, -10.0, 10.0);
+            if index % 2 == 0 {
+                collection.add(Box::new(Sphere::new(v0, rng.range(0.1, 1.5), Arc::clone(material))));
+            } else {
+                let (v1, v2) = (v0 + Vector::random(rng, -2.0, 2.0), v0 + Vector::random(rng, -2.0, 2.0));
+                collection.add(Box::new(Triangle::new(v0, v1, v2, Arc::clone(material))));
+            }
+        }
+
+        collection
+    }
+
+    fn random_rays(count: usize) -> Vec<Ray> {
+        let rng = &mut Rng::new(6);
+        (0..count)
+            .map(|_| Ray::new(Point::random(rng, -12.0, 12.0), Vector::random(rng, -1.0, 1.0)))
+            .collect()
+    }
+
+    fn nearest_hit<'a, H: Hittable<'a>>(hittable: &H, ray: &Ray) -> Option<(f64, usize)> {
+        hittable.hit(ray, 0.001, f64::INFINITY).map(|record| (record.t, material_id(&record.material)))
+    }
+
+    #[test]
+    fn hits_match_a_linear_scan() {
+        let materials = materials(120);
+        let linear = random_objects(&materials);
+        let rays = random_rays(5000);
+
+        for split_method in [BvhSplitMethod::Median, BvhSplitMethod::SurfaceAreaHeuristic] {
+            let bvh = BvhNode::with_split_method(random_objects(&materials), split_method).unwrap();
+
+            let mut hits_count = 0;
+            for ray in &rays {
+                let expected = nearest_hit(&linear, ray);
+                assert_eq!(nearest_hit(&bvh, ray), expected);
+                hits_count += expected.is_some() as usize;
+            }
+            assert!(hits_count > 500, "Only {} rays hit anything", hits_count);
+        }
+    }
+
+    #[test]
+    fn unbounded_objects_are_kept_next_to_the_hierarchy() {
+        let materials = materials(3);
+        let plane = || Box::new(Plane { material: Arc::clone(&materials[0]) });
+
+        assert!(BvhNode::new(HittableCollection::new()).is_none());
+        let mut unbounded = HittableCollection::new();
+        unbounded.add(plane());
+        assert!(BvhNode::new(unbounded).is_none());
+
+        let mut world = HittableCollection::new();
+        world.add(plane());
+        world.add(Box::new(Sphere::new(Point::new(0.0, 2.0, 0.0), 1.0, Arc::clone(&materials[1]))));
+        world.add(Box::new(Sphere::new(Point::new(5.0, 2.0, 0.0), 1.0, Arc::clone(&materials[2]))));
+        let world = BvhNode::with_unbounded_objects(world);
+        // The plane, and a node with both spheres.
+        assert_eq!(world.len(), 2);
+
+        let down = |x: f64| Ray::new(Point::new(x, 10.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(nearest_hit(&world, &down(0.0)), Some((7.0, material_id(&materials[1]))));
+        assert_eq!(nearest_hit(&world, &down(5.0)), Some((7.0, material_id(&materials[2]))));
+        assert_eq!(nearest_hit(&world, &down(-20.0)), Some((10.0, material_id(&materials[0]))));
+    }
+}
@@ -0,0 +1,114 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::{Ray, Rng};
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::vectors::Vector;
+use std::sync::Arc;
+
+/// A volume with the same density everywhere, such as fog or smoke, which fills a boundary object.
+/// A ray travelling through the volume is scattered at a random distance, which is exponentially
+/// distributed, so the denser the volume, the sooner rays scatter. Rays which scatter hit the
+/// volume with its phase function as the material (usually `Isotropic`).
+///
+/// The boundary must be convex, since a ray is assumed to leave it right after the first
+/// time it exits.
+pub struct ConstantMedium<H> {
+    boundary: H,
+    negative_inverse_density: f64,
+    phase_function: Arc<dyn Material>
+}
+
+
+impl<H> ConstantMedium<H> {
+    /// # Panics
+    ///
+    /// If the density isn't a positive finite number, since no scattering distance can be sampled for it.
+    pub fn new(boundary: H, density: f64, phase_function: Arc<dyn Material>) -> ConstantMedium<H> {
+        if !(density > 0.0 && density.is_finite()) {
+            panic!("The density of a medium must be positive and finite, got {}.", density);
+        }
+
+        ConstantMedium {
+            boundary,
+            negative_inverse_density: -1.0 / density,
+            phase_function
+        }
+    }
+
+    /// Sample the distance a ray travels through the volume before scattering. `hit` isn't
+    /// given a generator, so the ray itself is hashed into one: every ray gets its own
+    /// distance, and it's the same each time the ray is tested.
+    fn scattering_distance(&self, ray: &Ray) -> f64 {
+        let mut rng = Rng::from_hash(&[
+            ray.origin.x.to_bits(), ray.origin.y.to_bits(), ray.origin.z.to_bits(),
+            ray.direction.x.to_bits(), ray.direction.y.to_bits(), ray.direction.z.to_bits(),
+            ray.time.to_bits(),
+        ]);
+
+        // 1 - float() is in (0, 1], so the logarithm is finite
+        self.negative_inverse_density * (1.0 - rng.float()).ln()
+    }
+}
+
+
+impl<'a, H: Hittable<'a>> Hittable<'a> for ConstantMedium<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        // Find where the ray enters and exits the boundary, even if it starts inside of it
+        let entry = self.boundary.hit(ray, f64::NEG_INFINITY, f64::INFINITY)?;
+        let exit = self.boundary.hit(ray, entry.t + HIT_DISTANCE_THRESHOLD, f64::INFINITY)?;
+
+        let entry_t = entry.t.max(t_min).max(0.0);
+        let exit_t = exit.t.min(t_max);
+        if entry_t >= exit_t {
+            return None;
+        }
+
+        let ray_length = ray.direction.size();
+        let distance_inside = (exit_t - entry_t) * ray_length;
+        let hit_distance = self.scattering_distance(ray);
+        if hit_distance > distance_inside {
+            return None;
+        }
+
+        let t = entry_t + hit_distance / ray_length;
+        // The normal and the face are arbitrary, since a volume has no surface
+        Some(HitRecord::new(ray.at(t), Vector::new(1.0, 0.0, 0.0), Arc::clone(&self.phase_function), t, true))
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::Sphere;
+    use crate::engine::materials::Isotropic;
+    use crate::vectors::{Color, Point};
+
+    fn medium(density: f64) -> ConstantMedium<Sphere> {
+        let phase_function: Arc<dyn Material> = Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0)));
+        ConstantMedium::new(Sphere::new(Point::zeroes(), 1.0, Arc::clone(&phase_function)), density, phase_function)
+    }
+
+    #[test]
+    fn rays_scatter_inside_of_the_boundary() {
+        let medium = medium(1000.0);
+        let record = medium.hit(&Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 2.0)), 0.0, f64::INFINITY).unwrap();
+        assert!(record.t.is_finite() && (2.0..2.01).contains(&record.t));
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive and finite")]
+    fn zero_density_is_rejected() {
+        medium(0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "must be positive and finite")]
+    fn infinite_density_is_rejected() {
+        medium(f64::INFINITY);
+    }
+}
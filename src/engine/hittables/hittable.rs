@@ -1,34 +1,133 @@
 use crate::vectors::{Point, Vector};
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
+use crate::engine::aabb::AABB;
 use crate::engine::materials::material::Material;
-use std::rc::Rc;
+use std::sync::Arc;
 
 /// Includes information about where a ray hits a hittable object.
 pub struct HitRecord {
     pub point: Point,
+
+    /// The normal used for shading, which always points against the ray.
     pub normal: Vector,
-    pub material: Rc<Box<dyn Material>>,
+    pub material: Arc<dyn Material>,
     pub t: f64,
-    pub front_face: bool
+    pub front_face: bool,
+
+    /// The surface coordinates of the hit point, used for sampling textures.
+    pub uv: (f64, f64),
+
+    /// Where on the triangle the hit is, when the hit object is a triangle.
+    pub barycentric: Option<BarycentricCoordinates>
+}
+
+/// Barycentric coordinates of a point on a triangle (v0, v1, v2), such that
+/// point = w * v0 + u * v1 + v * v2, where w = 1 - u - v.
+#[derive(Copy, Clone, Debug)]
+pub struct BarycentricCoordinates {
+    pub u: f64,
+    pub v: f64
+}
+
+
+impl BarycentricCoordinates {
+    pub fn new(u: f64, v: f64) -> BarycentricCoordinates {
+        BarycentricCoordinates {u, v}
+    }
+
+    /// The weight of the first vertex.
+    pub fn w(&self) -> f64 {
+        1.0 - self.u - self.v
+    }
+
+    /// Interpolate values given per vertex, such as normals, at the point.
+    pub fn interpolate(&self, values: [Vector; 3]) -> Vector {
+        self.w() * values[0] + self.u * values[1] + self.v * values[2]
+    }
+
+    /// Interpolate surface coordinates given per vertex.
+    pub fn interpolate_uv(&self, uvs: [(f64, f64); 3]) -> (f64, f64) {
+        (
+            self.w() * uvs[0].0 + self.u * uvs[1].0 + self.v * uvs[2].0,
+            self.w() * uvs[0].1 + self.u * uvs[1].1 + self.v * uvs[2].1,
+        )
+    }
 }
 
 
 impl HitRecord {
-    pub fn new(point: Point, normal: Vector, material: Rc<Box<dyn Material>>, t: f64, front_face: bool) -> HitRecord {
-        HitRecord {point, normal, material, t, front_face}
+    pub fn new(point: Point, normal: Vector, material: Arc<dyn Material>, t: f64, front_face: bool) -> HitRecord {
+        HitRecord {point, normal, material, t, front_face, uv: (0.0, 0.0), barycentric: None}
     }
 
-    pub fn from_ray(ray: &Ray,  t: f64, outward_normal: Vector, material: Rc<Box<dyn Material>>) -> HitRecord {
+    pub fn from_ray(ray: &Ray,  t: f64, outward_normal: Vector, material: Arc<dyn Material>) -> HitRecord {
         let front_face = ray.direction.dot(outward_normal) < 0.0;
         let normal = if front_face { outward_normal } else { -outward_normal };
         let hit_point = ray.at(t);
 
         HitRecord::new(hit_point, normal, material, t, front_face)
-   }
+    }
+
+    pub fn with_uv(mut self, uv: (f64, f64)) -> HitRecord {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_barycentric(mut self, barycentric: BarycentricCoordinates) -> HitRecord {
+        self.barycentric = Some(barycentric);
+        self
+    }
+
+    /// Shade with a different normal than the geometric one, for example an interpolated
+    /// vertex normal. Which face was hit is still determined by the geometric normal.
+    pub fn with_shading_normal(mut self, outward_normal: Vector) -> HitRecord {
+        self.normal = if self.front_face { outward_normal } else { -outward_normal };
+        self
+    }
 }
 
 /// Should be implemented by types which represent hittable objects.
 /// For example: sphere, qube, etc...
-pub trait Hittable<'a> {
-    fn hit<'b, 'c>(self: &'b Self, ray: &'c Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
-}
\ No newline at end of file
+/// The world is shared between the rendering threads, so hittables must be
+/// safe to send and share across threads.
+pub trait Hittable<'a>: Send + Sync {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+
+    /// Get a box which bounds the whole object, or None if the object
+    /// has no bounding box (for example an infinite plane or an empty collection).
+    fn bounding_box(&self) -> Option<AABB>;
+
+    /// The probability density (over solid angle) of `random` generating the given
+    /// direction from the origin. Objects which can't be sampled directly, and
+    /// directions which miss the object, have a density of 0.
+    fn pdf_value(&self, _origin: &Point, _direction: &Vector) -> f64 {
+        0.0
+    }
+
+    /// Generate a random direction from the origin towards the object, used for
+    /// sampling lights directly. The direction doesn't have to be a unit vector.
+    /// None for objects which can't be sampled directly.
+    fn random(&self, _origin: &Point, _rng: &mut Rng) -> Option<Vector> {
+        None
+    }
+}
+
+/// Shared objects are hittable too, which allows placing the same object in the
+/// world several times, for example with different transforms.
+impl<'a, H: Hittable<'a> + ?Sized> Hittable<'a> for Arc<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        (**self).hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        (**self).bounding_box()
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        (**self).pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        (**self).random(origin, rng)
+    }
+}
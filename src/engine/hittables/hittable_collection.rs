@@ -1,8 +1,10 @@
 use crate::engine::hittables::hittable::{Hittable, HitRecord};
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
+use crate::vectors::{Point, Vector};
+use crate::engine::aabb::AABB;
 
 pub struct HittableCollection<'a> {
-    hittable_list: Vec<Box<dyn Hittable<'a>>>
+    hittable_list: Vec<Box<dyn Hittable<'a> + 'a>>
 }
 
 
@@ -13,13 +15,33 @@ impl<'a> HittableCollection<'a> {
         }
     }
 
-    pub fn add(&mut self, hittable: Box<dyn Hittable<'a>>) {
+    pub fn add(&mut self, hittable: Box<dyn Hittable<'a> + 'a>) {
         self.hittable_list.push(hittable);
     }
 
     pub fn clear(&mut self) {
         self.hittable_list.clear();
     }
+
+    pub fn len(&self) -> usize {
+        self.hittable_list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hittable_list.is_empty()
+    }
+
+    /// Take the hittables out of the collection, for example in order to
+    /// build a bounding volume hierarchy out of them.
+    pub fn into_vec(self) -> Vec<Box<dyn Hittable<'a> + 'a>> {
+        self.hittable_list
+    }
+}
+
+impl<'a> Default for HittableCollection<'a> {
+    fn default() -> Self {
+        HittableCollection::new()
+    }
 }
 
 impl<'a> Hittable<'a> for HittableCollection<'a> {
@@ -28,16 +50,48 @@ impl<'a> Hittable<'a> for HittableCollection<'a> {
         let mut result = None;
 
         for hittable in &self.hittable_list {
-            let hit_result = hittable.hit(ray, t_min, smallest_distance);
-            match hit_result {
-                Some(hit_record) => {
-                    smallest_distance = hit_record.t;
-                    result = Some(hit_record);
-                },
-                _ => {}
+            if let Some(hit_record) = hittable.hit(ray, t_min, smallest_distance) {
+                smallest_distance = hit_record.t;
+                result = Some(hit_record);
+            }
+        }
+
+        result
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        let mut result: Option<AABB> = None;
+
+        for hittable in &self.hittable_list {
+            let bounding_box = hittable.bounding_box()?;
+            result = match result {
+                Some(result) => Some(AABB::surrounding_box(result, bounding_box)),
+                None => Some(bounding_box)
             };
         }
 
         result
     }
+
+    /// Every object is picked with the same probability, so the density is the average of their densities.
+    /// Objects which can't be sampled have a density of 0, and the samples which pick them are lost.
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        if self.hittable_list.is_empty() {
+            return 0.0;
+        }
+
+        let total: f64 = self.hittable_list.iter()
+            .map(|hittable| hittable.pdf_value(origin, direction))
+            .sum();
+        total / self.hittable_list.len() as f64
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        if self.hittable_list.is_empty() {
+            return None;
+        }
+
+        let index = ((rng.float() * self.hittable_list.len() as f64) as usize).min(self.hittable_list.len() - 1);
+        self.hittable_list[index].random(origin, rng)
+    }
 }
\ No newline at end of file
@@ -1,7 +1,23 @@
 pub mod hittable;
 pub mod sphere;
+pub mod moving_sphere;
 pub mod hittable_collection;
+pub mod bvh_node;
+pub mod triangle;
+pub mod triangle_mesh;
+pub mod quad;
+pub mod box_shape;
+pub mod transformed;
+pub mod constant_medium;
 
-pub use hittable::{Hittable, HitRecord};
+pub use hittable::{Hittable, HitRecord, BarycentricCoordinates};
 pub use sphere::Sphere;
-pub use hittable_collection::HittableCollection;
\ No newline at end of file
+pub use moving_sphere::MovingSphere;
+pub use hittable_collection::HittableCollection;
+pub use bvh_node::{BvhNode, BvhSplitMethod};
+pub use triangle::Triangle;
+pub use triangle_mesh::{MeshBuffers, MeshFace, TriangleMesh};
+pub use quad::Quad;
+pub use box_shape::BoxShape;
+pub use transformed::Transformed;
+pub use constant_medium::ConstantMedium;
@@ -0,0 +1,65 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::hittables::sphere::Sphere;
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::Ray;
+use crate::vectors::{Point, Vector};
+use std::sync::Arc;
+
+/// A sphere which moves in a straight line, from `center0` at `time0` to `center1` at `time1`.
+/// Rays hit the sphere where it is at the ray's time, so it's blurred along its path when
+/// the camera's shutter stays open while it moves.
+pub struct MovingSphere {
+    center0: Point,
+    center1: Point,
+    time0: f64,
+    time1: f64,
+    radius: f64,
+    material: Arc<dyn Material>
+}
+
+
+impl MovingSphere {
+    pub fn new(center0: Point, center1: Point, time0: f64, time1: f64, radius: f64, material: Arc<dyn Material>) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material
+        }
+    }
+
+    /// The center at the given time. Before time0 and after time1 the sphere stays at the ends of its path.
+    pub fn center(&self, time: f64) -> Point {
+        if self.time1 <= self.time0 {
+            return self.center0;
+        }
+
+        let progress = ((time - self.time0) / (self.time1 - self.time0)).clamp(0.0, 1.0);
+        self.center0 + progress * (self.center1 - self.center0)
+    }
+}
+
+
+impl<'a> Hittable<'a> for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let center = self.center(ray.time);
+        let root = Sphere::intersect(&center, self.radius, ray, t_min, t_max)?;
+
+        let normal = (ray.at(root) - center) / self.radius;
+        let record = HitRecord::from_ray(ray, root, normal, Arc::clone(&self.material))
+            .with_uv(Sphere::get_uv(&normal));
+        Some(record)
+    }
+
+    /// The box around the sphere at both ends of its path.
+    fn bounding_box(&self) -> Option<AABB> {
+        let radius_vector = Vector::new(self.radius, self.radius, self.radius);
+        let box0 = AABB::new(self.center0 - radius_vector, self.center0 + radius_vector);
+        let box1 = AABB::new(self.center1 - radius_vector, self.center1 + radius_vector);
+
+        Some(AABB::surrounding_box(box0, box1))
+    }
+}
@@ -0,0 +1,228 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::{Ray, Rng};
+use crate::consts::{HIT_DISTANCE_THRESHOLD, NEAR_ZERO_THRESHOLD};
+use crate::vectors::{Point, Vector};
+use std::sync::Arc;
+
+/// A parallelogram spanned by two edges from a corner: every point on it is
+/// corner + alpha * u + beta * v, where alpha and beta are between 0 and 1.
+/// The front face is the one the normal u x v points out of.
+pub struct Quad {
+    corner: Point,
+    u: Vector,
+    v: Vector,
+    material: Arc<dyn Material>,
+
+    /// The unit normal of the quad's plane, which is every point p with normal . p = d.
+    normal: Vector,
+    d: f64,
+
+    /// (u x v) / |u x v|^2, used for finding the planar coordinates of a hit point.
+    w: Vector,
+    area: f64,
+}
+
+
+impl Quad {
+    pub fn new(corner: Point, u: Vector, v: Vector, material: Arc<dyn Material>) -> Quad {
+        let n = u.cross(v);
+        let normal = n.unit();
+
+        Quad {
+            corner,
+            u,
+            v,
+            material,
+            normal,
+            d: normal.dot(corner),
+            w: n / n.size_squared(),
+            area: n.size(),
+        }
+    }
+
+    /// A rectangle on the plane z = k, facing +Z.
+    pub fn xy_rect(x0: f64, x1: f64, y0: f64, y1: f64, k: f64, material: Arc<dyn Material>) -> Quad {
+        Quad::new(
+            Point::new(x0, y0, k),
+            Vector::new(x1 - x0, 0.0, 0.0),
+            Vector::new(0.0, y1 - y0, 0.0),
+            material,
+        )
+    }
+
+    /// A rectangle on the plane y = k, facing +Y.
+    /// The v texture coordinate goes from z1 to z0, so that the normal points upwards.
+    pub fn xz_rect(x0: f64, x1: f64, z0: f64, z1: f64, k: f64, material: Arc<dyn Material>) -> Quad {
+        Quad::new(
+            Point::new(x0, k, z1),
+            Vector::new(x1 - x0, 0.0, 0.0),
+            Vector::new(0.0, 0.0, z0 - z1),
+            material,
+        )
+    }
+
+    /// A rectangle on the plane x = k, facing +X.
+    pub fn yz_rect(y0: f64, y1: f64, z0: f64, z1: f64, k: f64, material: Arc<dyn Material>) -> Quad {
+        Quad::new(
+            Point::new(k, y0, z0),
+            Vector::new(0.0, y1 - y0, 0.0),
+            Vector::new(0.0, 0.0, z1 - z0),
+            material,
+        )
+    }
+
+    /// Find the distance along the ray to the quad, and the planar coordinates (alpha, beta) of the hit point.
+    fn intersect(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, (f64, f64))> {
+        let denominator = self.normal.dot(ray.direction);
+
+        // The ray is parallel to the quad
+        if denominator.abs() < NEAR_ZERO_THRESHOLD {
+            return None;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin)) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let planar_hit_point = ray.at(t) - self.corner;
+        let alpha = self.w.dot(planar_hit_point.cross(self.v));
+        let beta = self.w.dot(self.u.cross(planar_hit_point));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return None;
+        }
+
+        Some((t, (alpha, beta)))
+    }
+}
+
+
+impl<'a> Hittable<'a> for Quad {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, uv) = self.intersect(ray, t_min, t_max)?;
+
+        Some(HitRecord::from_ray(ray, t, self.normal, Arc::clone(&self.material)).with_uv(uv))
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        let bounding_box = AABB::new(self.corner, self.corner);
+        let bounding_box = AABB::surrounding_point(bounding_box, self.corner + self.u);
+        let bounding_box = AABB::surrounding_point(bounding_box, self.corner + self.v);
+        let bounding_box = AABB::surrounding_point(bounding_box, self.corner + self.u + self.v);
+
+        Some(bounding_box.padded())
+    }
+
+    /// Convert the density of picking a point uniformly over the area into a density over solid angle.
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        let ray = Ray::new(*origin, *direction);
+        let (t, _) = match self.intersect(&ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+            Some(hit) => hit,
+            None => return 0.0
+        };
+
+        let distance_squared = t * t * direction.size_squared();
+        let cosine = (direction.dot(self.normal) / direction.size()).abs();
+
+        distance_squared / (cosine * self.area)
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        Some(self.corner + rng.float() * self.u + rng.float() * self.v - *origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::materials::Lambertian;
+    use crate::vectors::Color;
+    use std::f64::consts::PI;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    /// A 2 by 1 rectangle on the plane z = -1, facing +Z.
+    fn rectangle() -> Quad {
+        Quad::new(Point::new(-1.0, -0.5, -1.0), Vector::new(2.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0), material())
+    }
+
+    #[test]
+    fn hits_have_the_planar_coordinates_as_uv() {
+        let ray = Ray::new(Point::new(0.5, 0.25, 1.0), Vector::new(0.0, 0.0, -1.0));
+        let record = rectangle().hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+        assert!((record.t - 2.0).abs() < 1e-12);
+        assert!((record.uv.0 - 0.75).abs() < 1e-12 && (record.uv.1 - 0.75).abs() < 1e-12);
+        assert!((record.point.x - 0.5).abs() < 1e-12 && (record.point.y - 0.25).abs() < 1e-12 && (record.point.z + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn rays_outside_of_the_edges_or_parallel_to_the_quad_are_missed() {
+        let quad = rectangle();
+        for origin in [Point::new(1.01, 0.0, 1.0), Point::new(0.0, -0.51, 1.0), Point::new(-1.5, 0.6, 1.0)] {
+            assert!(quad.hit(&Ray::new(origin, Vector::new(0.0, 0.0, -1.0)), 0.0, f64::INFINITY).is_none());
+        }
+        assert!(quad.hit(&Ray::new(Point::new(-2.0, 0.0, -1.0), Vector::new(1.0, 0.0, 0.0)), 0.0, f64::INFINITY).is_none());
+        assert!(quad.hit(&Ray::new(Point::new(0.0, 0.0, 1.0), Vector::new(0.0, 0.0, -1.0)), 0.0, 1.5).is_none());
+    }
+
+    #[test]
+    fn normals_point_against_the_ray() {
+        let front = rectangle().hit(&Ray::new(Point::new(0.0, 0.0, 1.0), Vector::new(0.1, 0.0, -1.0)), 0.0, f64::INFINITY).unwrap();
+        assert!(front.front_face);
+        assert!((front.normal.z - 1.0).abs() < 1e-12);
+
+        let back = rectangle().hit(&Ray::new(Point::new(0.0, 0.0, -3.0), Vector::new(0.1, 0.0, 1.0)), 0.0, f64::INFINITY).unwrap();
+        assert!(!back.front_face);
+        assert!((back.normal.z + 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn axis_aligned_rectangles_face_the_positive_axis() {
+        let rectangles = [
+            (Quad::xy_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vector::new(0.0, 0.0, 1.0)),
+            (Quad::xz_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vector::new(0.0, 1.0, 0.0)),
+            (Quad::yz_rect(0.0, 1.0, 0.0, 1.0, 0.0, material()), Vector::new(1.0, 0.0, 0.0)),
+        ];
+
+        for (rectangle, axis) in rectangles {
+            let origin = Point::new(0.5, 0.5, 0.5) + axis;
+            let record = rectangle.hit(&Ray::new(origin, -axis), 0.0, f64::INFINITY).unwrap();
+            assert!(record.front_face);
+            assert!((record.normal - axis).size() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn density_matches_the_sampled_directions() {
+        let quad = rectangle();
+        let origin = Point::zeroes();
+        let rng = &mut Rng::new(4);
+
+        // The solid angle of a rectangle of sides a and b, seen from a distance d above its center.
+        let (a, b, d) = (2.0f64, 1.0f64, 1.0f64);
+        let solid_angle = 4.0 * (a * b / ((a * a + 4.0 * d * d) * (b * b + 4.0 * d * d)).sqrt()).asin();
+
+        let samples_count = 100_000;
+        let integral = 4.0 * PI * (0..samples_count)
+            .map(|_| quad.pdf_value(&origin, &Vector::random_unit_vector(rng)))
+            .sum::<f64>() / samples_count as f64;
+        assert!((integral - 1.0).abs() < 0.03, "The density integrates to {}", integral);
+
+        // Every sampled direction hits the quad, so the average of 1 / pdf over them is its solid angle.
+        let inverse_density_sum: f64 = (0..samples_count)
+            .map(|_| {
+                let direction = quad.random(&origin, rng).unwrap();
+                assert!(quad.hit(&Ray::new(origin, direction), 0.0, f64::INFINITY).is_some());
+                1.0 / quad.pdf_value(&origin, &direction)
+            })
+            .sum();
+        let sampled_solid_angle = inverse_density_sum / samples_count as f64;
+        assert!((sampled_solid_angle - solid_angle).abs() < 0.01 * solid_angle, "{} != {}", sampled_solid_angle, solid_angle);
+        assert_eq!(quad.pdf_value(&origin, &Vector::new(0.0, 0.0, 1.0)), 0.0);
+    }
+}
@@ -1,33 +1,46 @@
 use crate::engine::hittables::hittable::{HitRecord, Hittable};
-use crate::engine::Ray;
-use crate::vectors::Point;
+use crate::engine::{OrthonormalBasis, Ray, Rng};
+use crate::vectors::{Point, Vector};
+use crate::engine::aabb::AABB;
+use crate::consts::HIT_DISTANCE_THRESHOLD;
 use crate::engine::materials::material::Material;
-use std::rc::Rc;
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 pub struct Sphere {
     center: Point,
     radius: f64,
-    material: Rc<Box<dyn Material>>
+    material: Arc<dyn Material>
 }
 
 
 impl Sphere {
-    pub fn new(center: Point, radius: f64, material: Box<dyn Material>) -> Sphere {
+    pub fn new(center: Point, radius: f64, material: Arc<dyn Material>) -> Sphere {
         Sphere {
             center,
             radius,
-            material: Rc::new(material)
+            material
         }
     }
-}
 
+    /// The cosine of the half angle of the cone the sphere takes from the origin,
+    /// or None when the origin is inside the sphere.
+    fn cone_cos_theta_max(&self, origin: &Point) -> Option<f64> {
+        let distance_squared = (self.center - *origin).size_squared();
+        if distance_squared <= self.radius * self.radius {
+            return None;
+        }
 
-impl<'a> Hittable<'a> for Sphere {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        let distance_from_center = ray.origin - self.center;
+        Some((1.0 - self.radius * self.radius / distance_squared).sqrt())
+    }
+
+    /// Find the nearest distance along the ray, in the (t_min, t_max) range, at which
+    /// it hits a sphere with the given center and radius.
+    pub fn intersect(center: &Point, radius: f64, ray: &Ray, t_min: f64, t_max: f64) -> Option<f64> {
+        let distance_from_center = ray.origin - *center;
         let a = ray.direction.size_squared();
         let half_b = distance_from_center.dot(ray.direction);
-        let c = distance_from_center.size_squared() - self.radius * self.radius;
+        let c = distance_from_center.size_squared() - radius * radius;
 
         let discriminant = half_b * half_b - a * c;
         if discriminant < 0.0 {
@@ -44,8 +57,57 @@ impl<'a> Hittable<'a> for Sphere {
             }
         }
 
+        Some(root)
+    }
+
+    /// Map a point on the unit sphere into (u, v) coordinates: u goes around the Y axis,
+    /// starting from -X, and v goes from the bottom (-Y) to the top (+Y).
+    pub fn get_uv(outward_normal: &Vector) -> (f64, f64) {
+        let theta = (-outward_normal.y).clamp(-1.0, 1.0).acos();
+        let phi = (-outward_normal.z).atan2(outward_normal.x) + PI;
+
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
+
+impl<'a> Hittable<'a> for Sphere {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let root = Sphere::intersect(&self.center, self.radius, ray, t_min, t_max)?;
+
         let hit_point = ray.at(root);
         let normal = (hit_point - self.center) / self.radius;
-        Some(HitRecord::from_ray(ray, root, normal, Rc::clone(&self.material)))
+        let record = HitRecord::from_ray(ray, root, normal, Arc::clone(&self.material))
+            .with_uv(Sphere::get_uv(&normal));
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        let radius_vector = Vector::new(self.radius, self.radius, self.radius);
+        Some(AABB::new(self.center - radius_vector, self.center + radius_vector))
+    }
+
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        let ray = Ray::new(*origin, *direction);
+        if Sphere::intersect(&self.center, self.radius, &ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY).is_none() {
+            return 0.0;
+        }
+
+        match self.cone_cos_theta_max(origin) {
+            Some(cos_theta_max) => 1.0 / (2.0 * PI * (1.0 - cos_theta_max)),
+            None => 1.0 / (4.0 * PI)
+        }
+    }
+
+    /// Sample uniformly within the cone the sphere takes from the origin,
+    /// or in all directions when the origin is inside the sphere.
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        let cos_theta_max = self.cone_cos_theta_max(origin).unwrap_or(-1.0);
+        let phi = 2.0 * PI * rng.float();
+        let z = 1.0 + rng.float() * (cos_theta_max - 1.0);
+        let sin_theta = (1.0 - z * z).max(0.0).sqrt();
+
+        let basis = OrthonormalBasis::from_w(self.center - *origin);
+        Some(basis.local(phi.cos() * sin_theta, phi.sin() * sin_theta, z))
     }
 }
\ No newline at end of file
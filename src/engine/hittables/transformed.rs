@@ -0,0 +1,127 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::aabb::AABB;
+use crate::engine::{Ray, Rng};
+use crate::vectors::{Point, Transform, Vector};
+
+/// Places an object in the world with a transformation, by transforming rays into
+/// the object's space and hit records back into world space.
+///
+/// The object can be shared, for example `Transformed<Arc<TriangleMesh>>`, so that
+/// a single mesh is instanced in many places without copying its triangles.
+pub struct Transformed<H> {
+    object: H,
+    transform: Transform,
+}
+
+
+impl<H> Transformed<H> {
+    pub fn new(object: H, transform: Transform) -> Transformed<H> {
+        Transformed { object, transform }
+    }
+
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+}
+
+
+impl<'a, H: Hittable<'a>> Hittable<'a> for Transformed<H> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let inverse = self.transform.inverse();
+        // The direction isn't normalized, so the distances along both rays are the same
+        let object_ray = Ray::with_time(inverse.point(ray.origin), inverse.vector(ray.direction), ray.time);
+
+        let mut record = self.object.hit(&object_ray, t_min, t_max)?;
+        record.point = self.transform.point(record.point);
+        // The normal keeps its side relative to the ray, so front_face doesn't change
+        record.normal = self.transform.normal(record.normal).unit();
+
+        Some(record)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        Some(self.object.bounding_box()?.transformed(&self.transform).padded())
+    }
+
+    /// The density is found in object space, and converted to world space with the ratio between
+    /// the solid angles, which for a linear map A of unit directions d is |det A| / |A d|^3.
+    /// The ratio is 1 for rotations and uniform scaling, since they preserve angles.
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        let inverse = self.transform.inverse();
+        let object_direction = inverse.vector(direction.unit());
+        let solid_angle_ratio = inverse.matrix().linear_determinant().abs() / object_direction.size().powi(3);
+
+        self.object.pdf_value(&inverse.point(*origin), &object_direction) * solid_angle_ratio
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        let object_origin = self.transform.inverse().point(*origin);
+        Some(self.transform.vector(self.object.random(&object_origin, rng)?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::{Quad, Sphere};
+    use crate::engine::materials::{Lambertian, Material};
+    use crate::vectors::Color;
+    use std::f64::consts::PI;
+    use std::sync::Arc;
+
+    fn material() -> Arc<dyn Material> {
+        Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)))
+    }
+
+    #[test]
+    fn scaled_sphere_normals_are_perpendicular_to_the_surface() {
+        let factors = Vector::new(1.0, 3.0, 0.5);
+        let ellipsoid = Transformed::new(Sphere::new(Point::zeroes(), 1.0, material()), Transform::scaling(factors));
+        let rng = &mut Rng::new(2);
+
+        for _ in 0..100 {
+            let origin = 5.0 * Vector::random_unit_vector(rng);
+            let ray = Ray::new(origin, Vector::random_in_unit_sphere(rng) - origin);
+            let record = match ellipsoid.hit(&ray, 0.001, f64::INFINITY) {
+                Some(record) => record,
+                None => continue,
+            };
+
+            // The gradient of x^2 / a^2 + y^2 / b^2 + z^2 / c^2 is perpendicular to the surface.
+            let point = record.point;
+            let gradient = Vector::new(point.x / factors.x.powi(2), point.y / factors.y.powi(2), point.z / factors.z.powi(2));
+            assert!(record.normal.cross(gradient.unit()).size() < 1e-9);
+            assert!((record.normal.size() - 1.0).abs() < 1e-9);
+            assert!(record.front_face && record.normal.dot(ray.direction) < 0.0);
+        }
+    }
+
+    #[test]
+    fn transformed_quad_density_integrates_to_1() {
+        let quad = Quad::new(Point::new(-0.5, -0.5, 0.0), Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0), material());
+        let transform = Transform::scaling(Vector::new(2.0, 0.5, 1.0)).rotate_x(30.0).translate(Vector::new(0.0, 0.0, -0.5));
+        let quad = Transformed::new(quad, transform);
+        let origin = Point::new(0.2, 0.1, 0.2);
+        let rng = &mut Rng::new(3);
+
+        // The integral of the density over all directions, and the solid angle of the quad.
+        let samples_count = 200_000;
+        let (mut density_sum, mut hits_count) = (0.0, 0);
+        for _ in 0..samples_count {
+            let pdf = quad.pdf_value(&origin, &Vector::random_unit_vector(rng));
+            density_sum += pdf;
+            hits_count += (pdf > 0.0) as usize;
+        }
+        let integral = 4.0 * PI * density_sum / samples_count as f64;
+        let solid_angle = 4.0 * PI * hits_count as f64 / samples_count as f64;
+        assert!((integral - 1.0).abs() < 0.03, "The density integrates to {}", integral);
+
+        // The directions `random` generates have the density `pdf_value` gives them,
+        // so the average of 1 / pdf over them is the solid angle of the quad.
+        let inverse_density_sum: f64 = (0..samples_count)
+            .map(|_| 1.0 / quad.pdf_value(&origin, &quad.random(&origin, rng).unwrap()))
+            .sum();
+        let sampled_solid_angle = inverse_density_sum / samples_count as f64;
+        assert!((sampled_solid_angle - solid_angle).abs() < 0.03 * solid_angle, "{} != {}", sampled_solid_angle, solid_angle);
+    }
+}
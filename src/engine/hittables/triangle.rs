@@ -0,0 +1,168 @@
+use crate::engine::hittables::hittable::{BarycentricCoordinates, HitRecord, Hittable};
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::{Ray, Rng};
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::vectors::{Point, Vector};
+use crate::consts::NEAR_ZERO_THRESHOLD;
+use std::sync::Arc;
+
+/// A triangle between 3 vertices. The front face is the one from which the
+/// vertices are seen in counter-clockwise order.
+pub struct Triangle {
+    vertices: [Point; 3],
+
+    /// Normals given per vertex, which are interpolated across the triangle
+    /// for smooth shading. When not set, the triangle is flat shaded.
+    normals: Option<[Vector; 3]>,
+    material: Arc<dyn Material>
+}
+
+
+impl Triangle {
+    pub fn new(v0: Point, v1: Point, v2: Point, material: Arc<dyn Material>) -> Triangle {
+        Triangle {
+            vertices: [v0, v1, v2],
+            normals: None,
+            material
+        }
+    }
+
+    pub fn with_normals(v0: Point, v1: Point, v2: Point, normals: [Vector; 3], material: Arc<dyn Material>) -> Triangle {
+        Triangle {
+            vertices: [v0, v1, v2],
+            normals: Some(normals),
+            material
+        }
+    }
+
+    /// Möller–Trumbore ray-triangle intersection. Returns the distance along the ray
+    /// and the barycentric coordinates of the hit point.
+    pub fn intersect(vertices: &[Point; 3], ray: &Ray, t_min: f64, t_max: f64) -> Option<(f64, BarycentricCoordinates)> {
+        let edge1 = vertices[1] - vertices[0];
+        let edge2 = vertices[2] - vertices[0];
+
+        let p = ray.direction.cross(edge2);
+        let determinant = edge1.dot(p);
+
+        // The ray is parallel to the triangle. The determinant is the cosine between the ray and the
+        // triangle's normal, scaled by the length of the ray and twice the area of the triangle,
+        // so it's compared relatively to them, or rays would miss small triangles.
+        let scale_squared = ray.direction.size_squared() * edge1.cross(edge2).size_squared();
+        if determinant * determinant <= NEAR_ZERO_THRESHOLD * NEAR_ZERO_THRESHOLD * scale_squared {
+            return None;
+        }
+
+        let inverse_determinant = 1.0 / determinant;
+        let distance_from_vertex = ray.origin - vertices[0];
+
+        let u = distance_from_vertex.dot(p) * inverse_determinant;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+
+        let q = distance_from_vertex.cross(edge1);
+        let v = ray.direction.dot(q) * inverse_determinant;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(q) * inverse_determinant;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        Some((t, BarycentricCoordinates::new(u, v)))
+    }
+
+    /// The normal of the triangle's plane, pointing out of its front face.
+    pub fn geometric_normal(vertices: &[Point; 3]) -> Vector {
+        (vertices[1] - vertices[0]).cross(vertices[2] - vertices[0]).unit()
+    }
+
+    pub fn get_bounding_box(vertices: &[Point; 3]) -> AABB {
+        let minimum = vertices[0].min(vertices[1]).min(vertices[2]);
+        let maximum = vertices[0].max(vertices[1]).max(vertices[2]);
+
+        AABB::new(minimum, maximum).padded()
+    }
+}
+
+
+impl<'a> Hittable<'a> for Triangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let (t, barycentric) = Triangle::intersect(&self.vertices, ray, t_min, t_max)?;
+
+        let normal = Triangle::geometric_normal(&self.vertices);
+        // Without texture coordinates of its own, the triangle is mapped by its barycentric coordinates
+        let record = HitRecord::from_ray(ray, t, normal, Arc::clone(&self.material))
+            .with_uv((barycentric.u, barycentric.v))
+            .with_barycentric(barycentric);
+
+        match self.normals {
+            Some(normals) => Some(record.with_shading_normal(barycentric.interpolate(normals).unit())),
+            None => Some(record)
+        }
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        Some(Triangle::get_bounding_box(&self.vertices))
+    }
+
+    /// Convert the density of picking a point uniformly over the area into a density over solid angle.
+    fn pdf_value(&self, origin: &Point, direction: &Vector) -> f64 {
+        let ray = Ray::new(*origin, *direction);
+        let (t, _) = match Triangle::intersect(&self.vertices, &ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+            Some(hit) => hit,
+            None => return 0.0
+        };
+
+        let cross = (self.vertices[1] - self.vertices[0]).cross(self.vertices[2] - self.vertices[0]);
+        let area = cross.size() / 2.0;
+        let distance_squared = t * t * direction.size_squared();
+        let cosine = (direction.dot(cross) / (direction.size() * cross.size())).abs();
+
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: &Point, rng: &mut Rng) -> Option<Vector> {
+        // Folding the unit square in half gives uniformly distributed barycentric coordinates
+        let (mut u, mut v) = (rng.float(), rng.float());
+        if u + v > 1.0 {
+            u = 1.0 - u;
+            v = 1.0 - v;
+        }
+
+        Some(BarycentricCoordinates::new(u, v).interpolate(self.vertices) - *origin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hits(vertices: &[Point; 3], ray: &Ray) -> bool {
+        Triangle::intersect(vertices, ray, 0.0, f64::INFINITY).is_some()
+    }
+
+    #[test]
+    fn small_triangles_are_hit() {
+        let size = 1e-6;
+        let vertices = [Point::new(0.0, 0.0, 0.0), Point::new(size, 0.0, 0.0), Point::new(0.0, size, 0.0)];
+        let ray = Ray::new(Point::new(size / 4.0, size / 4.0, 1.0), Vector::new(0.0, 0.0, -1.0));
+
+        let (t, barycentric) = Triangle::intersect(&vertices, &ray, 0.0, f64::INFINITY).unwrap();
+        assert!((t - 1.0).abs() < 1e-12);
+        assert!((barycentric.u - 0.25).abs() < 1e-9 && (barycentric.v - 0.25).abs() < 1e-9);
+    }
+
+    #[test]
+    fn parallel_and_degenerate_triangles_are_missed() {
+        let vertices = [Point::new(0.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0), Point::new(0.0, 1.0, 0.0)];
+        assert!(!hits(&vertices, &Ray::new(Point::new(-1.0, 0.25, 0.0), Vector::new(1.0, 0.0, 0.0))));
+        assert!(hits(&vertices, &Ray::new(Point::new(0.25, 0.25, 1.0), Vector::new(0.0, 0.0, -1e-6))));
+
+        let line = [Point::new(0.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0), Point::new(2.0, 0.0, 0.0)];
+        assert!(!hits(&line, &Ray::new(Point::new(0.5, 0.0, 1.0), Vector::new(0.0, 0.0, -1.0))));
+    }
+}
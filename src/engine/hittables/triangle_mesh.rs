@@ -0,0 +1,129 @@
+use crate::engine::hittables::hittable::{HitRecord, Hittable};
+use crate::engine::hittables::hittable_collection::HittableCollection;
+use crate::engine::hittables::bvh_node::BvhNode;
+use crate::engine::hittables::triangle::Triangle;
+use crate::engine::materials::material::Material;
+use crate::engine::aabb::AABB;
+use crate::engine::Ray;
+use crate::vectors::{Point, Vector};
+use std::sync::Arc;
+
+/// A single triangle of a mesh. Each index points into the matching buffer of
+/// the mesh, so vertices shared by several triangles are stored only once.
+#[derive(Copy, Clone, Debug)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+
+    /// Index into the materials of the mesh.
+    pub material: usize,
+}
+
+/// The buffers which are shared by all of the triangles of a mesh.
+pub struct MeshBuffers {
+    pub positions: Vec<Point>,
+    pub normals: Vec<Vector>,
+    pub uvs: Vec<(f64, f64)>,
+    pub materials: Vec<Arc<dyn Material>>,
+}
+
+/// A mesh made of triangles which share vertex, normal and UV buffers.
+/// The triangles are placed in their own bounding volume hierarchy, so a mesh
+/// can be placed in the world as a single object, no matter how big it is.
+pub struct TriangleMesh {
+    bvh: Option<BvhNode<'static>>,
+    faces_count: usize,
+}
+
+/// A triangle which references its vertices in the mesh buffers.
+struct MeshTriangle {
+    buffers: Arc<MeshBuffers>,
+    face: MeshFace,
+}
+
+
+impl TriangleMesh {
+    pub fn new(buffers: MeshBuffers, faces: Vec<MeshFace>) -> TriangleMesh {
+        for face in &faces {
+            TriangleMesh::validate_face(&buffers, face);
+        }
+
+        let faces_count = faces.len();
+        let buffers = Arc::new(buffers);
+        let mut triangles = HittableCollection::new();
+        for face in faces {
+            triangles.add(Box::new(MeshTriangle { buffers: Arc::clone(&buffers), face }));
+        }
+
+        let bvh = BvhNode::new(triangles);
+        TriangleMesh { bvh, faces_count }
+    }
+
+    pub fn faces_count(&self) -> usize {
+        self.faces_count
+    }
+
+    fn validate_face(buffers: &MeshBuffers, face: &MeshFace) {
+        let is_in_range = |indices: &[usize; 3], length: usize| indices.iter().all(|&index| index < length);
+
+        if !is_in_range(&face.positions, buffers.positions.len()) {
+            panic!("Mesh face {:?} references a missing vertex position.", face);
+        }
+        if face.normals.is_some_and(|normals| !is_in_range(&normals, buffers.normals.len())) {
+            panic!("Mesh face {:?} references a missing vertex normal.", face);
+        }
+        if face.uvs.is_some_and(|uvs| !is_in_range(&uvs, buffers.uvs.len())) {
+            panic!("Mesh face {:?} references a missing vertex UV.", face);
+        }
+        if face.material >= buffers.materials.len() {
+            panic!("Mesh face {:?} references a missing material.", face);
+        }
+    }
+}
+
+impl MeshTriangle {
+    fn vertices(&self) -> [Point; 3] {
+        let [i0, i1, i2] = self.face.positions;
+        [self.buffers.positions[i0], self.buffers.positions[i1], self.buffers.positions[i2]]
+    }
+}
+
+
+impl<'a> Hittable<'a> for TriangleMesh {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        self.bvh.as_ref()?.hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        self.bvh.as_ref()?.bounding_box()
+    }
+}
+
+impl<'a> Hittable<'a> for MeshTriangle {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+        let vertices = self.vertices();
+        let (t, barycentric) = Triangle::intersect(&vertices, ray, t_min, t_max)?;
+
+        let material = Arc::clone(&self.buffers.materials[self.face.material]);
+        let uv = match self.face.uvs {
+            Some([t0, t1, t2]) => barycentric.interpolate_uv([self.buffers.uvs[t0], self.buffers.uvs[t1], self.buffers.uvs[t2]]),
+            None => (barycentric.u, barycentric.v)
+        };
+        let record = HitRecord::from_ray(ray, t, Triangle::geometric_normal(&vertices), material)
+            .with_uv(uv)
+            .with_barycentric(barycentric);
+
+        match self.face.normals {
+            Some([n0, n1, n2]) => {
+                let normals = [self.buffers.normals[n0], self.buffers.normals[n1], self.buffers.normals[n2]];
+                Some(record.with_shading_normal(barycentric.interpolate(normals).unit()))
+            }
+            None => Some(record)
+        }
+    }
+
+    fn bounding_box(&self) -> Option<AABB> {
+        Some(Triangle::get_bounding_box(&self.vertices()))
+    }
+}
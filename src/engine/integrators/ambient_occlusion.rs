@@ -0,0 +1,44 @@
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::engine::integrators::Integrator;
+use crate::engine::samplers::SampleDimensions;
+use crate::engine::{Background, OrthonormalBasis, Ray, Rng, Scene};
+use crate::vectors::Color;
+use std::f64::consts::PI;
+
+/// Shades every point by how much of the hemisphere above it is open, ignoring materials
+/// and lights. Each sample checks a single cosine distributed direction for objects
+/// closer than `distance`. Useful for quickly previewing the geometry of a scene.
+pub struct AmbientOcclusion {
+    distance: f64
+}
+
+
+impl AmbientOcclusion {
+    pub fn new(distance: f64) -> AmbientOcclusion {
+        AmbientOcclusion {distance}
+    }
+}
+
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, ray: &Ray, scene: &Scene, _background: &Background, rng: &mut Rng) -> Color {
+        let open = Color::new(1.0, 1.0, 1.0);
+        let record = match scene.world.hit(ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+            Some(record) => record,
+            None => return open
+        };
+
+        rng.use_dimensions(SampleDimensions::Scattering { bounce: 0 });
+        let r1 = rng.float();
+        let r2 = rng.float();
+        let phi = 2.0 * PI * r1;
+        let direction = OrthonormalBasis::from_w(record.normal)
+            .local(phi.cos() * r2.sqrt(), phi.sin() * r2.sqrt(), (1.0 - r2).sqrt());
+
+        let occlusion_ray = Ray::with_time(record.point, direction, ray.time);
+        match scene.world.hit(&occlusion_ray, HIT_DISTANCE_THRESHOLD, self.distance) {
+            Some(_) => Color::zeroes(),
+            None => open
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::engine::integrators::light_sampling::sample_lights;
+use crate::engine::integrators::Integrator;
+use crate::engine::materials::ScatterResult;
+use crate::engine::samplers::SampleDimensions;
+use crate::engine::{Background, Ray, Rng, Scene};
+use crate::vectors::Color;
+
+/// Whitted-style ray tracing: diffuse surfaces are lit only by light which reaches them
+/// directly, either from the scene lights or from the background. Mirrors and glass are
+/// followed recursively, up to `max_depth` bounces. Much faster than path tracing, but
+/// without any indirect lighting.
+pub struct DirectLighting {
+    max_depth: usize
+}
+
+
+impl DirectLighting {
+    pub fn new(max_depth: usize) -> DirectLighting {
+        DirectLighting {max_depth}
+    }
+
+    fn ray_color(&self, ray: &Ray, scene: &Scene, background: &Background, depth: usize, rng: &mut Rng) -> Color {
+        if depth == 0 {
+            return Color::zeroes();
+        }
+
+        let record = match scene.world.hit(ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+            Some(record) => record,
+            None => return background.color(ray)
+        };
+
+        let emitted = record.material.emitted(ray, &record);
+        let bounce = self.max_depth - depth;
+        rng.use_dimensions(SampleDimensions::Scattering { bounce });
+        let ScatterResult { scattered, attenuation, pdf } = match record.material.scatter(ray, &record, rng) {
+            Some(scatter_result) => scatter_result,
+            None => return emitted
+        };
+
+        match pdf {
+            Some(pdf) if pdf > 0.0 => {
+                rng.use_dimensions(SampleDimensions::Light { bounce });
+                let from_lights = sample_lights(ray, &record, attenuation, scene, false, rng);
+
+                // The scattered ray only counts the background, since the lights were already sampled.
+                let from_background = match scene.world.hit(&scattered, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+                    Some(_) => Color::zeroes(),
+                    None => {
+                        let scattering_pdf = record.material.scattering_pdf(ray, &record, &scattered);
+                        (scattering_pdf / pdf) * attenuation * background.color(&scattered)
+                    }
+                };
+
+                emitted + from_lights + from_background
+            }
+            Some(_) => emitted,
+            None => emitted + attenuation * self.ray_color(&scattered, scene, background, depth - 1, rng)
+        }
+    }
+}
+
+
+impl Integrator for DirectLighting {
+    fn radiance(&self, ray: &Ray, scene: &Scene, background: &Background, rng: &mut Rng) -> Color {
+        self.ray_color(ray, scene, background, self.max_depth, rng)
+    }
+}
@@ -0,0 +1,38 @@
+use crate::engine::integrators::{AmbientOcclusion, DirectLighting, NaivePathTracer, PathTracer};
+use crate::engine::{Background, Ray, RenderSettings, Rng, Scene};
+use crate::vectors::Color;
+
+/// An algorithm which computes the light arriving at the camera along a ray.
+/// Integrators are shared between the rendering threads.
+pub trait Integrator: Send + Sync {
+    fn radiance(&self, ray: &Ray, scene: &Scene, background: &Background, rng: &mut Rng) -> Color;
+}
+
+/// The built-in integrators, from the most accurate to the fastest.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum IntegratorKind {
+    /// Iterative path tracing with light sampling, ended by Russian roulette.
+    #[default]
+    PathTracer,
+
+    /// Recursive path tracing, which only follows the scattered rays, up to `max_depth` bounces.
+    NaivePathTracer,
+
+    /// Direct light only, plus perfect reflections and refractions.
+    DirectLighting,
+
+    /// How much of the hemisphere above each point is unoccluded within the given distance.
+    AmbientOcclusion { distance: f64 },
+}
+
+
+impl IntegratorKind {
+    pub fn create(&self, settings: &RenderSettings) -> Box<dyn Integrator> {
+        match *self {
+            IntegratorKind::PathTracer => Box::new(PathTracer::new(settings.max_depth)),
+            IntegratorKind::NaivePathTracer => Box::new(NaivePathTracer::new(settings.max_depth)),
+            IntegratorKind::DirectLighting => Box::new(DirectLighting::new(settings.max_depth)),
+            IntegratorKind::AmbientOcclusion { distance } => Box::new(AmbientOcclusion::new(distance)),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::engine::hittables::{HitRecord, Hittable};
+use crate::engine::{Ray, Rng, Scene};
+use crate::vectors::Color;
+
+/// The light which arrives at the hit point from a direction sampled towards the scene lights.
+/// When `use_mis` is set, the result is weighted against the chance of the material scattering
+/// into the same direction, for integrators which also follow the scattered ray.
+pub fn sample_lights(ray: &Ray, record: &HitRecord, attenuation: Color, scene: &Scene, use_mis: bool, rng: &mut Rng) -> Color {
+    // Lights which can't be sampled directly are only found by following scattered rays.
+    let direction = match scene.lights.random(&record.point, rng) {
+        Some(direction) => direction,
+        None => return Color::zeroes()
+    };
+
+    let light_ray = Ray::with_time(record.point, direction, ray.time);
+    let light_pdf = scene.lights.pdf_value(&light_ray.origin, &light_ray.direction);
+    let scattering_pdf = record.material.scattering_pdf(ray, record, &light_ray);
+    if light_pdf <= 0.0 || scattering_pdf <= 0.0 {
+        return Color::zeroes();
+    }
+
+    // Whatever is hit first is what's seen in that direction, which might not be the light.
+    match scene.world.hit(&light_ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+        Some(light_record) => {
+            let emitted = light_record.material.emitted(&light_ray, &light_record);
+            let weight = if use_mis { power_heuristic(light_pdf, scattering_pdf) } else { 1.0 };
+            (weight * scattering_pdf / light_pdf) * attenuation * emitted
+        }
+        None => Color::zeroes()
+    }
+}
+
+/// The weight of a sample taken with density `pdf`, when the same direction could also
+/// have been sampled with density `other_pdf` by the other technique.
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let squared = pdf * pdf;
+    squared / (squared + other_pdf * other_pdf)
+}
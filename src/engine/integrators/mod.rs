@@ -0,0 +1,12 @@
+pub mod integrator;
+pub mod light_sampling;
+pub mod naive_path_tracer;
+pub mod path_tracer;
+pub mod ambient_occlusion;
+pub mod direct_lighting;
+
+pub use integrator::{Integrator, IntegratorKind};
+pub use naive_path_tracer::NaivePathTracer;
+pub use path_tracer::PathTracer;
+pub use ambient_occlusion::AmbientOcclusion;
+pub use direct_lighting::DirectLighting;
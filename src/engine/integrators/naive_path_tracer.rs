@@ -0,0 +1,47 @@
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::engine::integrators::Integrator;
+use crate::engine::materials::ScatterResult;
+use crate::engine::samplers::SampleDimensions;
+use crate::engine::{Background, Ray, Rng, Scene};
+use crate::vectors::Color;
+
+/// Follows the scattered rays until they escape the scene or `max_depth` is reached,
+/// without sampling the lights. Simple, but converges slowly for small lights.
+pub struct NaivePathTracer {
+    max_depth: usize
+}
+
+
+impl NaivePathTracer {
+    pub fn new(max_depth: usize) -> NaivePathTracer {
+        NaivePathTracer {max_depth}
+    }
+
+    fn ray_color(&self, ray: &Ray, scene: &Scene, background: &Background, depth: usize, rng: &mut Rng) -> Color {
+        if depth == 0 {
+            return Color::zeroes();
+        }
+
+        match scene.world.hit(ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+            Some(record) => {
+                let emitted = record.material.emitted(ray, &record);
+
+                rng.use_dimensions(SampleDimensions::Scattering { bounce: self.max_depth - depth });
+                if let Some(scatter_result) = record.material.scatter(ray, &record, rng) {
+                    let ScatterResult { scattered, attenuation, .. } = scatter_result;
+                    return emitted + attenuation * self.ray_color(&scattered, scene, background, depth - 1, rng);
+                }
+
+                emitted
+            }
+            None => background.color(ray)
+        }
+    }
+}
+
+
+impl Integrator for NaivePathTracer {
+    fn radiance(&self, ray: &Ray, scene: &Scene, background: &Background, rng: &mut Rng) -> Color {
+        self.ray_color(ray, scene, background, self.max_depth, rng)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::consts::HIT_DISTANCE_THRESHOLD;
+use crate::engine::integrators::light_sampling::{power_heuristic, sample_lights};
+use crate::engine::hittables::Hittable;
+use crate::engine::integrators::Integrator;
+use crate::engine::materials::ScatterResult;
+use crate::engine::samplers::SampleDimensions;
+use crate::engine::{Background, Ray, Rng, Scene};
+use crate::vectors::Color;
+
+/// Paths are never ended by Russian roulette before this many bounces.
+const ROULETTE_MIN_BOUNCES: usize = 3;
+
+/// Paths always have some chance of ending, even when they carry a lot of light.
+const ROULETTE_MAX_SURVIVAL_PROBABILITY: f64 = 0.95;
+
+/// Follows a path through the scene, sampling the lights directly at every diffuse hit.
+/// Light which is found both by sampling the lights and by following the scattered ray is
+/// weighted with multiple importance sampling, so it's counted only once, mostly through the
+/// technique which is more likely to find it.
+///
+/// Instead of always tracing `max_depth` bounces, paths which carry little light are ended
+/// randomly (Russian roulette), and the surviving ones are boosted to make up for it.
+/// `max_depth` only limits the rare paths which keep surviving.
+pub struct PathTracer {
+    max_depth: usize
+}
+
+
+impl PathTracer {
+    pub fn new(max_depth: usize) -> PathTracer {
+        PathTracer {max_depth}
+    }
+}
+
+
+impl Integrator for PathTracer {
+    fn radiance(&self, ray: &Ray, scene: &Scene, background: &Background, rng: &mut Rng) -> Color {
+        let mut color = Color::zeroes();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = *ray;
+
+        // The density the current ray was sampled with by the previous material,
+        // or None when it came from the camera or from a specular scattering.
+        let mut ray_pdf: Option<f64> = None;
+
+        for bounce in 0..self.max_depth {
+            let record = match scene.world.hit(&ray, HIT_DISTANCE_THRESHOLD, f64::INFINITY) {
+                Some(record) => record,
+                None => {
+                    color = color + throughput * background.color(&ray);
+                    break;
+                }
+            };
+
+            let mut emitted = record.material.emitted(&ray, &record);
+            if let Some(ray_pdf) = ray_pdf {
+                let light_pdf = scene.lights.pdf_value(&ray.origin, &ray.direction);
+                emitted = power_heuristic(ray_pdf, light_pdf) * emitted;
+            }
+            color = color + throughput * emitted;
+
+            rng.use_dimensions(SampleDimensions::Scattering { bounce });
+            let ScatterResult { scattered, attenuation, pdf } = match record.material.scatter(&ray, &record, rng) {
+                Some(scatter_result) => scatter_result,
+                None => break
+            };
+
+            match pdf {
+                Some(pdf) if pdf > 0.0 => {
+                    rng.use_dimensions(SampleDimensions::Light { bounce });
+                    color = color + throughput * sample_lights(&ray, &record, attenuation, scene, true, rng);
+
+                    let scattering_pdf = record.material.scattering_pdf(&ray, &record, &scattered);
+                    throughput = (scattering_pdf / pdf) * throughput * attenuation;
+                    ray_pdf = Some(pdf);
+                }
+                Some(_) => break,
+                None => {
+                    throughput = throughput * attenuation;
+                    ray_pdf = None;
+                }
+            }
+            ray = scattered;
+
+            if bounce + 1 >= ROULETTE_MIN_BOUNCES {
+                let survival_probability = throughput.max_element().min(ROULETTE_MAX_SURVIVAL_PROBABILITY);
+                rng.use_dimensions(SampleDimensions::Roulette { bounce });
+                if rng.float() >= survival_probability {
+                    break;
+                }
+                throughput = throughput / survival_probability;
+            }
+        }
+
+        color
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::{HittableCollection, MovingSphere, Quad, Sphere};
+    use crate::engine::integrators::NaivePathTracer;
+    use crate::engine::materials::{DiffuseLight, Lambertian, Material};
+    use crate::vectors::{Point, Vector};
+    use std::sync::Arc;
+
+    fn average_radiance(integrator: &dyn Integrator, scene: &Scene, ray: &Ray, samples_count: usize) -> Color {
+        let background = Background::black();
+        let mut rng = Rng::new(7);
+        let total = (0..samples_count)
+            .map(|_| integrator.radiance(ray, scene, &background, &mut rng))
+            .fold(Color::zeroes(), |total, color| total + color);
+
+        total / samples_count as f64
+    }
+
+    #[test]
+    fn light_sampling_agrees_with_the_naive_path_tracer() {
+        // A wall lit by a sphere in front of it, and by a moving sphere, which can't be sampled
+        // directly but is still in the lights. The moving sphere is only found by scattered rays.
+        let light: Arc<dyn Material> = Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0)));
+        let wall = Quad::new(Point::new(0.0, -5.0, -5.0), Vector::new(0.0, 10.0, 0.0), Vector::new(0.0, 0.0, 10.0),
+                             Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))));
+        let sphere = Arc::new(Sphere::new(Point::new(4.0, 0.0, 0.0), 1.0, Arc::clone(&light)));
+        let moving_sphere = Arc::new(MovingSphere::new(Point::new(2.0, 0.0, 3.0), Point::new(2.0, 0.0, 3.0), 0.0, 1.0, 1.0, light));
+
+        let mut world = HittableCollection::new();
+        world.add(Box::new(wall));
+        world.add(Box::new(Arc::clone(&sphere)));
+        world.add(Box::new(Arc::clone(&moving_sphere)));
+        let mut lights = HittableCollection::new();
+        lights.add(Box::new(sphere));
+        lights.add(Box::new(moving_sphere));
+        let scene = Scene::with_lights(Box::new(world), lights);
+
+        let ray = Ray::new(Point::new(1.0, 3.0, 0.0), Vector::new(-1.0, -3.0, 0.0));
+        let expected = average_radiance(&NaivePathTracer::new(10), &scene, &ray, 200_000);
+        let color = average_radiance(&PathTracer::new(10), &scene, &ray, 50_000);
+
+        assert!(expected.red > 0.1);
+        assert!((color.red - expected.red).abs() < 0.03 * expected.red, "expected {:?}, got {:?}", expected, color);
+    }
+}
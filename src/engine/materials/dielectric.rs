@@ -1,9 +1,8 @@
 use crate::engine::materials::material::{Material, ScatterResult};
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
 use crate::engine::hittables::hittable::HitRecord;
 use crate::vectors::Color;
 use crate::consts::AIR_REFRACTION_INDEX;
-use crate::engine::utils::random_float;
 
 pub struct Dielectric {
     refraction_index: f64
@@ -33,7 +32,7 @@ impl Dielectric {
 
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterResult> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut Rng) -> Option<ScatterResult> {
         let attenuation = Color::new(1.0, 1.0, 1.0);
         let refraction_ratio = self.get_refraction_ratio(hit_record);
 
@@ -44,15 +43,14 @@ impl Material for Dielectric {
         // According to snell's law
         let cannot_refract = refraction_ratio * sin_theta > 1.0;
 
-        let direction;
-        if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > random_float(0.0, 1.0) {
-            direction = unit_direction.reflect(hit_record.normal);
+        let direction = if cannot_refract || Dielectric::reflectance(cos_theta, refraction_ratio) > rng.float() {
+            unit_direction.reflect(hit_record.normal)
         } else {
-            direction = unit_direction.refract(hit_record.normal, refraction_ratio);
-        }
+            unit_direction.refract(hit_record.normal, refraction_ratio)
+        };
 
         let result = ScatterResult::new(
-            Ray::new(hit_record.point, direction),
+            Ray::with_time(hit_record.point, direction, ray.time),
             attenuation
         );
         Some(result)
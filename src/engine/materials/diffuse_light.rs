@@ -0,0 +1,43 @@
+use crate::vectors::Color;
+use crate::engine::materials::material::{Material, ScatterResult};
+use crate::engine::{Ray, Rng};
+use crate::engine::hittables::hittable::HitRecord;
+use crate::engine::textures::{SolidColor, Texture};
+use std::sync::Arc;
+
+/// A surface which emits light evenly in all directions, and doesn't reflect any.
+pub struct DiffuseLight {
+    emit: Arc<dyn Texture>
+}
+
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> DiffuseLight {
+        DiffuseLight::with_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn with_texture(emit: Arc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight {emit}
+    }
+}
+
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: &Ray, _hit_record: &HitRecord, _rng: &mut Rng) -> Option<ScatterResult> {
+        None
+    }
+
+    fn emitted(&self, _ray: &Ray, hit_record: &HitRecord) -> Color {
+        // Lights are one-sided, they only shine out of their front face.
+        if hit_record.front_face {
+            let (u, v) = hit_record.uv;
+            return self.emit.value(u, v, &hit_record.point);
+        }
+
+        Color::zeroes()
+    }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,39 @@
+use crate::vectors::{Color, Vector};
+use crate::engine::materials::material::{Material, ScatterResult};
+use crate::engine::{Ray, Rng};
+use crate::engine::hittables::hittable::HitRecord;
+use crate::engine::textures::{SolidColor, Texture};
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+/// The phase function of a participating medium, which scatters light
+/// evenly in all directions. Used with `ConstantMedium` for fog and smoke.
+pub struct Isotropic {
+    albedo: Arc<dyn Texture>
+}
+
+
+impl Isotropic {
+    pub fn new(albedo: Color) -> Isotropic {
+        Isotropic::with_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Isotropic {
+        Isotropic {albedo}
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut Rng) -> Option<ScatterResult> {
+        let scattered = Ray::with_time(hit_record.point, Vector::random_unit_vector(rng), ray.time);
+        let (u, v) = hit_record.uv;
+        let attenuation = self.albedo.value(u, v, &hit_record.point);
+
+        Some(ScatterResult::with_pdf(scattered, attenuation, 1.0 / (4.0 * PI)))
+    }
+
+    /// Every direction on the unit sphere is equally likely.
+    fn scattering_pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,31 +1,46 @@
 use crate::vectors::{Color, Vector};
 use crate::engine::materials::material::{Material, ScatterResult};
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
 use crate::engine::hittables::hittable::HitRecord;
+use crate::engine::textures::{SolidColor, Texture};
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 pub struct Lambertian {
-    albedo: Color
+    albedo: Arc<dyn Texture>
 }
 
 
 impl Lambertian {
     pub fn new(albedo: Color) -> Lambertian {
+        Lambertian::with_texture(Arc::new(SolidColor::new(albedo)))
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>) -> Lambertian {
         Lambertian {albedo}
     }
 }
 
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, hit_record: &HitRecord) -> Option<ScatterResult> {
-        let mut scatter_direction = hit_record.normal + Vector::random_unit_vector();
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut Rng) -> Option<ScatterResult> {
+        let mut scatter_direction = hit_record.normal + Vector::random_unit_vector(rng);
 
         if scatter_direction.near_zero() {
             scatter_direction = hit_record.normal;
         }
 
-        let scattered = Ray::new(hit_record.point, scatter_direction);
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(hit_record.point, scatter_direction, ray.time);
+        let (u, v) = hit_record.uv;
+        let attenuation = self.albedo.value(u, v, &hit_record.point);
+        let pdf = self.scattering_pdf(ray, hit_record, &scattered);
+
+        Some(ScatterResult::with_pdf(scattered, attenuation, pdf))
+    }
 
-        Some(ScatterResult::new(scattered, attenuation))
+    /// Adding a random unit vector to the normal distributes the directions by the cosine of their angle.
+    fn scattering_pdf(&self, _ray: &Ray, hit_record: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = hit_record.normal.dot(scattered.direction.unit());
+        cosine.max(0.0) / PI
     }
 }
\ No newline at end of file
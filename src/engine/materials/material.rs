@@ -1,4 +1,4 @@
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
 use crate::vectors::Color;
 use crate::engine::hittables::hittable::HitRecord;
 
@@ -6,14 +6,44 @@ use crate::engine::hittables::hittable::HitRecord;
 pub struct ScatterResult {
     pub scattered: Ray,
     pub attenuation: Color,
+
+    /// The probability density the scattered direction was sampled with. None for
+    /// specular scattering (such as a mirror), which can't be combined with light sampling.
+    pub pdf: Option<f64>,
 }
 
 impl ScatterResult {
+    /// A specular scattering.
     pub fn new(scattered: Ray, attenuation: Color) -> ScatterResult {
-        ScatterResult {scattered, attenuation}
+        ScatterResult {scattered, attenuation, pdf: None}
+    }
+
+    /// A scattering whose direction was sampled with the given density.
+    pub fn with_pdf(scattered: Ray, attenuation: Color, pdf: f64) -> ScatterResult {
+        ScatterResult {scattered, attenuation, pdf: Some(pdf)}
     }
 }
 
-pub trait Material {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterResult>;
+/// Materials are shared between objects and rendering threads, so they
+/// must be safe to send and share across threads.
+pub trait Material: Send + Sync {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut Rng) -> Option<ScatterResult>;
+
+    /// The light the surface emits at the hit point. Most materials don't emit any.
+    fn emitted(&self, _ray: &Ray, _hit_record: &HitRecord) -> Color {
+        Color::zeroes()
+    }
+
+    /// The density of the material scattering the ray into the given direction.
+    /// The light carried by a scattered ray is scaled by attenuation * scattering_pdf / pdf,
+    /// so materials which sample exactly by this density simply scale it by attenuation.
+    /// Only materials which scatter with a pdf (see `ScatterResult`) need to implement it.
+    fn scattering_pdf(&self, _ray: &Ray, _hit_record: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    /// Whether the material emits any light, so objects made of it should be sampled as lights.
+    fn is_emissive(&self) -> bool {
+        false
+    }
 }
\ No newline at end of file
@@ -1,28 +1,35 @@
 use crate::vectors::{Color, Vector};
 use crate::engine::materials::material::{Material, ScatterResult};
-use crate::engine::Ray;
+use crate::engine::{Ray, Rng};
 use crate::engine::hittables::hittable::HitRecord;
+use crate::engine::textures::{SolidColor, Texture};
+use std::sync::Arc;
 
 pub struct Metal {
-    albedo: Color,
+    albedo: Arc<dyn Texture>,
     fuzz: f64
 }
 
 impl Metal {
     pub fn new(albedo: Color, fuzz: f64) -> Metal {
+        Metal::with_texture(Arc::new(SolidColor::new(albedo)), fuzz)
+    }
+
+    pub fn with_texture(albedo: Arc<dyn Texture>, fuzz: f64) -> Metal {
         Metal {albedo, fuzz}
     }
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hit_record: &HitRecord) -> Option<ScatterResult> {
+    fn scatter(&self, ray: &Ray, hit_record: &HitRecord, rng: &mut Rng) -> Option<ScatterResult> {
         let mut reflected = ray.direction.unit().reflect(hit_record.normal);
 
         // Add fuzz to the reflected vector
-        reflected = reflected + self.fuzz * Vector::random_in_unit_sphere();
+        reflected = reflected + self.fuzz * Vector::random_in_unit_sphere(rng);
 
-        let scattered = Ray::new(hit_record.point, reflected);
-        let attenuation = self.albedo;
+        let scattered = Ray::with_time(hit_record.point, reflected, ray.time);
+        let (u, v) = hit_record.uv;
+        let attenuation = self.albedo.value(u, v, &hit_record.point);
 
         if scattered.direction.dot(hit_record.normal) > 0.0 {
             return Some(ScatterResult::new(scattered, attenuation));
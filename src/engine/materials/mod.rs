@@ -2,8 +2,12 @@ pub mod material;
 pub mod lambertian;
 pub mod metal;
 pub mod dielectric;
+pub mod diffuse_light;
+pub mod isotropic;
 
 pub use material::{ScatterResult, Material};
 pub use lambertian::Lambertian;
 pub use metal::Metal;
-pub use dielectric::Dielectric;
\ No newline at end of file
+pub use dielectric::Dielectric;
+pub use diffuse_light::DiffuseLight;
+pub use isotropic::Isotropic;
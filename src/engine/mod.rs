@@ -3,5 +3,26 @@ pub mod hittables;
 pub mod camera;
 pub mod utils;
 pub mod materials;
+pub mod aabb;
+pub mod background;
+pub mod scene;
+pub mod renderer;
+pub mod rng;
+pub mod onb;
+pub mod integrators;
+pub mod textures;
+pub mod framebuffer;
+pub mod samplers;
+pub mod filters;
 
 pub use ray::Ray;
+pub use aabb::AABB;
+pub use background::Background;
+pub use scene::Scene;
+pub use renderer::{
+    render, render_framebuffer, render_progressive, render_with_integrator, AdaptiveSampling, PassReport, ProgressiveSettings,
+    RenderSettings,
+};
+pub use framebuffer::{FilteredPixel, Framebuffer, PixelStatistics};
+pub use rng::Rng;
+pub use onb::OrthonormalBasis;
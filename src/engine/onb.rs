@@ -0,0 +1,31 @@
+use crate::vectors::Vector;
+
+/// Three perpendicular unit vectors, used for generating directions around an axis.
+/// Directions are easy to generate around the z axis, and are then transformed so
+/// that w takes the place of the z axis.
+#[derive(Copy, Clone, Debug)]
+pub struct OrthonormalBasis {
+    pub u: Vector,
+    pub v: Vector,
+    pub w: Vector,
+}
+
+
+impl OrthonormalBasis {
+    /// Build a basis around the given axis, which doesn't have to be a unit vector.
+    pub fn from_w(w: Vector) -> OrthonormalBasis {
+        let w = w.unit();
+
+        // Any vector which isn't parallel to w will do
+        let helper = if w.x.abs() > 0.9 { Vector::new(0.0, 1.0, 0.0) } else { Vector::new(1.0, 0.0, 0.0) };
+        let v = w.cross(helper).unit();
+        let u = w.cross(v);
+
+        OrthonormalBasis { u, v, w }
+    }
+
+    /// Transform a vector given in the basis coordinates into world coordinates.
+    pub fn local(&self, a: f64, b: f64, c: f64) -> Vector {
+        a * self.u + b * self.v + c * self.w
+    }
+}
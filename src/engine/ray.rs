@@ -1,14 +1,23 @@
 use crate::vectors::{Point, Vector};
 
+#[derive(Copy, Clone, Debug)]
 pub struct Ray {
     pub origin: Point,
-    pub direction: Vector
+    pub direction: Vector,
+
+    /// The moment the ray exists at, between the camera's shutter open and close times.
+    /// Moving objects are hit where they are at that moment, which creates motion blur.
+    pub time: f64
 }
 
 
 impl Ray {
     pub fn new(origin: Point, direction: Vector) -> Ray {
-        Ray {origin, direction}
+        Ray::with_time(origin, direction, 0.0)
+    }
+
+    pub fn with_time(origin: Point, direction: Vector, time: f64) -> Ray {
+        Ray {origin, direction, time}
     }
 
     /// This fuctions work as follows: P(t) = origin + t * direction.
@@ -16,4 +25,4 @@ impl Ray {
     pub fn at(&self, t: f64) -> Point {
         self.origin + t * self.direction
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,641 @@
+use crate::engine::camera::Camera;
+use crate::engine::integrators::{Integrator, IntegratorKind};
+use crate::engine::samplers::{SampleDimensions, Sampler, SamplerKind};
+use crate::engine::utils::random_seed;
+use crate::engine::filters::{Filter, FilterKind};
+use crate::engine::framebuffer::{FilteredPixel, Framebuffer, PixelStatistics};
+use crate::engine::{Background, Rng, Scene};
+use crate::utils::Image;
+use crate::vectors::Color;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The image is split into square tiles of this size, which are rendered in parallel.
+const TILE_SIZE: usize = 32;
+
+/// How many extra samples every round of adaptive sampling adds to each noisy pixel.
+const ADAPTIVE_ROUND_SAMPLES: usize = 16;
+
+/// Parameters which control how an image is rendered.
+#[derive(Copy, Clone, Debug)]
+pub struct RenderSettings {
+    pub width: usize,
+    pub height: usize,
+    pub samples_per_pixel: usize,
+
+    /// Maximum amount of times a ray may scatter before it's considered absorbed.
+    pub max_depth: usize,
+    pub background: Background,
+
+    /// The algorithm which computes the color of each sample.
+    pub integrator: IntegratorKind,
+
+    /// How the random numbers of the samples of a pixel are spread out.
+    pub sampler: SamplerKind,
+
+    /// How the samples are weighted into the pixels around them.
+    pub filter: FilterKind,
+    pub threads_count: usize,
+
+    /// Rendering with the same seed always produces the same image.
+    /// When not set, a random seed is used.
+    pub seed: Option<u64>,
+
+    /// When set, pixels which are still noisy after `samples_per_pixel` samples get more of them.
+    pub adaptive: Option<AdaptiveSampling>,
+}
+
+/// Spends extra samples only where they are needed (see `render_framebuffer`).
+#[derive(Copy, Clone, Debug)]
+pub struct AdaptiveSampling {
+    /// Pixels whose relative error (see `PixelStatistics::relative_error`) is above this
+    /// keep getting more samples.
+    pub noise_threshold: f64,
+
+    /// The most samples a single pixel may take, including the regular ones.
+    pub max_samples: usize,
+}
+
+/// Controls a progressive render (see `render_progressive`).
+#[derive(Copy, Clone, Debug)]
+pub struct ProgressiveSettings {
+    /// How many samples every pass adds to each pixel which isn't done yet.
+    pub samples_per_pass: usize,
+
+    /// Stop after the pass during which this much time has passed.
+    pub time_limit: Option<Duration>,
+
+    /// A pixel is done once its relative error (see `PixelStatistics::relative_error`) is
+    /// below this threshold. When not set, every pixel takes all of its samples.
+    pub noise_threshold: Option<f64>,
+
+    /// The error estimate isn't trusted before a pixel has this many samples, since a few
+    /// samples which all missed a small light would look perfectly converged.
+    pub min_samples: usize,
+}
+
+/// The progress of a progressive render, reported after every pass.
+#[derive(Copy, Clone, Debug)]
+pub struct PassReport {
+    /// The number of the pass which was just finished, starting from 1.
+    pub pass: usize,
+    pub elapsed: Duration,
+
+    /// The amount of pixels which still need more samples.
+    pub active_pixels: usize,
+}
+
+/// A rectangular part of the image, rendered by a single thread.
+struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+/// The filtered samples of a tile, which also reach the pixels around it, as far as the
+/// filter's radius. The pixels are stored row by row, like in the framebuffer.
+struct TileFilm {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    image_height: usize,
+    pixels: Vec<FilteredPixel>,
+}
+
+/// Everything needed for taking the samples of a pixel.
+struct RenderContext<'r, 's> {
+    scene: &'r Scene<'s>,
+    camera: &'r Camera,
+    settings: &'r RenderSettings,
+    integrator: &'r dyn Integrator,
+    sampler: Option<Arc<dyn Sampler>>,
+    filter: Box<dyn Filter>,
+    seed: u64,
+}
+
+/// The color of a sample, and where it was taken, in pixels from the image's bottom-left corner
+/// (like the camera's coordinates, where rows go upwards), so that the sample is at an offset
+/// in [-0.5, 0.5) from the center of its pixel along both axes.
+struct Sample {
+    color: Color,
+    x: f64,
+    y: f64,
+}
+
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        RenderSettings {
+            width: 1920,
+            height: 1080,
+            samples_per_pixel: 80,
+            max_depth: 50,
+            background: Background::sky(),
+            integrator: IntegratorKind::default(),
+            sampler: SamplerKind::default(),
+            filter: FilterKind::default(),
+            threads_count: thread::available_parallelism().map_or(1, |count| count.get()),
+            seed: None,
+            adaptive: None,
+        }
+    }
+}
+
+/// Render the scene as seen from the camera, with the integrator chosen in the settings.
+/// The returned image holds the average of the samples of every pixel, without any gamma correction.
+pub fn render(scene: &Scene, camera: &Camera, settings: &RenderSettings) -> Image {
+    render_framebuffer(scene, camera, settings).to_image()
+}
+
+/// Render the scene with a custom integrator, instead of the one chosen in the settings.
+pub fn render_with_integrator(scene: &Scene, camera: &Camera, settings: &RenderSettings, integrator: &dyn Integrator) -> Image {
+    render_framebuffer_with_integrator(scene, camera, settings, integrator).to_image()
+}
+
+/// Render the scene like `render`, but return the samples statistics of every pixel
+/// instead of just the image, for example for seeing where adaptive sampling spent its samples.
+///
+/// Every pixel first takes `samples_per_pixel` samples. With adaptive sampling, the pixels whose
+/// estimated error is still above the threshold then get more samples in rounds, until they are
+/// either below the threshold or have `max_samples` samples.
+pub fn render_framebuffer(scene: &Scene, camera: &Camera, settings: &RenderSettings) -> Framebuffer {
+    let integrator = settings.integrator.create(settings);
+    render_framebuffer_with_integrator(scene, camera, settings, integrator.as_ref())
+}
+
+fn render_framebuffer_with_integrator(
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    integrator: &dyn Integrator,
+) -> Framebuffer {
+    let context = RenderContext::new(scene, camera, settings, integrator);
+    let tiles = split_into_tiles(settings.width, settings.height);
+    let mut framebuffer = Framebuffer::new(settings.width, settings.height);
+
+    add_samples(&context, &tiles, &mut framebuffer, settings.samples_per_pixel, settings.samples_per_pixel, |_| true);
+
+    if let Some(adaptive) = settings.adaptive {
+        let needs_samples = |pixel: &PixelStatistics| adaptive.needs_samples(pixel);
+        while framebuffer.pixels().iter().any(needs_samples) {
+            add_samples(&context, &tiles, &mut framebuffer, ADAPTIVE_ROUND_SAMPLES, adaptive.max_samples, needs_samples);
+        }
+    }
+
+    framebuffer
+}
+
+/// Render the image in passes, where every pass adds a few samples to each pixel which still
+/// needs them, until either every pixel is done or the time limit is reached. A pixel is done
+/// once it has `samples_per_pixel` samples, or once its estimated error falls below the noise
+/// threshold, so simple regions stop sampling early. The adaptive sampling settings aren't
+/// used, since the progressive settings have their own noise threshold.
+///
+/// `on_pass` is called with the framebuffer after every pass, for example for saving the
+/// intermediate image. Samples are seeded the same way as in `render`, so a progressive render
+/// which takes all of the samples gives the same image (up to rounding with filters wider than
+/// a pixel, since the neighbours' samples are added in a different order).
+pub fn render_progressive<F: FnMut(&PassReport, &Framebuffer)>(
+    scene: &Scene,
+    camera: &Camera,
+    settings: &RenderSettings,
+    progressive: &ProgressiveSettings,
+    mut on_pass: F,
+) -> Framebuffer {
+    let integrator = settings.integrator.create(settings);
+    let context = RenderContext::new(scene, camera, settings, integrator.as_ref());
+    let tiles = split_into_tiles(settings.width, settings.height);
+    let mut framebuffer = Framebuffer::new(settings.width, settings.height);
+    let start = Instant::now();
+
+    for pass in 1.. {
+        add_samples(
+            &context,
+            &tiles,
+            &mut framebuffer,
+            progressive.samples_per_pass,
+            settings.samples_per_pixel,
+            |pixel| progressive.needs_samples(pixel, settings.samples_per_pixel),
+        );
+
+        let report = PassReport {
+            pass,
+            elapsed: start.elapsed(),
+            active_pixels: framebuffer.pixels().iter()
+                .filter(|pixel| progressive.needs_samples(pixel, settings.samples_per_pixel))
+                .count(),
+        };
+        on_pass(&report, &framebuffer);
+
+        let is_out_of_time = progressive.time_limit.is_some_and(|time_limit| report.elapsed >= time_limit);
+        if report.active_pixels == 0 || is_out_of_time {
+            break;
+        }
+    }
+
+    framebuffer
+}
+
+/// Add up to `samples` samples to every pixel for which `needs_samples` is true, without going
+/// over `max_samples` samples per pixel. The samples continue the pixel's sample indices, so
+/// taking them over several calls gives the same result as taking them all at once.
+///
+/// Every tile splats its samples into its own film, which starts from the framebuffer's values
+/// for the tile's pixels. The films are merged in the order of the tiles, first over the pixels of
+/// their tiles and then into the neighbouring tiles, so the image doesn't depend on the threads.
+fn add_samples<N: Fn(&PixelStatistics) -> bool + Sync>(
+    context: &RenderContext,
+    tiles: &[Tile],
+    framebuffer: &mut Framebuffer,
+    samples: usize,
+    max_samples: usize,
+    needs_samples: N,
+) {
+    let margin = (context.filter.radius() - 0.5).ceil().max(0.0) as usize;
+
+    let results = render_tiles(tiles, context.settings.threads_count, |tile| {
+        let mut film = TileFilm::new(tile, margin, framebuffer);
+        let pixels = tile.pixels()
+            .map(|(x, y)| {
+                let mut pixel = *framebuffer.pixel(x, y);
+                if needs_samples(&pixel) {
+                    let end = (pixel.count + samples).min(max_samples);
+                    for sample_index in pixel.count..end {
+                        let sample = context.sample(x, y, sample_index);
+                        pixel.add(sample.color);
+                        film.splat(&sample, context.filter.as_ref());
+                    }
+                }
+                pixel
+            })
+            .collect::<Vec<PixelStatistics>>();
+
+        (pixels, film)
+    });
+
+    for (tile, (pixels, film)) in tiles.iter().zip(&results) {
+        for ((x, y), pixel) in tile.pixels().zip(pixels) {
+            *framebuffer.pixel_mut(x, y) = *pixel;
+            *framebuffer.filtered_pixel_mut(x, y) = *film.pixel(x, y);
+        }
+    }
+
+    for (tile, (_, film)) in tiles.iter().zip(&results) {
+        for (x, y) in film.pixels().filter(|&(x, y)| !tile.contains(x, y)) {
+            framebuffer.filtered_pixel_mut(x, y).merge(film.pixel(x, y));
+        }
+    }
+}
+
+impl AdaptiveSampling {
+    /// Whether the pixel should get more samples in the next round.
+    pub fn needs_samples(&self, pixel: &PixelStatistics) -> bool {
+        pixel.count < self.max_samples && pixel.relative_error() > self.noise_threshold
+    }
+}
+
+impl ProgressiveSettings {
+    /// Whether the pixel should get more samples in the next pass.
+    pub fn needs_samples(&self, pixel: &PixelStatistics, max_samples: usize) -> bool {
+        if pixel.count >= max_samples {
+            return false;
+        }
+
+        match self.noise_threshold {
+            Some(threshold) => pixel.count < self.min_samples || pixel.relative_error() > threshold,
+            None => true
+        }
+    }
+}
+
+impl Default for ProgressiveSettings {
+    fn default() -> Self {
+        ProgressiveSettings {
+            samples_per_pass: 4,
+            time_limit: None,
+            noise_threshold: None,
+            min_samples: 16,
+        }
+    }
+}
+
+impl<'r, 's> RenderContext<'r, 's> {
+    fn new(scene: &'r Scene<'s>, camera: &'r Camera, settings: &'r RenderSettings, integrator: &'r dyn Integrator) -> RenderContext<'r, 's> {
+        let seed = settings.seed.unwrap_or_else(random_seed);
+        RenderContext {
+            scene,
+            camera,
+            settings,
+            integrator,
+            sampler: settings.sampler.create(settings, seed),
+            filter: settings.filter.create(),
+            seed,
+        }
+    }
+
+    /// Take a single sample of the pixel at (x, y), where y goes downwards from the top row.
+    /// Every sample draws from its own generator, seeded by the pixel and sample indices, so the
+    /// result doesn't depend on the tiling, or on which thread rendered which tile. With a sampler,
+    /// the generator takes the sampler's values for the dimensions of the camera and the bounces.
+    fn sample(&self, x: usize, y: usize, sample_index: usize) -> Sample {
+        let settings = self.settings;
+        // The camera's vertical coordinate goes upwards, while image rows go downwards.
+        let j = settings.height - 1 - y;
+        let pixel_index = y * settings.width + x;
+
+        let mut rng = Rng::for_sample(self.seed, pixel_index, sample_index);
+        if let Some(sampler) = &self.sampler {
+            rng = rng.with_sampler(Arc::clone(sampler), x, y, sample_index);
+        }
+
+        rng.use_dimensions(SampleDimensions::Pixel);
+        let random_bias_x = rng.float();
+        let random_bias_y = rng.float();
+
+        let u = (x as f64 + random_bias_x) / ((settings.width - 1) as f64);
+        let v = (j as f64 + random_bias_y) / ((settings.height - 1) as f64);
+
+        let ray = self.camera.get_ray(u, v, &mut rng);
+        Sample {
+            color: self.integrator.radiance(&ray, self.scene, &settings.background, &mut rng),
+            x: x as f64 + random_bias_x,
+            y: j as f64 + random_bias_y,
+        }
+    }
+}
+
+impl TileFilm {
+    /// An empty film for the tile and the pixels around it, except that the tile's own pixels
+    /// start with the framebuffer's values, so they keep adding up in the same order.
+    fn new(tile: &Tile, margin: usize, framebuffer: &Framebuffer) -> TileFilm {
+        let x = tile.x.saturating_sub(margin);
+        let y = tile.y.saturating_sub(margin);
+        let mut film = TileFilm {
+            x,
+            y,
+            width: (tile.x + tile.width + margin).min(framebuffer.width()) - x,
+            height: (tile.y + tile.height + margin).min(framebuffer.height()) - y,
+            image_height: framebuffer.height(),
+            pixels: Vec::new(),
+        };
+
+        film.pixels = film.pixels()
+            .map(|(x, y)| if tile.contains(x, y) { *framebuffer.filtered_pixel(x, y) } else { FilteredPixel::new() })
+            .collect();
+        film
+    }
+
+    /// Add the sample to every pixel of the film within the filter's radius.
+    fn splat(&mut self, sample: &Sample, filter: &dyn Filter) {
+        let radius = filter.radius();
+        // The vertical position of the center of the top row, measured upwards like the sample's,
+        // so the center of row y is at top_center - y.
+        let top_center = self.image_height as f64 - 0.5;
+        let first_x = (sample.x - 0.5 - radius).floor().max(self.x as f64) as usize;
+        let last_x = (sample.x - 0.5 + radius).ceil().min((self.x + self.width - 1) as f64);
+        let first_y = (top_center - sample.y - radius).floor().max(self.y as f64) as usize;
+        let last_y = (top_center - sample.y + radius).ceil().min((self.y + self.height - 1) as f64);
+        if last_x < first_x as f64 || last_y < first_y as f64 {
+            return;
+        }
+
+        for y in first_y..=last_y as usize {
+            let weight_y = filter.weight(sample.y - (top_center - y as f64));
+            if weight_y == 0.0 {
+                continue;
+            }
+
+            for x in first_x..=last_x as usize {
+                let weight = filter.weight(sample.x - (x as f64 + 0.5)) * weight_y;
+                if weight != 0.0 {
+                    let index = (y - self.y) * self.width + (x - self.x);
+                    self.pixels[index].add(sample.color, weight);
+                }
+            }
+        }
+    }
+
+    fn pixel(&self, x: usize, y: usize) -> &FilteredPixel {
+        &self.pixels[(y - self.y) * self.width + (x - self.x)]
+    }
+
+    /// The (x, y) coordinates of the film's pixels, row by row from its top-left corner.
+    fn pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.y..self.y + self.height).flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
+
+impl Tile {
+    /// The (x, y) coordinates of the tile's pixels, row by row from its top-left corner.
+    fn pixels(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (self.y..self.y + self.height).flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+
+    fn contains(&self, x: usize, y: usize) -> bool {
+        (self.x..self.x + self.width).contains(&x) && (self.y..self.y + self.height).contains(&y)
+    }
+}
+
+/// Run the function on every tile, spread over the given amount of threads.
+/// The results are returned in the same order as the tiles.
+fn render_tiles<R: Send, F: Fn(&Tile) -> R + Sync>(tiles: &[Tile], threads_count: usize, render_tile: F) -> Vec<R> {
+    let next_tile_index = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(tiles.len()));
+
+    thread::scope(|scope| {
+        for _ in 0..threads_count.max(1) {
+            scope.spawn(|| loop {
+                let tile_index = next_tile_index.fetch_add(1, Ordering::Relaxed);
+                if tile_index >= tiles.len() {
+                    break;
+                }
+
+                let result = render_tile(&tiles[tile_index]);
+                results.lock().unwrap().push((tile_index, result));
+            });
+        }
+    });
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(tile_index, _)| *tile_index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+fn split_into_tiles(image_width: usize, image_height: usize) -> Vec<Tile> {
+    let mut tiles = Vec::new();
+    for y in (0..image_height).step_by(TILE_SIZE) {
+        for x in (0..image_width).step_by(TILE_SIZE) {
+            tiles.push(Tile {
+                x,
+                y,
+                width: TILE_SIZE.min(image_width - x),
+                height: TILE_SIZE.min(image_height - y),
+            });
+        }
+    }
+
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::{HittableCollection, Sphere};
+    use crate::engine::Ray;
+    use crate::engine::materials::Lambertian;
+    use crate::vectors::{Point, Vector};
+
+    const SIZE: (usize, usize) = (70, 40);
+
+    fn settings(background: Background) -> RenderSettings {
+        RenderSettings {
+            width: SIZE.0,
+            height: SIZE.1,
+            samples_per_pixel: 4,
+            max_depth: 5,
+            background,
+            threads_count: 1,
+            seed: Some(3),
+            ..RenderSettings::default()
+        }
+    }
+
+    fn camera() -> Camera {
+        Camera::new(Point::new(0.0, 0.0, 3.0), Point::zeroes(), Vector::new(0.0, 1.0, 0.0),
+                    40.0, SIZE.0 as f64 / SIZE.1 as f64, 0.0, 3.0)
+    }
+
+    /// Nothing but the background, so every sample has the background's color.
+    fn empty_scene() -> Scene<'static> {
+        Scene::new(Box::new(HittableCollection::new()))
+    }
+
+    /// A diffuse sphere under the sky, which is noisy around its edges and in its shadowed side.
+    fn sphere_scene() -> Scene<'static> {
+        let mut world = HittableCollection::new();
+        world.add(Box::new(Sphere::new(Point::zeroes(), 0.8, Arc::new(Lambertian::new(Color::new(0.5, 0.3, 0.2))))));
+        Scene::new(Box::new(world))
+    }
+
+    fn assert_same_images(image: &Image, expected: &Image) {
+        for (index, (color, expected)) in image.pixels().iter().zip(expected.pixels()).enumerate() {
+            assert_eq!((color.red, color.green, color.blue), (expected.red, expected.green, expected.blue), "pixel {}", index);
+        }
+    }
+
+    /// Every sample gets a random gray, so pixels never look converged.
+    struct NoiseIntegrator;
+
+    impl Integrator for NoiseIntegrator {
+        fn radiance(&self, _ray: &Ray, _scene: &Scene, _background: &Background, rng: &mut Rng) -> Color {
+            let value = rng.float();
+            Color::new(value, value, value)
+        }
+    }
+
+    #[test]
+    fn box_filter_gives_the_average_of_every_pixel() {
+        let framebuffer = render_framebuffer(&sphere_scene(), &camera(), &settings(Background::sky()));
+        let averages = Image::from_pixels(SIZE.0, SIZE.1, framebuffer.pixels().iter().map(PixelStatistics::mean).collect());
+
+        assert_same_images(&framebuffer.to_image(), &averages);
+    }
+
+    #[test]
+    fn splatting_across_tiles_keeps_a_constant_image_constant() {
+        let color = Color::new(0.25, 0.5, 0.75);
+        for name in FilterKind::NAMES {
+            let settings = RenderSettings {
+                filter: FilterKind::from_name(name).unwrap(),
+                threads_count: 3,
+                ..settings(Background::Solid(color))
+            };
+            let image = render(&empty_scene(), &camera(), &settings);
+
+            for (index, pixel) in image.pixels().iter().enumerate() {
+                let error = (pixel.red - color.red).abs() + (pixel.green - color.green).abs() + (pixel.blue - color.blue).abs();
+                assert!(error < 1e-12, "{} filter, pixel {}: {:?}", name, index, pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn images_dont_depend_on_the_threads_count() {
+        for name in FilterKind::NAMES {
+            let settings = RenderSettings { filter: FilterKind::from_name(name).unwrap(), ..settings(Background::sky()) };
+            let expected = render(&sphere_scene(), &camera(), &settings);
+            let image = render(&sphere_scene(), &camera(), &RenderSettings { threads_count: 4, ..settings });
+
+            assert_same_images(&image, &expected);
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_stops_at_samples_per_pixel_when_pixels_are_converged() {
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling { noise_threshold: 0.01, max_samples: 100 }),
+            ..settings(Background::Solid(Color::new(0.2, 0.4, 0.6)))
+        };
+        let framebuffer = render_framebuffer(&empty_scene(), &camera(), &settings);
+
+        assert!(framebuffer.pixels().iter().all(|pixel| pixel.count == settings.samples_per_pixel));
+    }
+
+    #[test]
+    fn adaptive_sampling_stops_at_max_samples_when_pixels_stay_noisy() {
+        // The rounds add 16 samples each, which doesn't divide the remaining 60 samples.
+        let settings = RenderSettings {
+            adaptive: Some(AdaptiveSampling { noise_threshold: 0.01, max_samples: 64 }),
+            ..settings(Background::sky())
+        };
+        let framebuffer = render_framebuffer_with_integrator(&empty_scene(), &camera(), &settings, &NoiseIntegrator);
+
+        assert!(framebuffer.pixels().iter().all(|pixel| pixel.count == 64 && pixel.relative_error() > 0.01));
+    }
+
+    #[test]
+    fn complete_progressive_render_matches_render() {
+        let settings = RenderSettings { samples_per_pixel: 10, ..settings(Background::sky()) };
+        let progressive = ProgressiveSettings { samples_per_pass: 4, ..ProgressiveSettings::default() };
+        let mut passes = Vec::new();
+        let framebuffer = render_progressive(&sphere_scene(), &camera(), &settings, &progressive, |report, framebuffer| {
+            passes.push((report.pass, report.active_pixels, framebuffer.samples_count()));
+        });
+
+        // 4 + 4 + 2 samples, where the last pass is cut short by samples_per_pixel.
+        let pixels_count = SIZE.0 * SIZE.1;
+        assert_eq!(passes, vec![(1, pixels_count, 4 * pixels_count), (2, pixels_count, 8 * pixels_count), (3, 0, 10 * pixels_count)]);
+        assert_same_images(&framebuffer.to_image(), &render(&sphere_scene(), &camera(), &settings));
+    }
+
+    #[test]
+    fn progressive_render_stops_at_the_time_limit() {
+        let settings = RenderSettings { samples_per_pixel: 100, ..settings(Background::sky()) };
+        let progressive = ProgressiveSettings { time_limit: Some(Duration::ZERO), ..ProgressiveSettings::default() };
+        let mut passes_count = 0;
+        let framebuffer = render_progressive(&sphere_scene(), &camera(), &settings, &progressive, |_, _| passes_count += 1);
+
+        assert_eq!(passes_count, 1);
+        assert!(framebuffer.pixels().iter().all(|pixel| pixel.count == progressive.samples_per_pass));
+    }
+
+    #[test]
+    fn progressive_render_stops_sampling_converged_pixels() {
+        // The corners of the image only see the solid background, which is converged as soon as
+        // the error estimate is trusted, while the sphere in the middle stays noisy.
+        let settings = RenderSettings { samples_per_pixel: 64, ..settings(Background::Solid(Color::new(0.2, 0.4, 0.6))) };
+        let progressive = ProgressiveSettings { noise_threshold: Some(0.001), min_samples: 8, ..ProgressiveSettings::default() };
+        let mut last_report = None;
+        let framebuffer = render_progressive(&sphere_scene(), &camera(), &settings, &progressive, |report, _| last_report = Some(*report));
+
+        let counts = framebuffer.pixels().iter().map(|pixel| pixel.count).collect::<Vec<usize>>();
+        assert_eq!(last_report.unwrap().active_pixels, 0);
+        assert_eq!(counts[0], progressive.min_samples);
+        assert!(counts.iter().all(|&count| count >= progressive.min_samples && count <= settings.samples_per_pixel));
+        assert!(counts.contains(&settings.samples_per_pixel));
+    }
+}
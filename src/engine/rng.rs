@@ -0,0 +1,124 @@
+use crate::engine::samplers::{SampleDimensions, Sampler};
+use std::sync::Arc;
+
+/// Multiplier of the underlying linear congruential generator.
+const PCG_MULTIPLIER: u64 = 6364136223846793005;
+
+/// Used when no specific stream is requested.
+const DEFAULT_STREAM: u64 = 0xDA3E_39CB_94B9_5BDB;
+
+/// A small, fast and seedable random number generator (PCG32, XSH-RR variant).
+/// The generator is passed explicitly to everything that needs randomness, so
+/// a render only depends on its seed, and not on which thread drew what.
+///
+/// The generator of a pixel sample can also take its numbers from a `Sampler`: after
+/// `use_dimensions`, the next numbers are the sampler's values for those dimensions,
+/// and only once they run out does the generator go back to its own sequence.
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+    increment: u64,
+    sample: Option<SampleSource>,
+}
+
+/// The sampler dimensions a pixel sample's generator currently draws from.
+#[derive(Clone, Debug)]
+struct SampleSource {
+    sampler: Arc<dyn Sampler>,
+    x: usize,
+    y: usize,
+    sample_index: usize,
+    dimension: usize,
+    end: usize,
+}
+
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng::with_stream(seed, DEFAULT_STREAM)
+    }
+
+    /// Generators with the same seed but different streams produce independent sequences.
+    pub fn with_stream(seed: u64, stream: u64) -> Rng {
+        let mut rng = Rng { state: 0, increment: (stream << 1) | 1, sample: None };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+
+        rng
+    }
+
+    /// Get the generator of a single sample of a pixel. Every sample gets its own
+    /// generator, so it doesn't matter in which order (or how many) samples are taken.
+    pub fn for_sample(seed: u64, pixel_index: usize, sample_index: usize) -> Rng {
+        Rng::with_stream(mix(seed ^ mix(sample_index as u64)), pixel_index as u64)
+    }
+
+    /// Get a generator seeded by hashing the given values, for code which has no generator
+    /// passed to it. The same values always give the same sequence.
+    pub fn from_hash(values: &[u64]) -> Rng {
+        Rng::new(Rng::hash(values))
+    }
+
+    /// Hash the values into a well mixed number, which changes completely whenever any of them does.
+    pub fn hash(values: &[u64]) -> u64 {
+        values.iter().fold(0, |hash, &value| mix(hash ^ value))
+    }
+
+    /// Let the generator of the sample of the pixel at (x, y) take its numbers from the sampler,
+    /// for the dimensions chosen with `use_dimensions`.
+    pub fn with_sampler(mut self, sampler: Arc<dyn Sampler>, x: usize, y: usize, sample_index: usize) -> Rng {
+        self.sample = Some(SampleSource { sampler, x, y, sample_index, dimension: 0, end: 0 });
+        self
+    }
+
+    /// Draw the next numbers from the sampler's dimensions for the given part of the path.
+    /// Does nothing for generators without a sampler.
+    pub fn use_dimensions(&mut self, dimensions: SampleDimensions) {
+        if let Some(source) = &mut self.sample {
+            let range = dimensions.range();
+            source.dimension = range.start;
+            source.end = range.end;
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let state = self.state;
+        self.state = state.wrapping_mul(PCG_MULTIPLIER).wrapping_add(self.increment);
+
+        let xor_shifted = (((state >> 18) ^ state) >> 27) as u32;
+        let rotation = (state >> 59) as u32;
+        xor_shifted.rotate_right(rotation)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        ((self.next_u32() as u64) << 32) | self.next_u32() as u64
+    }
+
+    /// Generate a float in the [0, 1) range.
+    pub fn float(&mut self) -> f64 {
+        if let Some(source) = &mut self.sample {
+            if source.dimension < source.end {
+                let value = source.sampler.sample(source.x, source.y, source.sample_index, source.dimension);
+                source.dimension += 1;
+                return value;
+            }
+        }
+
+        // The top 53 bits fill the whole mantissa of an f64.
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Generate a float in the [min_value, max_value) range.
+    pub fn range(&mut self, min_value: f64, max_value: f64) -> f64 {
+        min_value + (max_value - min_value) * self.float()
+    }
+}
+
+/// SplitMix64 finalizer, spreads similar inputs (such as consecutive indices) apart.
+fn mix(value: u64) -> u64 {
+    let mut value = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    value ^ (value >> 31)
+}
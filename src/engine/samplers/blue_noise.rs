@@ -0,0 +1,157 @@
+use crate::engine::samplers::scrambling::ONE_MINUS_EPSILON;
+use crate::engine::samplers::sobol::sobol_owen;
+use crate::engine::samplers::Sampler;
+use crate::engine::Rng;
+use std::sync::OnceLock;
+
+/// The width and height of the blue noise mask, which is tiled over the image.
+const MASK_SIZE: usize = 64;
+
+/// How far the points of the mask push each other away while it's generated.
+const MASK_SIGMA: f64 = 1.5;
+
+/// The mask doesn't depend on the render seed, only the way it's shifted does.
+const MASK_SEED: u64 = 0x5EED_B10E;
+
+/// Generating the mask takes a while, so it's generated once and shared by every sampler.
+static MASK: OnceLock<Vec<f64>> = OnceLock::new();
+
+/// Every pixel takes the same Owen scrambled Sobol points, shifted (modulo 1) by the value of a
+/// blue noise mask at the pixel, where every dimension uses the mask with a different offset.
+/// Neighbouring pixels get very different shifts, so their errors don't add up into blotches,
+/// and the noise which is left looks like an even, fine grain, which is most noticeable at
+/// low sample counts ("Blue-noise Dithered Sampling", Georgiev and Fajardo).
+#[derive(Debug)]
+pub struct BlueNoiseSampler {
+    seed: u64,
+    mask: &'static [f64],
+}
+
+
+impl BlueNoiseSampler {
+    pub fn new(seed: u64) -> BlueNoiseSampler {
+        BlueNoiseSampler { seed, mask: MASK.get_or_init(|| void_and_cluster(&mut Rng::new(MASK_SEED))) }
+    }
+}
+
+
+impl Sampler for BlueNoiseSampler {
+    fn sample(&self, x: usize, y: usize, sample_index: usize, dimension: usize) -> f64 {
+        let offset = Rng::hash(&[self.seed, dimension as u64]);
+        let mask_x = (x + (offset as usize) % MASK_SIZE) % MASK_SIZE;
+        let mask_y = (y + ((offset >> 32) as usize) % MASK_SIZE) % MASK_SIZE;
+
+        let value = sobol_owen(sample_index as u32, dimension, self.seed) + self.mask[mask_y * MASK_SIZE + mask_x];
+        value.fract().min(ONE_MINUS_EPSILON)
+    }
+}
+
+/// Generate a tileable blue noise mask with values in (0, 1) using Ulichney's void-and-cluster
+/// method: pixels are ranked by repeatedly filling the largest void between the pixels which
+/// were already ranked, so pixels with close values are always far apart.
+fn void_and_cluster(rng: &mut Rng) -> Vec<f64> {
+    let mut pattern = Pattern::new();
+
+    // Start with a few random points, and spread them evenly by moving the point from
+    // the tightest cluster into the largest void until that doesn't change anything.
+    let initial_count = pattern.points.len() / 10;
+    while pattern.count < initial_count {
+        let index = rng.next_u32() as usize % pattern.points.len();
+        if !pattern.points[index] {
+            pattern.toggle(index);
+        }
+    }
+    for _ in 0..pattern.points.len() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    // The initial points are ranked by removing them one by one from the tightest cluster,
+    // and all of the others by filling the largest void.
+    let mut ranks = vec![0; pattern.points.len()];
+    let initial_pattern = pattern.clone();
+    for rank in (0..initial_count).rev() {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        ranks[cluster] = rank;
+    }
+
+    pattern = initial_pattern;
+    for rank in initial_count..ranks.len() {
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        ranks[void] = rank;
+    }
+
+    ranks.iter().map(|&rank| (rank as f64 + 0.5) / ranks.len() as f64).collect()
+}
+
+/// A binary pattern on a torus, along with how crowded the area around every pixel is.
+#[derive(Clone)]
+struct Pattern {
+    points: Vec<bool>,
+    count: usize,
+    /// The sum of a Gaussian of the distance from the pixel to every point.
+    energy: Vec<f64>,
+    /// The Gaussian of every offset between two pixels, wrapping around the edges.
+    kernel: Vec<f64>,
+}
+
+
+impl Pattern {
+    fn new() -> Pattern {
+        let wrapped_distance = |offset: usize| offset.min(MASK_SIZE - offset) as f64;
+        let mut kernel = vec![0.0; MASK_SIZE * MASK_SIZE];
+        for dy in 0..MASK_SIZE {
+            for dx in 0..MASK_SIZE {
+                let squared_distance = wrapped_distance(dx).powi(2) + wrapped_distance(dy).powi(2);
+                kernel[dy * MASK_SIZE + dx] = (-squared_distance / (2.0 * MASK_SIGMA * MASK_SIGMA)).exp();
+            }
+        }
+
+        Pattern {
+            points: vec![false; MASK_SIZE * MASK_SIZE],
+            count: 0,
+            energy: vec![0.0; MASK_SIZE * MASK_SIZE],
+            kernel,
+        }
+    }
+
+    /// Add a point at the index, or remove it if there's already one there.
+    fn toggle(&mut self, index: usize) {
+        let sign = if self.points[index] { -1.0 } else { 1.0 };
+        self.points[index] = !self.points[index];
+        self.count = if self.points[index] { self.count + 1 } else { self.count - 1 };
+
+        let (x, y) = (index % MASK_SIZE, index / MASK_SIZE);
+        for other_y in 0..MASK_SIZE {
+            let dy = (other_y + MASK_SIZE - y) % MASK_SIZE;
+            for other_x in 0..MASK_SIZE {
+                let dx = (other_x + MASK_SIZE - x) % MASK_SIZE;
+                self.energy[other_y * MASK_SIZE + other_x] += sign * self.kernel[dy * MASK_SIZE + dx];
+            }
+        }
+    }
+
+    /// The point with the most energy around it.
+    fn tightest_cluster(&self) -> usize {
+        self.find_extreme(true, |energy, best| energy > best)
+    }
+
+    /// The empty pixel with the least energy around it.
+    fn largest_void(&self) -> usize {
+        self.find_extreme(false, |energy, best| energy < best)
+    }
+
+    fn find_extreme<F: Fn(f64, f64) -> bool>(&self, is_point: bool, is_better: F) -> usize {
+        (0..self.points.len())
+            .filter(|&index| self.points[index] == is_point)
+            .reduce(|best, index| if is_better(self.energy[index], self.energy[best]) { index } else { best })
+            .expect("The pattern is neither empty nor full")
+    }
+}
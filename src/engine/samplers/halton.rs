@@ -0,0 +1,62 @@
+use crate::engine::samplers::scrambling::{permutation_element, ONE_MINUS_EPSILON};
+use crate::engine::samplers::{Sampler, SAMPLE_DIMENSIONS};
+use crate::engine::Rng;
+
+/// The Halton sequence, where every dimension is the radical inverse of the sample index in
+/// the next prime base. Every pixel Owen scrambles the digits with its own seed, so pixels
+/// get different points, and the correlation between the high dimensions is broken up.
+#[derive(Debug)]
+pub struct HaltonSampler {
+    seed: u64,
+    primes: Vec<u64>,
+}
+
+
+impl HaltonSampler {
+    pub fn new(seed: u64) -> HaltonSampler {
+        HaltonSampler { seed, primes: first_primes(SAMPLE_DIMENSIONS) }
+    }
+}
+
+
+impl Sampler for HaltonSampler {
+    fn sample(&self, x: usize, y: usize, sample_index: usize, dimension: usize) -> f64 {
+        let seed = Rng::hash(&[self.seed, x as u64, y as u64, dimension as u64]);
+        owen_scrambled_radical_inverse(sample_index as u64, self.primes[dimension], seed)
+    }
+}
+
+/// Mirror the digits of the index in the given base around the decimal point, permuting every
+/// digit with a permutation chosen by the seed and by the digits before it. The digits past the
+/// end of the index are 0, but are permuted as well, so that they aren't all 0 after scrambling.
+fn owen_scrambled_radical_inverse(mut index: u64, base: u64, seed: u64) -> f64 {
+    // Enough digits to fill the whole mantissa of an f64
+    let digits_count = (53.0 / (base as f64).log2()).ceil() as usize;
+    let inverse_base = 1.0 / base as f64;
+
+    let mut reversed_digits: u64 = 0;
+    let mut scale = 1.0;
+    for _ in 0..digits_count {
+        let digit_seed = Rng::hash(&[seed, reversed_digits]);
+        let digit = permutation_element((index % base) as u32, base as u32, digit_seed as u32);
+        index /= base;
+
+        reversed_digits = reversed_digits * base + digit as u64;
+        scale *= inverse_base;
+    }
+
+    (reversed_digits as f64 * scale).min(ONE_MINUS_EPSILON)
+}
+
+fn first_primes(count: usize) -> Vec<u64> {
+    let mut primes: Vec<u64> = Vec::with_capacity(count);
+    let mut candidate = 2;
+    while primes.len() < count {
+        if primes.iter().take_while(|&&prime| prime * prime <= candidate).all(|&prime| candidate % prime != 0) {
+            primes.push(candidate);
+        }
+        candidate += 1;
+    }
+
+    primes
+}
@@ -0,0 +1,12 @@
+pub mod sampler;
+pub mod scrambling;
+pub mod stratified;
+pub mod halton;
+pub mod sobol;
+pub mod blue_noise;
+
+pub use sampler::{SampleDimensions, Sampler, SamplerKind, MAX_SAMPLED_BOUNCES, SAMPLE_DIMENSIONS};
+pub use stratified::StratifiedSampler;
+pub use halton::HaltonSampler;
+pub use sobol::SobolSampler;
+pub use blue_noise::BlueNoiseSampler;
@@ -0,0 +1,105 @@
+use crate::engine::samplers::{BlueNoiseSampler, HaltonSampler, SobolSampler, StratifiedSampler};
+use crate::engine::RenderSettings;
+use std::fmt::Debug;
+use std::ops::Range;
+use std::sync::Arc;
+
+/// The dimensions of the camera come first: the pixel (0, 1), the lens (2, 3) and the time (4).
+/// Bounces start at an even dimension, so their 2D samples line up with pairs of dimensions.
+const FIRST_BOUNCE_DIMENSION: usize = 6;
+
+/// Every bounce uses 3 dimensions for scattering, 4 for sampling the lights
+/// and 1 for Russian roulette.
+const DIMENSIONS_PER_BOUNCE: usize = 8;
+
+/// Deeper bounces draw all of their numbers from the sample's generator, since they add
+/// little to the image, and low discrepancy sequences lose their benefit in high dimensions.
+pub const MAX_SAMPLED_BOUNCES: usize = 8;
+
+/// The amount of dimensions a sampler is asked for.
+pub const SAMPLE_DIMENSIONS: usize = FIRST_BOUNCE_DIMENSION + MAX_SAMPLED_BOUNCES * DIMENSIONS_PER_BOUNCE;
+
+/// Chooses the random numbers of pixel samples, so that the samples of a pixel cover every
+/// dimension evenly, instead of clumping like independent random numbers do.
+/// Samplers are shared between the rendering threads.
+pub trait Sampler: Send + Sync + Debug {
+    /// The value in [0, 1) of one dimension of a sample of the pixel at (x, y). The same
+    /// arguments always give the same value, so samples can be taken in any order.
+    fn sample(&self, x: usize, y: usize, sample_index: usize, dimension: usize) -> f64;
+}
+
+/// The part of a path which a group of dimensions is used for. Each part always gets the
+/// same dimensions, no matter how many numbers the earlier parts of the path used, so that
+/// for example the lens positions of all of a pixel's samples are well distributed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SampleDimensions {
+    /// The position inside of the pixel.
+    Pixel,
+    Lens,
+
+    /// The time during which the shutter is open.
+    Time,
+
+    /// Choosing the direction a material scatters into.
+    Scattering { bounce: usize },
+
+    /// Choosing a light, and a point on it.
+    Light { bounce: usize },
+    Roulette { bounce: usize },
+}
+
+/// The built-in samplers.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum SamplerKind {
+    /// Every number is drawn independently from the sample's generator.
+    #[default]
+    Independent,
+
+    /// Jittered samples, one in each cell of a grid over every pair of dimensions.
+    Stratified,
+
+    /// The Halton sequence, Owen scrambled differently for every pixel.
+    Halton,
+
+    /// The Sobol sequence, Owen scrambled and shuffled differently for every pixel.
+    Sobol,
+
+    /// The same Sobol points in every pixel, shifted by a blue noise mask, so the remaining
+    /// noise looks like fine grain instead of blotches.
+    BlueNoise,
+}
+
+
+impl SampleDimensions {
+    /// The dimensions used for this part of the path, which are empty for bounces past `MAX_SAMPLED_BOUNCES`.
+    pub fn range(&self) -> Range<usize> {
+        let (start, count) = match *self {
+            SampleDimensions::Pixel => (0, 2),
+            SampleDimensions::Lens => (2, 2),
+            SampleDimensions::Time => (4, 1),
+            SampleDimensions::Scattering { bounce } => (bounce_dimension(bounce), 3),
+            SampleDimensions::Light { bounce } => (bounce_dimension(bounce) + 3, 4),
+            SampleDimensions::Roulette { bounce } => (bounce_dimension(bounce) + 7, 1),
+        };
+
+        let end = (start + count).min(SAMPLE_DIMENSIONS);
+        start.min(end)..end
+    }
+}
+
+impl SamplerKind {
+    /// Create the sampler, or None for independent sampling, which only needs the sample's generator.
+    pub fn create(&self, settings: &RenderSettings, seed: u64) -> Option<Arc<dyn Sampler>> {
+        match self {
+            SamplerKind::Independent => None,
+            SamplerKind::Stratified => Some(Arc::new(StratifiedSampler::new(settings.samples_per_pixel, seed))),
+            SamplerKind::Halton => Some(Arc::new(HaltonSampler::new(seed))),
+            SamplerKind::Sobol => Some(Arc::new(SobolSampler::new(seed))),
+            SamplerKind::BlueNoise => Some(Arc::new(BlueNoiseSampler::new(seed))),
+        }
+    }
+}
+
+fn bounce_dimension(bounce: usize) -> usize {
+    FIRST_BOUNCE_DIMENSION + bounce.min(MAX_SAMPLED_BOUNCES) * DIMENSIONS_PER_BOUNCE
+}
@@ -0,0 +1,67 @@
+/// The largest f64 below 1, which sample values are clamped to.
+pub const ONE_MINUS_EPSILON: f64 = 1.0 - f64::EPSILON / 2.0;
+
+/// Convert 32 bits of a fraction into a float in [0, 1).
+pub fn fraction_to_float(bits: u32) -> f64 {
+    bits as f64 / (1u64 << 32) as f64
+}
+
+/// Convert the top 53 bits of a hash into a float in [0, 1).
+pub fn hash_to_float(hash: u64) -> f64 {
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// The element at the given index of a random permutation of 0..length, chosen by the seed
+/// (Kensler's "Correlated Multi-Jittered Sampling"). Each step is invertible on the bits below
+/// the length's power of 2, and values outside of the length are hashed again until they fit.
+pub fn permutation_element(index: u32, length: u32, seed: u32) -> u32 {
+    let mut mask = length.wrapping_sub(1);
+    mask |= mask >> 1;
+    mask |= mask >> 2;
+    mask |= mask >> 4;
+    mask |= mask >> 8;
+    mask |= mask >> 16;
+
+    let mut i = index;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & mask) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & mask) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & mask) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & mask) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & mask) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= mask;
+        i ^= i >> 5;
+
+        if i < length {
+            return ((i as u64 + seed as u64) % length as u64) as u32;
+        }
+    }
+}
+
+/// Owen scramble the bits of a fraction: every bit is flipped or not depending on the seed and
+/// on all of the bits above it, which randomizes the points while keeping them stratified.
+/// Uses Burley's improved Laine-Karras hash ("Practical Hash-based Owen Scrambling").
+pub fn nested_uniform_scramble(bits: u32, seed: u32) -> u32 {
+    laine_karras_permutation(bits.reverse_bits(), seed).reverse_bits()
+}
+
+/// A hash where every bit only depends on the bits below it.
+fn laine_karras_permutation(mut bits: u32, seed: u32) -> u32 {
+    bits = bits.wrapping_add(seed);
+    bits ^= bits.wrapping_mul(0x6c50b47c);
+    bits ^= bits.wrapping_mul(0xb82f1e52);
+    bits ^= bits.wrapping_mul(0xc7afe638);
+    bits ^= bits.wrapping_mul(0x8d22f6e6);
+    bits
+}
@@ -0,0 +1,101 @@
+use crate::engine::samplers::scrambling::{fraction_to_float, nested_uniform_scramble};
+use crate::engine::samplers::Sampler;
+use crate::engine::Rng;
+
+/// The primitive polynomials (degree, coefficients) and initial direction numbers of the
+/// second to fourth Sobol dimensions, from Joe and Kuo. The first dimension is the van der
+/// Corput sequence, which doesn't need them.
+const SOBOL_PARAMETERS: [(usize, u32, [u32; 3]); 3] = [
+    (1, 0, [1, 0, 0]),
+    (2, 1, [1, 3, 0]),
+    (3, 1, [1, 3, 1]),
+];
+
+/// The generator matrices of the first 4 Sobol dimensions, as a direction number for each bit of the index.
+const SOBOL_MATRICES: [[u32; 32]; 4] = sobol_matrices();
+
+/// Owen scrambled Sobol points (see `sobol_owen`), scrambled with a different seed in every pixel.
+#[derive(Debug)]
+pub struct SobolSampler {
+    seed: u64,
+}
+
+
+impl SobolSampler {
+    pub fn new(seed: u64) -> SobolSampler {
+        SobolSampler { seed }
+    }
+}
+
+
+impl Sampler for SobolSampler {
+    fn sample(&self, x: usize, y: usize, sample_index: usize, dimension: usize) -> f64 {
+        sobol_owen(sample_index as u32, dimension, Rng::hash(&[self.seed, x as u64, y as u64]))
+    }
+}
+
+/// A point of the Owen scrambled Sobol sequence, following Burley's "Practical Hash-based Owen
+/// Scrambling": only the first 4 dimensions are used, and every group of 4 dimensions gets its
+/// own scrambling and its own shuffled order of the indices, so the groups are independent.
+/// Shuffling the indices with Owen scrambling keeps every power of 2 of the first samples stratified.
+pub fn sobol_owen(index: u32, dimension: usize, seed: u64) -> f64 {
+    let group_seed = Rng::hash(&[seed, (dimension / 4) as u64]);
+    let shuffled_index = nested_uniform_scramble(index, group_seed as u32);
+
+    let point = sobol(shuffled_index, dimension % 4);
+    let scramble_seed = Rng::hash(&[group_seed, dimension as u64]);
+    fraction_to_float(nested_uniform_scramble(point, scramble_seed as u32))
+}
+
+/// The fraction bits of the index's point in one of the first 4 Sobol dimensions.
+fn sobol(index: u32, dimension: usize) -> u32 {
+    let mut point = 0;
+    let mut bits = index;
+    let mut bit = 0;
+    while bits != 0 {
+        if bits & 1 != 0 {
+            point ^= SOBOL_MATRICES[dimension][bit];
+        }
+        bits >>= 1;
+        bit += 1;
+    }
+
+    point
+}
+
+const fn sobol_matrices() -> [[u32; 32]; 4] {
+    let mut matrices = [[0; 32]; 4];
+
+    let mut bit = 0;
+    while bit < 32 {
+        matrices[0][bit] = 1 << (31 - bit);
+        bit += 1;
+    }
+
+    let mut dimension = 1;
+    while dimension < 4 {
+        let (degree, coefficients, initial) = SOBOL_PARAMETERS[dimension - 1];
+        let directions = &mut matrices[dimension];
+
+        let mut bit = 0;
+        while bit < 32 {
+            directions[bit] = if bit < degree {
+                initial[bit] << (31 - bit)
+            } else {
+                let mut direction = directions[bit - degree] ^ (directions[bit - degree] >> degree);
+                let mut k = 1;
+                while k < degree {
+                    if (coefficients >> (degree - 1 - k)) & 1 != 0 {
+                        direction ^= directions[bit - k];
+                    }
+                    k += 1;
+                }
+                direction
+            };
+            bit += 1;
+        }
+        dimension += 1;
+    }
+
+    matrices
+}
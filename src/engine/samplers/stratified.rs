@@ -0,0 +1,83 @@
+use crate::engine::samplers::scrambling::{hash_to_float, permutation_element, ONE_MINUS_EPSILON};
+use crate::engine::samplers::Sampler;
+use crate::engine::Rng;
+
+/// Splits every dimension into a stratum for each of the pixel's samples, and jitters every
+/// sample inside of its own stratum. The samples visit the strata in a random order, which is
+/// different for every pixel and dimension, so the dimensions aren't correlated and every pair
+/// of dimensions is a Latin hypercube, for any amount of samples.
+///
+/// Samples past `samples_per_pixel` (such as the extra samples of adaptive sampling) start
+/// another round over the same strata, so every `samples_per_pixel` samples are stratified.
+#[derive(Debug)]
+pub struct StratifiedSampler {
+    seed: u64,
+    samples_per_pixel: usize,
+}
+
+
+impl StratifiedSampler {
+    pub fn new(samples_per_pixel: usize, seed: u64) -> StratifiedSampler {
+        StratifiedSampler { seed, samples_per_pixel: samples_per_pixel.max(1) }
+    }
+}
+
+
+impl Sampler for StratifiedSampler {
+    fn sample(&self, x: usize, y: usize, sample_index: usize, dimension: usize) -> f64 {
+        let round = sample_index / self.samples_per_pixel;
+        let dimension_seed = Rng::hash(&[self.seed, x as u64, y as u64, dimension as u64, round as u64]);
+
+        let stratum = permutation_element(
+            (sample_index % self.samples_per_pixel) as u32,
+            self.samples_per_pixel as u32,
+            dimension_seed as u32,
+        );
+
+        let jitter = hash_to_float(Rng::hash(&[dimension_seed, sample_index as u64]));
+        ((stratum as f64 + jitter) / self.samples_per_pixel as f64).min(ONE_MINUS_EPSILON)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_stratum_gets_a_single_sample() {
+        let sampler = StratifiedSampler::new(7, 3);
+
+        for dimension in 0..8 {
+            for round in 0..2 {
+                let mut strata: Vec<usize> = (0..7)
+                    .map(|index| (sampler.sample(4, 5, round * 7 + index, dimension) * 7.0) as usize)
+                    .collect();
+                strata.sort_unstable();
+                assert_eq!(strata, (0..7).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn dimensions_are_uniform_for_any_samples_count() {
+        for samples_per_pixel in [2, 3, 5, 7, 80] {
+            let sampler = StratifiedSampler::new(samples_per_pixel, 11);
+
+            for dimension in 0..8 {
+                let mut sum = 0.0;
+                let mut count = 0;
+                for y in 0..40 {
+                    for x in 0..40 {
+                        for index in 0..samples_per_pixel {
+                            sum += sampler.sample(x, y, index, dimension);
+                            count += 1;
+                        }
+                    }
+                }
+
+                let mean = sum / count as f64;
+                assert!((mean - 0.5).abs() < 0.01, "{} samples, dimension {}: mean {}", samples_per_pixel, dimension, mean);
+            }
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use crate::engine::hittables::{Hittable, HittableCollection};
+
+/// Everything which should be rendered.
+pub struct Scene<'a> {
+    pub world: Box<dyn Hittable<'a> + 'a>,
+
+    /// Objects which are sampled directly as light sources. They should also be
+    /// a part of the world, which is where the light they emit is taken from.
+    pub lights: HittableCollection<'a>,
+}
+
+
+impl<'a> Scene<'a> {
+    pub fn new(world: Box<dyn Hittable<'a> + 'a>) -> Scene<'a> {
+        Scene::with_lights(world, HittableCollection::new())
+    }
+
+    pub fn with_lights(world: Box<dyn Hittable<'a> + 'a>, lights: HittableCollection<'a>) -> Scene<'a> {
+        Scene { world, lights }
+    }
+}
@@ -0,0 +1,43 @@
+use crate::engine::textures::solid_color::SolidColor;
+use crate::engine::textures::texture::Texture;
+use crate::vectors::{Color, Point};
+use std::sync::Arc;
+
+/// A 3D checker board, which alternates between two textures in cubes of the given size.
+/// Since it depends on the hit point, rather than on the surface coordinates,
+/// it looks like the object was carved out of a checkered block.
+pub struct CheckerTexture {
+    inverse_scale: f64,
+    even: Arc<dyn Texture>,
+    odd: Arc<dyn Texture>,
+}
+
+
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> CheckerTexture {
+        CheckerTexture {
+            inverse_scale: 1.0 / scale,
+            even,
+            odd,
+        }
+    }
+
+    pub fn from_colors(scale: f64, even: Color, odd: Color) -> CheckerTexture {
+        CheckerTexture::new(scale, Arc::new(SolidColor::new(even)), Arc::new(SolidColor::new(odd)))
+    }
+}
+
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color {
+        let x = (self.inverse_scale * point.x).floor() as i64;
+        let y = (self.inverse_scale * point.y).floor() as i64;
+        let z = (self.inverse_scale * point.z).floor() as i64;
+
+        if (x + y + z) % 2 == 0 {
+            return self.even.value(u, v, point);
+        }
+
+        self.odd.value(u, v, point)
+    }
+}
@@ -0,0 +1,41 @@
+use crate::engine::textures::texture::Texture;
+use crate::utils::{load_image, Image};
+use crate::vectors::{Color, Point};
+use std::io;
+
+/// Wraps an image around the surface, according to its (u, v) coordinates.
+/// (0, 0) is the bottom-left corner of the image, and (1, 1) is the top-right one.
+pub struct ImageTexture {
+    image: Image
+}
+
+
+impl ImageTexture {
+    pub fn new(image: Image) -> ImageTexture {
+        ImageTexture {image}
+    }
+
+    /// Load a PPM or PNG image as a texture.
+    pub fn load(file_path: &str) -> io::Result<ImageTexture> {
+        Ok(ImageTexture::new(load_image(file_path)?))
+    }
+}
+
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _point: &Point) -> Color {
+        let width = self.image.width();
+        let height = self.image.height();
+        if width == 0 || height == 0 {
+            return Color::zeroes();
+        }
+
+        let u = u.clamp(0.0, 1.0);
+        // Image rows go downwards, while v goes upwards
+        let v = 1.0 - v.clamp(0.0, 1.0);
+
+        let x = ((u * width as f64) as usize).min(width - 1);
+        let y = ((v * height as f64) as usize).min(height - 1);
+        self.image.get_pixel(x, y)
+    }
+}
@@ -0,0 +1,59 @@
+use crate::engine::textures::perlin::Perlin;
+use crate::engine::textures::texture::Texture;
+use crate::engine::Rng;
+use crate::vectors::{Color, Point};
+
+/// Default amount of turbulence octaves.
+const DEFAULT_OCTAVES: usize = 7;
+
+/// Default strength of the turbulence, in radians of the stripes' phase.
+const DEFAULT_TURBULENCE: f64 = 10.0;
+
+/// Marble-like stripes along the Z axis, whose phase is distorted by turbulence,
+/// so they bend into veins. The color blends between the base and the veins.
+pub struct MarbleTexture {
+    perlin: Perlin,
+    scale: f64,
+    turbulence: f64,
+    octaves: usize,
+    base: Color,
+    veins: Color,
+}
+
+
+impl MarbleTexture {
+    /// White marble with dark veins. Higher scales give thinner stripes.
+    pub fn new(rng: &mut Rng, scale: f64) -> MarbleTexture {
+        MarbleTexture {
+            perlin: Perlin::new(rng),
+            scale,
+            turbulence: DEFAULT_TURBULENCE,
+            octaves: DEFAULT_OCTAVES,
+            base: Color::new(1.0, 1.0, 1.0),
+            veins: Color::zeroes(),
+        }
+    }
+
+    pub fn with_colors(mut self, base: Color, veins: Color) -> MarbleTexture {
+        self.base = base;
+        self.veins = veins;
+        self
+    }
+
+    /// Stronger turbulence bends the stripes more, and more octaves make the veins rougher.
+    pub fn with_turbulence(mut self, turbulence: f64, octaves: usize) -> MarbleTexture {
+        self.turbulence = turbulence;
+        self.octaves = octaves;
+        self
+    }
+}
+
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let phase = self.scale * point.z + self.turbulence * self.perlin.turbulence(point, self.octaves);
+        let t = 0.5 * (1.0 + phase.sin());
+
+        t * self.base + (1.0 - t) * self.veins
+    }
+}
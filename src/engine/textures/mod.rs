@@ -0,0 +1,15 @@
+pub mod texture;
+pub mod solid_color;
+pub mod checker_texture;
+pub mod image_texture;
+pub mod perlin;
+pub mod noise_texture;
+pub mod marble_texture;
+
+pub use texture::Texture;
+pub use solid_color::SolidColor;
+pub use checker_texture::CheckerTexture;
+pub use image_texture::ImageTexture;
+pub use perlin::Perlin;
+pub use noise_texture::NoiseTexture;
+pub use marble_texture::MarbleTexture;
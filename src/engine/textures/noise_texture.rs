@@ -0,0 +1,41 @@
+use crate::engine::textures::perlin::Perlin;
+use crate::engine::textures::texture::Texture;
+use crate::engine::Rng;
+use crate::vectors::{Color, Point};
+
+/// Amount of octaves summed by turbulent noise.
+const TURBULENCE_OCTAVES: usize = 7;
+
+/// Gray Perlin noise, as a solid texture. Higher scales give finer noise.
+pub struct NoiseTexture {
+    perlin: Perlin,
+    scale: f64,
+    turbulent: bool,
+}
+
+
+impl NoiseTexture {
+    /// Smooth noise, mapped from (-1, 1) into (0, 1).
+    pub fn new(rng: &mut Rng, scale: f64) -> NoiseTexture {
+        NoiseTexture { perlin: Perlin::new(rng), scale, turbulent: false }
+    }
+
+    /// Fractal, turbulent noise.
+    pub fn turbulent(rng: &mut Rng, scale: f64) -> NoiseTexture {
+        NoiseTexture { perlin: Perlin::new(rng), scale, turbulent: true }
+    }
+}
+
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, point: &Point) -> Color {
+        let point = self.scale * *point;
+        let value = if self.turbulent {
+            self.perlin.turbulence(&point, TURBULENCE_OCTAVES)
+        } else {
+            0.5 * (1.0 + self.perlin.noise(&point))
+        };
+
+        Color::new(value, value, value)
+    }
+}
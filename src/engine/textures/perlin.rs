@@ -0,0 +1,128 @@
+use crate::engine::Rng;
+use crate::vectors::{Point, Vector};
+
+/// Amount of lattice points along each axis before the noise repeats itself.
+const POINTS_COUNT: usize = 256;
+
+/// Every octave has this much higher frequency than the previous one.
+const LACUNARITY: f64 = 2.0;
+
+/// Every octave has this much lower weight than the previous one.
+const GAIN: f64 = 0.5;
+
+/// Perlin gradient noise: a random unit vector is placed at every point of an integer
+/// lattice, and the noise at a point blends the dot products with the gradients of the
+/// 8 lattice points around it. The result is smooth, and roughly between -1 and 1.
+pub struct Perlin {
+    gradients: Vec<Vector>,
+    permutation_x: Vec<usize>,
+    permutation_y: Vec<usize>,
+    permutation_z: Vec<usize>,
+}
+
+
+impl Perlin {
+    /// The same generator state always produces the same noise.
+    pub fn new(rng: &mut Rng) -> Perlin {
+        let gradients = (0..POINTS_COUNT)
+            .map(|_| Vector::random(rng, -1.0, 1.0).unit())
+            .collect();
+
+        Perlin {
+            gradients,
+            permutation_x: Perlin::generate_permutation(rng),
+            permutation_y: Perlin::generate_permutation(rng),
+            permutation_z: Perlin::generate_permutation(rng),
+        }
+    }
+
+    pub fn noise(&self, point: &Point) -> f64 {
+        let (i, j, k) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (u, v, w) = (point.x - i, point.y - j, point.z - k);
+        let (i, j, k) = (i as i64, j as i64, k as i64);
+
+        let mut corners = [[[Vector::zeroes(); 2]; 2]; 2];
+        for (di, plane) in corners.iter_mut().enumerate() {
+            for (dj, row) in plane.iter_mut().enumerate() {
+                for (dk, corner) in row.iter_mut().enumerate() {
+                    let index = self.permutation_x[Perlin::wrap(i + di as i64)]
+                        ^ self.permutation_y[Perlin::wrap(j + dj as i64)]
+                        ^ self.permutation_z[Perlin::wrap(k + dk as i64)];
+                    *corner = self.gradients[index];
+                }
+            }
+        }
+
+        Perlin::interpolate(&corners, u, v, w)
+    }
+
+    /// Fractal Brownian motion: a sum of noise octaves, each one with double the frequency
+    /// and half the weight of the previous one, which adds finer and finer details.
+    /// The weights add up to 2 - 2^(1 - octaves), so the result may reach almost twice the
+    /// range of a single octave, but since the octaves rarely peak together it stays within
+    /// about -0.9 and 0.9 in practice.
+    pub fn fractal(&self, point: &Point, octaves: usize) -> f64 {
+        self.sum_octaves(point, octaves, |noise| noise)
+    }
+
+    /// Like `fractal`, but sums the absolute value of every octave. The creases where the
+    /// noise crosses zero give the turbulent look of smoke, fire or veins. The result is never
+    /// negative, and for the same reason stays below about 0.9 in practice.
+    pub fn turbulence(&self, point: &Point, octaves: usize) -> f64 {
+        self.sum_octaves(point, octaves, f64::abs)
+    }
+
+    fn sum_octaves<F: Fn(f64) -> f64>(&self, point: &Point, octaves: usize, shape: F) -> f64 {
+        let mut accumulated = 0.0;
+        let mut point = *point;
+        let mut weight = 1.0;
+
+        for _ in 0..octaves {
+            accumulated += weight * shape(self.noise(&point));
+            weight *= GAIN;
+            point = LACUNARITY * point;
+        }
+
+        accumulated
+    }
+
+    fn generate_permutation(rng: &mut Rng) -> Vec<usize> {
+        let mut permutation: Vec<usize> = (0..POINTS_COUNT).collect();
+
+        // Fisher-Yates shuffle
+        for i in (1..POINTS_COUNT).rev() {
+            let target = ((rng.float() * (i + 1) as f64) as usize).min(i);
+            permutation.swap(i, target);
+        }
+
+        permutation
+    }
+
+    fn wrap(coordinate: i64) -> usize {
+        coordinate.rem_euclid(POINTS_COUNT as i64) as usize
+    }
+
+    /// Trilinear interpolation of the gradient contributions, smoothed with a Hermite
+    /// cubic so the noise has no visible lattice artifacts.
+    fn interpolate(corners: &[[[Vector; 2]; 2]; 2], u: f64, v: f64, w: f64) -> f64 {
+        let smooth = |t: f64| t * t * (3.0 - 2.0 * t);
+        let (uu, vv, ww) = (smooth(u), smooth(v), smooth(w));
+
+        let mut accumulated = 0.0;
+        for (i, plane) in corners.iter().enumerate() {
+            for (j, row) in plane.iter().enumerate() {
+                for (k, gradient) in row.iter().enumerate() {
+                    let (fi, fj, fk) = (i as f64, j as f64, k as f64);
+                    let weight = Vector::new(u - fi, v - fj, w - fk);
+
+                    accumulated += (fi * uu + (1.0 - fi) * (1.0 - uu))
+                        * (fj * vv + (1.0 - fj) * (1.0 - vv))
+                        * (fk * ww + (1.0 - fk) * (1.0 - ww))
+                        * gradient.dot(weight);
+                }
+            }
+        }
+
+        accumulated
+    }
+}
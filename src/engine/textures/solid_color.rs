@@ -0,0 +1,21 @@
+use crate::engine::textures::texture::Texture;
+use crate::vectors::{Color, Point};
+
+/// The same color everywhere.
+pub struct SolidColor {
+    color: Color
+}
+
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor {color}
+    }
+}
+
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _point: &Point) -> Color {
+        self.color
+    }
+}
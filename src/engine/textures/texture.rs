@@ -0,0 +1,8 @@
+use crate::vectors::{Color, Point};
+
+/// A color which varies over a surface. Textures are sampled either by the surface
+/// coordinates (u, v) of the hit, or by the hit point itself for solid textures.
+/// Like materials, textures are shared between the rendering threads.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, point: &Point) -> Color;
+}
@@ -5,6 +5,7 @@ pub fn degrees_to_radians(degrees: f64) -> f64 {
     degrees * PI / 180.0
 }
 
-pub fn random_float(min_value: f64, max_value: f64) -> f64 {
-    rand::thread_rng().gen_range(min_value..max_value)
-}
\ No newline at end of file
+/// Generate a random seed, for when the caller doesn't care about reproducibility.
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
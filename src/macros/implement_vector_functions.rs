@@ -14,14 +14,13 @@ macro_rules! implement_unit_function {
 
 macro_rules! implement_random_function {
     ($vector_type: ty, $($field: ident), *) => {
-        use crate::engine::utils::{random_float};
         impl $vector_type {
-            /// Generate a vector with random elements between 0 - 1
-            pub fn random(min_value: f64, max_value: f64) -> $vector_type {
+            /// Generate a vector with random elements between min_value - max_value
+            pub fn random(rng: &mut $crate::engine::Rng, min_value: f64, max_value: f64) -> $vector_type {
                 <$vector_type>::new(
-                    random_float(min_value, max_value),
-                    random_float(min_value, max_value),
-                    random_float(min_value, max_value)
+                    rng.range(min_value, max_value),
+                    rng.range(min_value, max_value),
+                    rng.range(min_value, max_value)
                 )
             }
         }
@@ -52,7 +51,7 @@ macro_rules! implement_cross_function {
 macro_rules! implement_common_vector_functions {
     ($vector_type: ty, $element_type: ty, $($field: ident), *) => {
         use std::ops::{Add, Mul, Div, Sub, Neg};
-        use crate::consts::NEAR_ZERO_THRESHOLD;
+        use $crate::consts::NEAR_ZERO_THRESHOLD;
 
         impl $vector_type {
             pub fn size_squared(&self) -> f64 {
@@ -82,7 +81,7 @@ macro_rules! implement_common_vector_functions {
             pub fn near_zero(&self) -> bool {
                 let mut is_near_zero = true;
                 $(
-                    is_near_zero = is_near_zero && self.$field < NEAR_ZERO_THRESHOLD;
+                    is_near_zero = is_near_zero && self.$field.abs() < NEAR_ZERO_THRESHOLD;
                 )*
 
                 is_near_zero
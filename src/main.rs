@@ -1,138 +1,95 @@
-use rust_ray_tracing::consts::ASPECT_RATIO;
-use rust_ray_tracing::engine::camera::Camera;
-use rust_ray_tracing::engine::hittables::{Hittable, HittableCollection, Sphere};
-use rust_ray_tracing::engine::materials::{Dielectric, Lambertian, Material, Metal, ScatterResult};
-use rust_ray_tracing::engine::Ray;
-use rust_ray_tracing::engine::utils::random_float;
-use rust_ray_tracing::utils::ppm_writer::PPMWriter;
-use rust_ray_tracing::vectors::{Color, Point};
+mod cli;
+mod scenes;
+
+use cli::{parse_arguments, usage, Command};
+use rust_ray_tracing::engine::hittables::BvhNode;
+use rust_ray_tracing::engine::utils::random_seed;
+use rust_ray_tracing::engine::{render_framebuffer, render_progressive, RenderSettings, Rng, Scene};
+use rust_ray_tracing::utils::scene_file::{load_scene_file_with, SceneDescription};
+use rust_ray_tracing::utils::Image;
+use std::env;
+use std::process;
+use std::time::Instant;
 
-fn ray_color<'a, 'b, T: Hittable<'a>>(ray: &Ray, world: &'b T, depth: usize) -> Color {
-    let record = world.hit(ray, 0.001, f64::INFINITY);
-    if depth <= 0 {
-        return Color::zeroes();
-    }
-
-    match record {
-        Some(record) => {
-            let material = record.material.clone();
-            let scatter_result = material.scatter(ray, &record);
-
-            if let Some(scatter_result) = scatter_result {
-                let ScatterResult { scattered, attenuation } = scatter_result;
-                return attenuation * ray_color(&scattered, world, depth - 1);
-            }
-
-            Color::zeroes()
+fn main() {
+    let arguments = match parse_arguments(env::args().skip(1)) {
+        Ok(Command::Render(arguments)) => *arguments,
+        Ok(Command::Help) => {
+            print!("{}", usage());
+            return;
         }
-        None => {
-            let t = (ray.direction.unit().y + 1.0) / 2.0;
-            let blue = Color::new(0.5, 0.7, 1.0);
-            let white = Color::new(1.0, 1.0, 1.0);
-
-            (1.0 - t) * white + t * blue
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, usage());
+            process::exit(2);
         }
+    };
+
+    // World & camera
+    let description: SceneDescription = if scenes::BUILTIN_SCENES.contains(&arguments.scene.as_str()) {
+        let mut settings = RenderSettings::default();
+        arguments.apply_to(&mut settings);
+
+        // Built-in scenes are generated randomly, so they depend on the seed as well.
+        let mut rng = Rng::new(*settings.seed.get_or_insert_with(random_seed));
+        scenes::load_builtin_scene(&arguments.scene, settings, &mut rng).unwrap()
+    } else {
+        // The seed is chosen before the scene is loaded, since procedural textures depend on it.
+        let adjust_settings = |settings: &mut RenderSettings| {
+            arguments.apply_to(settings);
+            settings.seed.get_or_insert_with(random_seed);
+        };
+        load_scene_file_with(&arguments.scene, adjust_settings).unwrap_or_else(|error| {
+            eprintln!("error: {}", error);
+            process::exit(1);
+        })
+    };
+
+    let SceneDescription { world, lights, camera, mut settings } = description;
+    if let Err(message) = arguments.check_applied(&settings) {
+        eprintln!("error: {}", message);
+        process::exit(2);
     }
-}
+    if world.is_empty() {
+        eprintln!("error: The scene {} has no objects to render", arguments.scene);
+        process::exit(1);
+    }
+    let seed = *settings.seed.get_or_insert_with(random_seed);
+    let scene = Scene::with_lights(Box::new(BvhNode::with_unbounded_objects(world)), lights);
 
-fn generate_random_scene<'a>() -> HittableCollection<'a> {
-    let mut world = HittableCollection::new();
-    let ground_material: Box<dyn Material> = Box::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
-    let ground = Sphere::new(
-        Point::new(0.0, -1000.0, 0.0),
-        1000.0,
-        ground_material,
+    // Render
+    eprintln!(
+        "Rendering {}x{} with {} samples per pixel (seed {})",
+        settings.width, settings.height, settings.samples_per_pixel, seed
     );
-
-    world.add(Box::new(ground));
-
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_material = random_float(0.0, 1.0);
-            let center = Point::new(
-                (a as f64) + 0.9 * random_float(0.0, 1.0),
-                0.2,
-                (b as f64) + 0.9 * random_float(0.0, 1.0),
+    let save = |image: &Image, path: &str| {
+        arguments.format.encoder().save(image, path).unwrap_or_else(|error| {
+            eprintln!("error: Failed to save {}: {}", path, error);
+            process::exit(1);
+        });
+    };
+
+    let start = Instant::now();
+    let framebuffer = match &arguments.progressive {
+        Some(progressive) => render_progressive(&scene, &camera, &settings, progressive, |report, framebuffer| {
+            eprintln!(
+                "Pass {}: {} pixels left after {:.2}s",
+                report.pass, report.active_pixels, report.elapsed.as_secs_f64()
             );
-
-            if (center - Point::new(4.0, 0.2, 0.0)).size() > 0.9 {
-                let sphere_material: Box<dyn Material>;
-                if choose_material < 0.8 {
-                    // Diffuse
-                    let albedo = Color::random(0.0, 1.0) * Color::random(0.0, 1.0);
-                    sphere_material = Box::new(Lambertian::new(albedo));
-                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
-                } else if choose_material < 0.95 {
-                    // Metal
-                    let albedo = Color::random(0.5, 1.0);
-                    let fuzz = random_float(0.0, 0.5);
-                    sphere_material = Box::new(Metal::new(albedo, fuzz));
-                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)))
-                } else {
-                    // Glass
-                    sphere_material = Box::new(Dielectric::new(1.5));
-                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
-                }
+            if arguments.save_every.is_some_and(|passes| report.pass % passes == 0) {
+                save(&framebuffer.to_image(), &arguments.output);
             }
-        }
+        }),
+        None => render_framebuffer(&scene, &camera, &settings),
+    };
+    eprintln!("Rendered in {:.2}s", start.elapsed().as_secs_f64());
+
+    if arguments.progressive.is_some() || settings.adaptive.is_some() {
+        let pixels_count = framebuffer.width() * framebuffer.height();
+        eprintln!("Took {:.1} samples per pixel on average", framebuffer.samples_count() as f64 / pixels_count as f64);
     }
 
-    let material1 = Box::new(Dielectric::new(1.5));
-    let material2 = Box::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
-    let material3 = Box::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
-
-    world.add(Box::new(Sphere::new(Point::new(0.0, 1.0, 0.0), 1.0, material1)));
-    world.add(Box::new(Sphere::new(Point::new(-4.0, 1.0, 0.0), 1.0, material2)));
-    world.add(Box::new(Sphere::new(Point::new(4.0, 1.0, 0.0), 1.0, material3)));
-
-    world
-}
-
-fn main() {
-    // Image
-    let aspect_ratio = ASPECT_RATIO;
-    let image_width: usize = 1920;
-    let image_height: usize = (image_width as f64 / aspect_ratio) as usize;
-
-    // World
-    let world = generate_random_scene();
-
-    // Camera
-    let look_from = Point::new(13.0, 2.0, 3.0);
-    let look_at = Point::new(0.0, 0.0, 0.0);
-    let camera = Camera::new(
-        look_from,
-        look_at,
-        Point::new(0.0, 1.0, 0.0),
-        20.0,
-        ASPECT_RATIO,
-        0.1,
-        10.0,
-    );
-
-    // Render
-    let samples_count = 80;
-    let depth = 50;
-
-    let mut writer = PPMWriter::get_file_writer("result.ppm");
-    writer.write_size(image_height as usize, image_width as usize);
-
-    for j in (0..image_height).rev() {
-        for i in 0..(image_width) {
-            let mut color = Color::zeroes();
-
-            for _ in 0..samples_count {
-                let random_bias_x = random_float(0.0, 1.0);
-                let random_bias_y = random_float(0.0, 1.0);
-
-                let x = (i as f64 + random_bias_x) / ((image_width - 1) as f64);
-                let y = (j as f64 + random_bias_y) / ((image_height - 1) as f64);
-
-                let ray = camera.get_ray(x, y);
-                color = color + ray_color(&ray, &world, depth);
-            }
-
-            writer.write_color(color, samples_count);
-        }
+    save(&framebuffer.to_image(), &arguments.output);
+    if let Some(heatmap_path) = &arguments.heatmap {
+        save(&framebuffer.samples_heatmap(), heatmap_path);
     }
 }
@@ -0,0 +1,121 @@
+use rust_ray_tracing::engine::camera::Camera;
+use rust_ray_tracing::engine::hittables::{HittableCollection, Sphere};
+use rust_ray_tracing::engine::materials::{Dielectric, Lambertian, Material, Metal};
+use rust_ray_tracing::engine::textures::{MarbleTexture, NoiseTexture, Texture};
+use rust_ray_tracing::engine::{RenderSettings, Rng};
+use rust_ray_tracing::utils::scene_file::SceneDescription;
+use rust_ray_tracing::vectors::{Color, Point};
+use std::sync::Arc;
+
+/// Names of the scenes which can be rendered without a scene file.
+pub const BUILTIN_SCENES: [&str; 2] = ["random", "perlin_spheres"];
+
+/// Build one of the built-in scenes, with a camera which matches the image size in the settings.
+pub fn load_builtin_scene<'a>(name: &str, settings: RenderSettings, rng: &mut Rng) -> Option<SceneDescription<'a>> {
+    let aspect_ratio = settings.width as f64 / settings.height as f64;
+
+    match name {
+        "random" => Some(SceneDescription {
+            world: generate_random_scene(rng),
+            lights: HittableCollection::new(),
+            camera: random_scene_camera(aspect_ratio),
+            settings,
+        }),
+        "perlin_spheres" => Some(SceneDescription {
+            world: generate_perlin_spheres(rng),
+            lights: HittableCollection::new(),
+            camera: random_scene_camera(aspect_ratio),
+            settings,
+        }),
+        _ => None
+    }
+}
+
+fn generate_random_scene<'a>(rng: &mut Rng) -> HittableCollection<'a> {
+    let mut world = HittableCollection::new();
+    let ground_material: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let ground = Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        ground_material,
+    );
+
+    world.add(Box::new(ground));
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_material = rng.float();
+            let center = Point::new(
+                (a as f64) + 0.9 * rng.float(),
+                0.2,
+                (b as f64) + 0.9 * rng.float(),
+            );
+
+            if (center - Point::new(4.0, 0.2, 0.0)).size() > 0.9 {
+                let sphere_material: Arc<dyn Material>;
+                if choose_material < 0.8 {
+                    // Diffuse
+                    let albedo = Color::random(rng, 0.0, 1.0) * Color::random(rng, 0.0, 1.0);
+                    sphere_material = Arc::new(Lambertian::new(albedo));
+                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
+                } else if choose_material < 0.95 {
+                    // Metal
+                    let albedo = Color::random(rng, 0.5, 1.0);
+                    let fuzz = rng.range(0.0, 0.5);
+                    sphere_material = Arc::new(Metal::new(albedo, fuzz));
+                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)))
+                } else {
+                    // Glass
+                    sphere_material = Arc::new(Dielectric::new(1.5));
+                    world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
+                }
+            }
+        }
+    }
+
+    let material1 = Arc::new(Dielectric::new(1.5));
+    let material2 = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
+    let material3 = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+
+    world.add(Box::new(Sphere::new(Point::new(0.0, 1.0, 0.0), 1.0, material1)));
+    world.add(Box::new(Sphere::new(Point::new(-4.0, 1.0, 0.0), 1.0, material2)));
+    world.add(Box::new(Sphere::new(Point::new(4.0, 1.0, 0.0), 1.0, material3)));
+
+    world
+}
+
+/// Two spheres with procedural textures, so no image files are needed.
+fn generate_perlin_spheres<'a>(rng: &mut Rng) -> HittableCollection<'a> {
+    let mut world = HittableCollection::new();
+
+    let turbulence: Arc<dyn Texture> = Arc::new(NoiseTexture::turbulent(rng, 4.0));
+    let marble: Arc<dyn Texture> = Arc::new(MarbleTexture::new(rng, 4.0));
+
+    world.add(Box::new(Sphere::new(
+        Point::new(0.0, -1000.0, 0.0),
+        1000.0,
+        Arc::new(Lambertian::with_texture(turbulence)),
+    )));
+    world.add(Box::new(Sphere::new(
+        Point::new(0.0, 2.0, 0.0),
+        2.0,
+        Arc::new(Lambertian::with_texture(marble)),
+    )));
+
+    world
+}
+
+fn random_scene_camera(aspect_ratio: f64) -> Camera {
+    let look_from = Point::new(13.0, 2.0, 3.0);
+    let look_at = Point::new(0.0, 0.0, 0.0);
+
+    Camera::new(
+        look_from,
+        look_at,
+        Point::new(0.0, 1.0, 0.0),
+        20.0,
+        aspect_ratio,
+        0.1,
+        10.0,
+    )
+}
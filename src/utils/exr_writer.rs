@@ -0,0 +1,296 @@
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::ImageEncoder;
+
+const EXR_MAGIC_NUMBER: [u8; 4] = [0x76, 0x2F, 0x31, 0x01];
+
+/// Version 2, single part scanline image.
+const EXR_VERSION: [u8; 4] = [2, 0, 0, 0];
+
+/// Channels must be listed in alphabetical order.
+const CHANNEL_NAMES: [&str; 3] = ["B", "G", "R"];
+
+/// The size of the y coordinate and the data size which precede every scanline.
+const SCANLINE_HEADER_SIZE: usize = 8;
+
+/// The floating point type each channel is stored with.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EXRPixelType {
+    /// 16 bit floats. Half the size, and precise enough for most purposes.
+    Half,
+
+    /// 32 bit floats.
+    Float,
+}
+
+/// Used for writing images in OpenEXR format, which keeps the unclamped linear colors.
+/// The output is an uncompressed scanline image, made of:
+///
+/// <magic number> <version>
+/// <header attributes: name, type, size, value> <null byte>
+/// <offset table: the position of every scanline in the file>
+/// <scanlines: y, data size, and all of the B, G and R values of the line>
+pub struct EXRWriter {
+    pixel_type: EXRPixelType,
+}
+
+
+impl EXRPixelType {
+    fn id(&self) -> i32 {
+        match self {
+            EXRPixelType::Half => 1,
+            EXRPixelType::Float => 2,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            EXRPixelType::Half => 2,
+            EXRPixelType::Float => 4,
+        }
+    }
+}
+
+impl EXRWriter {
+    pub fn new(pixel_type: EXRPixelType) -> EXRWriter {
+        EXRWriter { pixel_type }
+    }
+
+    fn get_header(&self, image: &Image) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&EXR_MAGIC_NUMBER);
+        header.extend_from_slice(&EXR_VERSION);
+
+        let mut channels = Vec::new();
+        for name in CHANNEL_NAMES.iter() {
+            channels.extend_from_slice(name.as_bytes());
+            channels.push(0);
+            channels.extend_from_slice(&self.pixel_type.id().to_le_bytes());
+            // Perceptually linear flag, followed by 3 reserved bytes.
+            channels.extend_from_slice(&[0, 0, 0, 0]);
+            // x and y sampling.
+            channels.extend_from_slice(&1i32.to_le_bytes());
+            channels.extend_from_slice(&1i32.to_le_bytes());
+        }
+        channels.push(0);
+
+        let mut window = Vec::new();
+        for value in [0, 0, image.width() as i32 - 1, image.height() as i32 - 1] {
+            window.extend_from_slice(&value.to_le_bytes());
+        }
+
+        write_attribute(&mut header, "channels", "chlist", &channels);
+        // No compression
+        write_attribute(&mut header, "compression", "compression", &[0]);
+        write_attribute(&mut header, "dataWindow", "box2i", &window);
+        write_attribute(&mut header, "displayWindow", "box2i", &window);
+        // Scanlines are written in increasing y order
+        write_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        write_attribute(&mut header, "pixelAspectRatio", "float", &1f32.to_le_bytes());
+        write_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        write_attribute(&mut header, "screenWindowWidth", "float", &1f32.to_le_bytes());
+        header.push(0);
+
+        header
+    }
+
+    fn write_value(&self, scanline: &mut Vec<u8>, value: f64) {
+        match self.pixel_type {
+            EXRPixelType::Half => scanline.extend_from_slice(&to_half(value as f32).to_le_bytes()),
+            EXRPixelType::Float => scanline.extend_from_slice(&(value as f32).to_le_bytes()),
+        }
+    }
+}
+
+impl Default for EXRWriter {
+    fn default() -> Self {
+        EXRWriter::new(EXRPixelType::Half)
+    }
+}
+
+impl ImageEncoder for EXRWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        let header = self.get_header(image);
+        buffer.write_all(&header)?;
+
+        let data_size = image.width() * CHANNEL_NAMES.len() * self.pixel_type.size();
+        let offset_table_size = image.height() * 8;
+        for y in 0..image.height() {
+            let offset = header.len() + offset_table_size + y * (SCANLINE_HEADER_SIZE + data_size);
+            buffer.write_all(&(offset as u64).to_le_bytes())?;
+        }
+
+        for (y, row) in image.rows().enumerate() {
+            let mut scanline = Vec::with_capacity(SCANLINE_HEADER_SIZE + data_size);
+            scanline.extend_from_slice(&(y as i32).to_le_bytes());
+            scanline.extend_from_slice(&(data_size as i32).to_le_bytes());
+
+            for color in row {
+                self.write_value(&mut scanline, color.blue);
+            }
+            for color in row {
+                self.write_value(&mut scanline, color.green);
+            }
+            for color in row {
+                self.write_value(&mut scanline, color.red);
+            }
+
+            buffer.write_all(&scanline)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn write_attribute(header: &mut Vec<u8>, name: &str, attribute_type: &str, value: &[u8]) {
+    header.extend_from_slice(name.as_bytes());
+    header.push(0);
+    header.extend_from_slice(attribute_type.as_bytes());
+    header.push(0);
+    header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+    header.extend_from_slice(value);
+}
+
+/// Convert a 32 bit float into the bits of a 16 bit float, rounding to the nearest value.
+/// Values which are too large become infinity, and values which are too small become 0.
+fn to_half(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xFF) as i32;
+    let mantissa = bits & 0x7F_FFFF;
+
+    // Infinity or NaN
+    if exponent == 0xFF {
+        return sign | 0x7C00 | if mantissa != 0 { 0x200 } else { 0 };
+    }
+
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1F {
+        return sign | 0x7C00;
+    }
+
+    // Rounding up happens when the first dropped bit is set, and either one of the
+    // other dropped bits or the lowest kept bit is set (round half to even).
+    let round = |kept: u32, dropped_bits: u32, full: u32| {
+        let round_bit = 1 << (dropped_bits - 1);
+        if full & round_bit != 0 && full & (3 * round_bit - 1) != 0 {
+            return kept + 1;
+        }
+        kept
+    };
+
+    if half_exponent <= 0 {
+        // Too small even for a subnormal half
+        if half_exponent < -10 {
+            return sign;
+        }
+
+        // Subnormal half, the implicit leading bit becomes explicit.
+        let full_mantissa = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        return sign | round(full_mantissa >> shift, shift, full_mantissa) as u16;
+    }
+
+    // An overflow of the mantissa while rounding correctly carries into the exponent.
+    let kept = ((half_exponent as u32) << 10) | (mantissa >> 13);
+    sign | round(kept, 13, mantissa) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vectors::Color;
+
+    fn read_u32(data: &[u8], position: usize) -> u32 {
+        u32::from_le_bytes([data[position], data[position + 1], data[position + 2], data[position + 3]])
+    }
+
+    /// Find an attribute by name in the header, and return its type and value.
+    fn find_attribute<'d>(exr: &'d [u8], name: &str) -> (&'d str, &'d [u8]) {
+        let read_string = |position: usize| {
+            let end = position + exr[position..].iter().position(|&byte| byte == 0).unwrap();
+            (std::str::from_utf8(&exr[position..end]).unwrap(), end + 1)
+        };
+
+        let mut position = 8;
+        while exr[position] != 0 {
+            let (attribute_name, type_start) = read_string(position);
+            let (attribute_type, size_start) = read_string(type_start);
+            let size = read_u32(exr, size_start) as usize;
+            let value = &exr[size_start + 4..size_start + 4 + size];
+            if attribute_name == name {
+                return (attribute_type, value);
+            }
+            position = size_start + 4 + size;
+        }
+
+        panic!("The header has no '{}' attribute", name);
+    }
+
+    #[test]
+    fn half_conversion() {
+        assert_eq!(to_half(0.0), 0x0000);
+        assert_eq!(to_half(-0.0), 0x8000);
+        assert_eq!(to_half(1.0), 0x3C00);
+        assert_eq!(to_half(-2.0), 0xC000);
+        assert_eq!(to_half(0.1), 0x2E66);
+        assert_eq!(to_half(65504.0), 0x7BFF);
+        assert_eq!(to_half(65520.0), 0x7C00);
+        assert_eq!(to_half(f32::INFINITY), 0x7C00);
+        assert_eq!(to_half(f32::NAN) & 0x7E00, 0x7E00);
+
+        // Ties round to the even mantissa.
+        assert_eq!(to_half(1.0 + 2f32.powi(-11)), 0x3C00);
+        assert_eq!(to_half(1.0 + 3.0 * 2f32.powi(-11)), 0x3C02);
+
+        // Subnormals, and values below the smallest one.
+        assert_eq!(to_half(2f32.powi(-14)), 0x0400);
+        assert_eq!(to_half(6.1e-5), 0x03FF);
+        assert_eq!(to_half(2f32.powi(-24)), 0x0001);
+        assert_eq!(to_half(1e-8), 0x0000);
+    }
+
+    #[test]
+    fn header_describes_the_image() {
+        let mut exr = Vec::new();
+        EXRWriter::new(EXRPixelType::Half).encode(&Image::new(3, 2), &mut exr).unwrap();
+
+        assert_eq!(exr[..4], EXR_MAGIC_NUMBER);
+        assert_eq!(exr[4..8], EXR_VERSION);
+
+        let (attribute_type, channels) = find_attribute(&exr, "channels");
+        assert_eq!(attribute_type, "chlist");
+        assert_eq!(channels.len(), 3 * 18 + 1);
+        assert_eq!(channels[..6], [b'B', 0, 1, 0, 0, 0]);
+
+        let (attribute_type, window) = find_attribute(&exr, "dataWindow");
+        assert_eq!(attribute_type, "box2i");
+        let window: Vec<u32> = (0..4).map(|index| read_u32(window, index * 4)).collect();
+        assert_eq!(window, vec![0, 0, 2, 1]);
+        assert_eq!(find_attribute(&exr, "compression").1, [0]);
+    }
+
+    #[test]
+    fn float_scanlines_round_trip() {
+        let pixels: Vec<Color> = (0..6).map(|index| Color::new(index as f64, 100.5 * index as f64, -0.25)).collect();
+        let image = Image::from_pixels(3, 2, pixels);
+
+        let mut exr = Vec::new();
+        EXRWriter::new(EXRPixelType::Float).encode(&image, &mut exr).unwrap();
+
+        let header_size = EXRWriter::new(EXRPixelType::Float).get_header(&image).len();
+        for (y, row) in image.rows().enumerate() {
+            let offset = read_u32(&exr, header_size + y * 8) as usize;
+            assert_eq!(read_u32(&exr, offset), y as u32);
+            assert_eq!(read_u32(&exr, offset + 4), 3 * 3 * 4);
+
+            let values: Vec<f32> = (0..9).map(|index| f32::from_bits(read_u32(&exr, offset + 8 + index * 4))).collect();
+            for (x, color) in row.iter().enumerate() {
+                assert_eq!(values[x], color.blue as f32);
+                assert_eq!(values[3 + x], color.green as f32);
+                assert_eq!(values[6 + x], color.red as f32);
+            }
+        }
+        assert_eq!(exr.len(), header_size + 2 * 8 + 2 * (8 + 36));
+    }
+}
@@ -0,0 +1,231 @@
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::ImageEncoder;
+use crate::vectors::Color;
+
+/// Run length encoded scanlines are only supported for widths in this range.
+const MIN_ENCODED_WIDTH: usize = 8;
+const MAX_ENCODED_WIDTH: usize = 0x7FFF;
+
+/// Runs shorter than this are cheaper to write as literals.
+const MIN_RUN_LENGTH: usize = 4;
+
+/// Longest run (or literal dump) a single count byte can represent.
+const MAX_RUN_LENGTH: usize = 127;
+
+/// Used for writing images in Radiance HDR (RGBE) format, which keeps the
+/// unclamped linear colors. The output format is as follows:
+///
+/// #?RADIANCE
+/// FORMAT=32-bit_rle_rgbe
+///
+/// -Y 2 +X 3
+/// <scanlines>
+///
+/// -Y 2 +X 3 => 2 rows from top to bottom, of 3 columns each from left to right.
+///
+/// Every pixel is stored as 4 bytes: red, green and blue mantissas which share
+/// a single exponent. Each scanline is run length encoded, component by component.
+#[derive(Default)]
+pub struct HDRWriter;
+
+
+impl HDRWriter {
+    pub fn new() -> HDRWriter {
+        HDRWriter
+    }
+}
+
+impl ImageEncoder for HDRWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        writeln!(buffer, "#?RADIANCE")?;
+        writeln!(buffer, "FORMAT=32-bit_rle_rgbe")?;
+        writeln!(buffer)?;
+        writeln!(buffer, "-Y {} +X {}", image.height(), image.width())?;
+
+        for row in image.rows() {
+            let pixels: Vec<[u8; 4]> = row.iter().map(|&color| to_rgbe(color)).collect();
+
+            if (MIN_ENCODED_WIDTH..=MAX_ENCODED_WIDTH).contains(&image.width()) {
+                write_encoded_scanline(buffer, &pixels)?;
+            } else {
+                for pixel in pixels {
+                    buffer.write_all(&pixel)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Convert a linear color into RGBE, where all of the components share the exponent
+/// of the largest one. Negative components can't be represented, so they become 0.
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let red = color.red.max(0.0);
+    let green = color.green.max(0.0);
+    let blue = color.blue.max(0.0);
+    let max_component = red.max(green).max(blue);
+
+    if max_component < 1e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // max_component = mantissa * 2 ^ exponent, where mantissa is in [0.5, 1)
+    let exponent = max_component.log2().floor() as i32 + 1;
+    let scale = 256.0 / 2f64.powi(exponent);
+
+    [
+        (red * scale).min(255.0) as u8,
+        (green * scale).min(255.0) as u8,
+        (blue * scale).min(255.0) as u8,
+        (exponent + 128).clamp(0, 255) as u8,
+    ]
+}
+
+/// An encoded scanline starts with 2, 2 and the width as a big endian u16,
+/// followed by each of the 4 components encoded separately.
+fn write_encoded_scanline(buffer: &mut dyn Write, pixels: &[[u8; 4]]) -> io::Result<()> {
+    let width = pixels.len() as u16;
+    buffer.write_all(&[2, 2])?;
+    buffer.write_all(&width.to_be_bytes())?;
+
+    for component in 0..4 {
+        let values: Vec<u8> = pixels.iter().map(|pixel| pixel[component]).collect();
+        buffer.write_all(&encode_run_length(&values))?;
+    }
+
+    Ok(())
+}
+
+/// A count byte above 128 means the next byte repeats (count - 128) times,
+/// otherwise it's followed by that many literal bytes.
+fn encode_run_length(values: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(values.len() + values.len() / MAX_RUN_LENGTH + 1);
+    let mut literal_start = 0;
+    let mut index = 0;
+
+    while index < values.len() {
+        let mut run_length = 1;
+        while index + run_length < values.len()
+            && run_length < MAX_RUN_LENGTH
+            && values[index + run_length] == values[index] {
+            run_length += 1;
+        }
+
+        if run_length >= MIN_RUN_LENGTH {
+            write_literals(&mut result, &values[literal_start..index]);
+            result.push((128 + run_length) as u8);
+            result.push(values[index]);
+
+            index += run_length;
+            literal_start = index;
+        } else {
+            index += 1;
+        }
+    }
+
+    write_literals(&mut result, &values[literal_start..]);
+    result
+}
+
+fn write_literals(result: &mut Vec<u8>, literals: &[u8]) {
+    for chunk in literals.chunks(MAX_RUN_LENGTH) {
+        result.push(chunk.len() as u8);
+        result.extend_from_slice(chunk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode the run length encoded components of a scanline with the given width.
+    fn decode_scanline(data: &[u8], width: usize) -> (Vec<[u8; 4]>, usize) {
+        assert_eq!(data[..4], [2, 2, (width >> 8) as u8, width as u8]);
+
+        let mut pixels = vec![[0u8; 4]; width];
+        let mut position = 4;
+        for component in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = data[position] as usize;
+                if count > 128 {
+                    for pixel in &mut pixels[x..x + count - 128] {
+                        pixel[component] = data[position + 1];
+                    }
+                    x += count - 128;
+                    position += 2;
+                } else {
+                    for (offset, pixel) in pixels[x..x + count].iter_mut().enumerate() {
+                        pixel[component] = data[position + 1 + offset];
+                    }
+                    x += count;
+                    position += 1 + count;
+                }
+            }
+        }
+
+        (pixels, position)
+    }
+
+    fn from_rgbe(pixel: [u8; 4]) -> Color {
+        let scale = 2f64.powi(pixel[3] as i32 - 128 - 8);
+        Color::new(pixel[0] as f64 * scale, pixel[1] as f64 * scale, pixel[2] as f64 * scale)
+    }
+
+    #[test]
+    fn rgbe_shares_the_largest_exponent() {
+        assert_eq!(to_rgbe(Color::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+        assert_eq!(to_rgbe(Color::new(0.0, 3.0, -1.0)), [0, 192, 0, 130]);
+        assert_eq!(to_rgbe(Color::zeroes()), [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn run_length_encoding() {
+        assert_eq!(encode_run_length(&[1, 2, 3]), vec![3, 1, 2, 3]);
+        assert_eq!(encode_run_length(&[5; 10]), vec![138, 5]);
+        assert_eq!(encode_run_length(&[1, 2, 7, 7, 7, 7, 7, 3]), vec![2, 1, 2, 133, 7, 1, 3]);
+        assert_eq!(encode_run_length(&[9; 200]), vec![255, 9, 201, 9]);
+    }
+
+    #[test]
+    fn encoded_image_round_trip() {
+        let width = 10;
+        let pixels: Vec<Color> = (0..width * 2)
+            .map(|index| Color::new(index as f64 * 10.0, 0.5, if index < 6 { 0.0 } else { 1e-3 }))
+            .collect();
+        let image = Image::from_pixels(width, 2, pixels);
+
+        let mut hdr = Vec::new();
+        HDRWriter::new().encode(&image, &mut hdr).unwrap();
+
+        let header = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 10\n";
+        assert_eq!(hdr[..header.len()], header[..]);
+
+        let mut position = header.len();
+        for row in image.rows() {
+            let (pixels, size) = decode_scanline(&hdr[position..], width);
+            position += size;
+
+            for (&color, &pixel) in row.iter().zip(pixels.iter()) {
+                assert_eq!(pixel, to_rgbe(color));
+                let decoded = from_rgbe(pixel);
+                let max_component = color.red.max(color.green).max(color.blue);
+                for (original, decoded) in [(color.red, decoded.red), (color.green, decoded.green), (color.blue, decoded.blue)] {
+                    assert!((original - decoded).abs() <= max_component / 128.0);
+                }
+            }
+        }
+        assert_eq!(position, hdr.len());
+    }
+
+    #[test]
+    fn narrow_images_are_not_encoded() {
+        let image = Image::from_pixels(2, 1, vec![Color::new(1.0, 0.5, 0.25), Color::zeroes()]);
+
+        let mut hdr = Vec::new();
+        HDRWriter::new().encode(&image, &mut hdr).unwrap();
+        assert!(hdr.ends_with(b"-Y 1 +X 2\n\x80\x40\x20\x81\0\0\0\0"));
+    }
+}
@@ -0,0 +1,76 @@
+use crate::vectors::Color;
+
+/// An in-memory image which holds linear colors, one per pixel.
+/// Pixels are stored row by row, starting at the top-left corner.
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+
+impl Image {
+    /// Create a black image in the given size.
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![Color::zeroes(); width * height],
+        }
+    }
+
+    /// Create an image out of the given pixels, which are ordered row by row.
+    pub fn from_pixels(width: usize, height: usize, pixels: Vec<Color>) -> Image {
+        if pixels.len() != width * height {
+            panic!("Expected {} pixels for a {}x{} image, got {}.", width * height, width, height, pixels.len());
+        }
+
+        Image { width, height, pixels }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Color {
+        self.pixels[self.get_index(x, y)]
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, color: Color) {
+        let index = self.get_index(x, y);
+        self.pixels[index] = color;
+    }
+
+    /// All of the pixels, row by row from the top-left corner.
+    pub fn pixels(&self) -> &[Color] {
+        &self.pixels
+    }
+
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        &mut self.pixels
+    }
+
+    /// Iterate over the rows of the image, from top to bottom.
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[Color]> {
+        self.pixels.chunks(self.width.max(1))
+    }
+
+    /// Apply a function on every pixel of the image, for example for tone mapping.
+    pub fn map_pixels<F: Fn(Color) -> Color>(&mut self, function: F) {
+        for pixel in self.pixels.iter_mut() {
+            *pixel = function(*pixel);
+        }
+    }
+
+    fn get_index(&self, x: usize, y: usize) -> usize {
+        if x >= self.width || y >= self.height {
+            panic!("Pixel ({}, {}) is out of the image bounds ({}x{}).", x, y, self.width, self.height);
+        }
+
+        y * self.width + x
+    }
+}
@@ -0,0 +1,48 @@
+use crate::utils::Image;
+use crate::vectors::Color;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+/// Should be implemented by types which convert an image into a specific file format.
+pub trait ImageEncoder {
+    /// Write the encoded image into the buffer.
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()>;
+
+    /// Write the encoded image into a new file at the given path.
+    fn save(&self, image: &Image, file_path: &str) -> io::Result<()> {
+        let mut file_writer = BufWriter::new(File::create(file_path)?);
+        self.encode(image, &mut file_writer)?;
+        file_writer.flush()
+    }
+}
+
+pub fn clamp(number: f64, min_value: f64, max_value: f64) -> f64 {
+    if number > max_value {
+        return max_value;
+    }
+    if number < min_value {
+        return min_value
+    }
+
+    number
+}
+
+/// Convert a linear color into 8 bit components for display, using
+/// square root for gamma 2.0 correction.
+pub fn to_display_bytes(color: Color) -> [u8; 3] {
+    [
+        to_display_byte(color.red),
+        to_display_byte(color.green),
+        to_display_byte(color.blue),
+    ]
+}
+
+/// Convert a linear value into a gamma corrected 8 bit value.
+pub fn to_display_byte(component: f64) -> u8 {
+    to_linear_byte(component.sqrt())
+}
+
+/// Convert a value between 0 - 1 into an 8 bit value, as is.
+pub fn to_linear_byte(component: f64) -> u8 {
+    (clamp(component, 0.0, 0.999) * 256.0) as u8
+}
@@ -0,0 +1,470 @@
+use crate::utils::png_writer::PNG_SIGNATURE;
+use crate::utils::zlib::decompress;
+use crate::utils::Image;
+use crate::vectors::Color;
+use std::fs;
+use std::io;
+
+/// Load a PPM (P3 / P6) or PNG image, according to the content of the file.
+/// The pixels are assumed to be gamma corrected the same way the writers do it (gamma 2.0),
+/// and are converted back into linear colors. Alpha channels are ignored.
+pub fn load_image(file_path: &str) -> io::Result<Image> {
+    let data = fs::read(file_path)?;
+
+    if data.starts_with(&PNG_SIGNATURE) {
+        return decode_png(&data);
+    }
+    if data.starts_with(b"P3") || data.starts_with(b"P6") {
+        return decode_ppm(&data);
+    }
+
+    Err(invalid_data(format!("'{}' is neither a PPM nor a PNG image", file_path)))
+}
+
+/// Decode an ASCII (P3) or binary (P6) PPM image.
+pub fn decode_ppm(data: &[u8]) -> io::Result<Image> {
+    let mut position = 0;
+    let magic = read_ppm_token(data, &mut position)?;
+    let width = parse_ppm_number(read_ppm_token(data, &mut position)?)?;
+    let height = parse_ppm_number(read_ppm_token(data, &mut position)?)?;
+    let max_value = parse_ppm_number(read_ppm_token(data, &mut position)?)?;
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid_data(format!("Invalid PPM maximum value {}", max_value)));
+    }
+
+    let samples_count = width * height * 3;
+    let mut samples = Vec::with_capacity(samples_count);
+
+    match magic {
+        b"P3" => {
+            for _ in 0..samples_count {
+                samples.push(parse_ppm_number(read_ppm_token(data, &mut position)?)?);
+            }
+        }
+        b"P6" => {
+            // A single whitespace separates the header from the binary data.
+            position += 1;
+            let sample_size = if max_value < 256 { 1 } else { 2 };
+            let bytes = data.get(position..position + samples_count * sample_size)
+                .ok_or_else(|| invalid_data("The PPM image data is truncated".to_string()))?;
+
+            samples.extend(bytes.chunks(sample_size).map(|sample| match sample {
+                [byte] => *byte as usize,
+                [high, low] => ((*high as usize) << 8) | *low as usize,
+                _ => unreachable!(),
+            }));
+        }
+        _ => return Err(invalid_data("Unsupported PPM format, expected P3 or P6".to_string())),
+    }
+
+    let pixels = samples.chunks(3)
+        .map(|rgb| Color::new(
+            to_linear(rgb[0] as f64 / max_value as f64),
+            to_linear(rgb[1] as f64 / max_value as f64),
+            to_linear(rgb[2] as f64 / max_value as f64),
+        ))
+        .collect();
+
+    Ok(Image::from_pixels(width, height, pixels))
+}
+
+/// Decode a non interlaced PNG image of any color type and bit depth.
+pub fn decode_png(data: &[u8]) -> io::Result<Image> {
+    let mut position = PNG_SIGNATURE.len();
+    let mut header: Option<PngHeader> = None;
+    let mut palette: Vec<[u8; 3]> = Vec::new();
+    let mut compressed = Vec::new();
+
+    loop {
+        let length_bytes = data.get(position..position + 8)
+            .ok_or_else(|| invalid_data("The PNG image is truncated".to_string()))?;
+        let length = u32::from_be_bytes([length_bytes[0], length_bytes[1], length_bytes[2], length_bytes[3]]) as usize;
+        let chunk_type = &length_bytes[4..8];
+        let chunk = data.get(position + 8..position + 8 + length)
+            .ok_or_else(|| invalid_data("The PNG image is truncated".to_string()))?;
+
+        // Skip the data and the CRC
+        position += 12 + length;
+
+        match chunk_type {
+            b"IHDR" => header = Some(PngHeader::parse(chunk)?),
+            b"PLTE" => palette = chunk.chunks_exact(3).map(|rgb| [rgb[0], rgb[1], rgb[2]]).collect(),
+            b"IDAT" => compressed.extend_from_slice(chunk),
+            b"IEND" => break,
+            // Ancillary chunks (gamma, transparency, text, ...) are ignored
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or_else(|| invalid_data("The PNG image has no IHDR chunk".to_string()))?;
+    let scanlines = decompress(&compressed).map_err(invalid_data)?;
+    let rows = header.unfilter(&scanlines)?;
+
+    let max_value = ((1u32 << header.bit_depth) - 1) as f64;
+    let mut pixels = Vec::with_capacity(header.width * header.height);
+    for row in &rows {
+        for x in 0..header.width {
+            let sample = |channel: usize| header.sample(row, x, channel);
+
+            let color = match header.color_type {
+                PNG_GRAYSCALE | PNG_GRAYSCALE_ALPHA => {
+                    let gray = to_linear(sample(0) as f64 / max_value);
+                    Color::new(gray, gray, gray)
+                }
+                PNG_RGB | PNG_RGBA => Color::new(
+                    to_linear(sample(0) as f64 / max_value),
+                    to_linear(sample(1) as f64 / max_value),
+                    to_linear(sample(2) as f64 / max_value),
+                ),
+                _ => {
+                    let [red, green, blue] = *palette.get(sample(0) as usize)
+                        .ok_or_else(|| invalid_data("PNG palette index out of range".to_string()))?;
+                    Color::new(
+                        to_linear(red as f64 / 255.0),
+                        to_linear(green as f64 / 255.0),
+                        to_linear(blue as f64 / 255.0),
+                    )
+                }
+            };
+            pixels.push(color);
+        }
+    }
+
+    Ok(Image::from_pixels(header.width, header.height, pixels))
+}
+
+const PNG_GRAYSCALE: u8 = 0;
+const PNG_RGB: u8 = 2;
+const PNG_PALETTE: u8 = 3;
+const PNG_GRAYSCALE_ALPHA: u8 = 4;
+const PNG_RGBA: u8 = 6;
+
+/// The content of the IHDR chunk.
+struct PngHeader {
+    width: usize,
+    height: usize,
+    bit_depth: u8,
+    color_type: u8,
+}
+
+impl PngHeader {
+    fn parse(chunk: &[u8]) -> io::Result<PngHeader> {
+        if chunk.len() != 13 {
+            return Err(invalid_data("Invalid PNG IHDR chunk".to_string()));
+        }
+
+        let header = PngHeader {
+            width: u32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]) as usize,
+            height: u32::from_be_bytes([chunk[4], chunk[5], chunk[6], chunk[7]]) as usize,
+            bit_depth: chunk[8],
+            color_type: chunk[9],
+        };
+
+        let valid_bit_depths: &[u8] = match header.color_type {
+            PNG_GRAYSCALE => &[1, 2, 4, 8, 16],
+            PNG_PALETTE => &[1, 2, 4, 8],
+            PNG_RGB | PNG_GRAYSCALE_ALPHA | PNG_RGBA => &[8, 16],
+            other => return Err(invalid_data(format!("Invalid PNG color type {}", other))),
+        };
+        if !valid_bit_depths.contains(&header.bit_depth) {
+            return Err(invalid_data(format!("Invalid PNG bit depth {} for color type {}", header.bit_depth, header.color_type)));
+        }
+        if chunk[12] != 0 {
+            return Err(invalid_data("Interlaced PNG images are not supported".to_string()));
+        }
+
+        Ok(header)
+    }
+
+    fn channels_count(&self) -> usize {
+        match self.color_type {
+            PNG_RGB => 3,
+            PNG_GRAYSCALE_ALPHA => 2,
+            PNG_RGBA => 4,
+            _ => 1,
+        }
+    }
+
+    fn bits_per_pixel(&self) -> usize {
+        self.channels_count() * self.bit_depth as usize
+    }
+
+    /// Undo the filter of every scanline, and get the raw rows.
+    fn unfilter(&self, scanlines: &[u8]) -> io::Result<Vec<Vec<u8>>> {
+        let row_size = (self.width * self.bits_per_pixel()).div_ceil(8);
+        // Filters work on whole bytes, and compare each byte to the matching byte of the previous pixel.
+        let pixel_size = (self.bits_per_pixel() / 8).max(1);
+
+        if scanlines.len() < (row_size + 1) * self.height {
+            return Err(invalid_data("The PNG image data is truncated".to_string()));
+        }
+
+        let mut rows: Vec<Vec<u8>> = Vec::with_capacity(self.height);
+        let empty_row = vec![0u8; row_size];
+        for scanline in scanlines.chunks(row_size + 1).take(self.height) {
+            let filter = scanline[0];
+            let mut row = scanline[1..].to_vec();
+            let previous = rows.last().unwrap_or(&empty_row);
+
+            for index in 0..row_size {
+                let left = if index >= pixel_size { row[index - pixel_size] } else { 0 };
+                let up = previous[index];
+                let up_left = if index >= pixel_size { previous[index - pixel_size] } else { 0 };
+
+                let predictor = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((left as u16 + up as u16) / 2) as u8,
+                    4 => paeth(left, up, up_left),
+                    other => return Err(invalid_data(format!("Invalid PNG filter type {}", other))),
+                };
+                row[index] = row[index].wrapping_add(predictor);
+            }
+
+            rows.push(row);
+        }
+
+        Ok(rows)
+    }
+
+    /// Get a single channel of a pixel out of an unfiltered row.
+    fn sample(&self, row: &[u8], x: usize, channel: usize) -> u32 {
+        let bit_depth = self.bit_depth as usize;
+        let bit_offset = (x * self.channels_count() + channel) * bit_depth;
+        let byte = bit_offset / 8;
+
+        match bit_depth {
+            16 => ((row[byte] as u32) << 8) | row[byte + 1] as u32,
+            8 => row[byte] as u32,
+            // Sub-byte samples are packed from the most significant bit
+            _ => {
+                let shift = 8 - bit_depth - bit_offset % 8;
+                ((row[byte] >> shift) as u32) & ((1 << bit_depth) - 1)
+            }
+        }
+    }
+}
+
+/// Predict a byte out of its neighbours: the one closest to left + up - up_left.
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let left_distance = (estimate - left as i16).abs();
+    let up_distance = (estimate - up as i16).abs();
+    let up_left_distance = (estimate - up_left as i16).abs();
+
+    if left_distance <= up_distance && left_distance <= up_left_distance {
+        left
+    } else if up_distance <= up_left_distance {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Undo the gamma 2.0 correction which is applied when images are written.
+fn to_linear(display_value: f64) -> f64 {
+    display_value * display_value
+}
+
+/// Read the next whitespace separated token, skipping comments.
+fn read_ppm_token<'d>(data: &'d [u8], position: &mut usize) -> io::Result<&'d [u8]> {
+    loop {
+        match data.get(*position) {
+            Some(b'#') => {
+                while data.get(*position).is_some_and(|&byte| byte != b'\n') {
+                    *position += 1;
+                }
+            }
+            Some(byte) if byte.is_ascii_whitespace() => *position += 1,
+            Some(_) => break,
+            None => return Err(invalid_data("The PPM image is truncated".to_string())),
+        }
+    }
+
+    let start = *position;
+    while data.get(*position).is_some_and(|byte| !byte.is_ascii_whitespace()) {
+        *position += 1;
+    }
+
+    Ok(&data[start..*position])
+}
+
+fn parse_ppm_number(token: &[u8]) -> io::Result<usize> {
+    std::str::from_utf8(token).ok()
+        .and_then(|text| text.parse().ok())
+        .ok_or_else(|| invalid_data(format!("Invalid number '{}' in the PPM header", String::from_utf8_lossy(token))))
+}
+
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::image_encoder::ImageEncoder;
+    use crate::utils::png_writer::{crc32, PNGBitDepth, PNGWriter};
+    use crate::utils::zlib::compress_stored;
+
+    /// Build a PNG file out of its IHDR fields, extra chunks placed before the image data, and raw scanlines.
+    fn png_file(width: u32, height: u32, bit_depth: u8, color_type: u8, chunks: &[(&[u8; 4], &[u8])], scanlines: &[u8]) -> Vec<u8> {
+        let mut header = Vec::new();
+        header.extend_from_slice(&width.to_be_bytes());
+        header.extend_from_slice(&height.to_be_bytes());
+        header.extend_from_slice(&[bit_depth, color_type, 0, 0, 0]);
+        let image_data = compress_stored(scanlines);
+
+        let mut all_chunks: Vec<(&[u8; 4], &[u8])> = vec![(b"IHDR", &header)];
+        all_chunks.extend_from_slice(chunks);
+        all_chunks.push((b"IDAT", &image_data));
+        all_chunks.push((b"IEND", &[]));
+
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (chunk_type, data) in all_chunks {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(chunk_type.as_slice());
+            png.extend_from_slice(data);
+            png.extend_from_slice(&crc32(&[chunk_type.as_slice(), data]).to_be_bytes());
+        }
+        png
+    }
+
+    fn assert_pixels(image: &Image, expected: &[[f64; 3]]) {
+        assert_eq!(image.pixels().len(), expected.len());
+        for (pixel, expected) in image.pixels().iter().zip(expected) {
+            for (component, expected) in [pixel.red, pixel.green, pixel.blue].iter().zip(expected) {
+                assert!((component - expected).abs() < 1e-9, "expected {:?}, got {:?}", expected, pixel);
+            }
+        }
+    }
+
+    fn error_message(result: io::Result<Image>) -> String {
+        match result {
+            Ok(_) => panic!("expected the image to be rejected"),
+            Err(error) => error.to_string(),
+        }
+    }
+
+    #[test]
+    fn paeth_picks_the_closest_neighbour() {
+        assert_eq!(paeth(10, 20, 10), 20);
+        assert_eq!(paeth(20, 10, 10), 20);
+        assert_eq!(paeth(10, 20, 30), 10);
+        assert_eq!(paeth(30, 20, 10), 30);
+        assert_eq!(paeth(10, 10, 10), 10);
+    }
+
+    #[test]
+    fn png_with_every_filter_is_decoded() {
+        // A 3x4 RGB image made by zlib, with the rows filtered by Sub, Up, Average and Paeth,
+        // a text chunk, and the image data split between two IDAT chunks.
+        let png = [
+            137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 3, 0, 0, 0, 4, 8, 2, 0, 0, 0, 196, 79,
+            18, 80, 0, 0, 0, 12, 116, 69, 88, 116, 67, 111, 109, 109, 101, 110, 116, 0, 116, 101, 115, 116, 87, 97, 43,
+            233, 0, 0, 0, 10, 73, 68, 65, 84, 120, 218, 99, 100, 144, 179, 113, 3, 3, 38, 7, 15, 213, 14, 0, 0, 0, 17,
+            73, 68, 65, 84, 17, 24, 96, 150, 211, 181, 209, 5, 3, 22, 184, 24, 0, 113, 187, 5, 6, 10, 15, 94, 164, 0, 0,
+            0, 0, 73, 69, 78, 68, 174, 66, 96, 130,
+        ];
+        let rows: [[u8; 9]; 4] = [
+            [0, 30, 60, 70, 100, 130, 140, 170, 200],
+            [20, 50, 80, 90, 120, 150, 160, 190, 220],
+            [40, 70, 100, 110, 140, 170, 180, 210, 240],
+            [60, 90, 120, 130, 160, 190, 200, 230, 4],
+        ];
+        let expected: Vec<[f64; 3]> = rows.iter()
+            .flat_map(|row| row.chunks(3).map(|rgb| {
+                [to_linear(rgb[0] as f64 / 255.0), to_linear(rgb[1] as f64 / 255.0), to_linear(rgb[2] as f64 / 255.0)]
+            }).collect::<Vec<_>>())
+            .collect();
+
+        let image = decode_png(&png).unwrap();
+        assert_eq!((image.width(), image.height()), (3, 4));
+        assert_pixels(&image, &expected);
+    }
+
+    #[test]
+    fn png_writer_output_is_decoded() {
+        let pixels = vec![Color::new(0.0, 0.25, 1.0), Color::new(1.0, 0.0, 0.25)];
+        let image = Image::from_pixels(2, 1, pixels);
+
+        for (bit_depth, tolerance) in [(PNGBitDepth::Eight, 1e-2), (PNGBitDepth::Sixteen, 1e-4)] {
+            let mut png = Vec::new();
+            PNGWriter::new(bit_depth).encode(&image, &mut png).unwrap();
+
+            let decoded = decode_png(&png).unwrap();
+            assert_eq!((decoded.width(), decoded.height()), (2, 1));
+            for (pixel, expected) in decoded.pixels().iter().zip(image.pixels()) {
+                assert!((*pixel - *expected).size() < tolerance, "expected {:?}, got {:?}", expected, pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn png_with_palette_and_packed_samples_is_decoded() {
+        // 2 bits per palette index, so a row of 5 pixels takes 2 bytes.
+        let palette = [0, 0, 0, 255, 255, 255, 255, 0, 0];
+        let scanlines = [0, 0b00_01_10_01, 0b00_000000];
+        let png = png_file(5, 1, 2, PNG_PALETTE, &[(b"PLTE", &palette)], &scanlines);
+
+        let image = decode_png(&png).unwrap();
+        assert_pixels(&image, &[[0.0; 3], [1.0; 3], [1.0, 0.0, 0.0], [1.0; 3], [0.0; 3]]);
+
+        let png = png_file(5, 1, 2, PNG_PALETTE, &[(b"PLTE", &palette[..6])], &scanlines);
+        assert_eq!(error_message(decode_png(&png)), "PNG palette index out of range");
+    }
+
+    #[test]
+    fn png_with_grayscale_and_alpha_is_decoded() {
+        // 16 bit gray and alpha values, where the alpha is ignored.
+        let scanlines = [0, 0x80, 0x00, 0x00, 0x00, 0xFF, 0xFF, 0x12, 0x34];
+        let png = png_file(2, 1, 16, PNG_GRAYSCALE_ALPHA, &[], &scanlines);
+
+        let gray = to_linear(0x8000 as f64 / 65535.0);
+        assert_pixels(&decode_png(&png).unwrap(), &[[gray; 3], [1.0; 3]]);
+    }
+
+    #[test]
+    fn invalid_png_images_are_rejected() {
+        let error = |png: &[u8]| error_message(decode_png(png));
+
+        assert_eq!(error(&png_file(1, 1, 16, PNG_PALETTE, &[], &[0, 0])), "Invalid PNG bit depth 16 for color type 3");
+        assert_eq!(error(&png_file(1, 1, 8, 5, &[], &[0, 0])), "Invalid PNG color type 5");
+        assert_eq!(error(&png_file(1, 2, 8, PNG_GRAYSCALE, &[], &[0, 0])), "The PNG image data is truncated");
+        assert_eq!(error(&png_file(1, 1, 8, PNG_GRAYSCALE, &[], &[5, 0])), "Invalid PNG filter type 5");
+
+        let png = png_file(1, 1, 8, PNG_GRAYSCALE, &[], &[0, 0]);
+        assert_eq!(error(&png[..png.len() - 6]), "The PNG image is truncated");
+    }
+
+    #[test]
+    fn ascii_ppm_with_comments_is_decoded() {
+        let ppm = b"P3\n# A comment\n2 1 # after the size\n4\n0 2 4\n4 0 2\n";
+
+        let image = decode_ppm(ppm).unwrap();
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_pixels(&image, &[[0.0, 0.25, 1.0], [1.0, 0.0, 0.25]]);
+    }
+
+    #[test]
+    fn binary_ppm_is_decoded() {
+        let image = decode_ppm(b"P6 1 2 255\n\x00\x80\xFF\xFF\x00\x80").unwrap();
+        let half = to_linear(128.0 / 255.0);
+        assert_pixels(&image, &[[0.0, half, 1.0], [1.0, 0.0, half]]);
+
+        // Values above 255 take 2 big endian bytes.
+        let image = decode_ppm(b"P6 1 1 1000\n\x03\xE8\x01\xF4\x00\x00").unwrap();
+        assert_pixels(&image, &[[1.0, 0.25, 0.0]]);
+    }
+
+    #[test]
+    fn invalid_ppm_images_are_rejected() {
+        let error = |ppm: &[u8]| error_message(decode_ppm(ppm));
+
+        assert_eq!(error(b"P6 2 1 255\n\x00\x00\x00"), "The PPM image data is truncated");
+        assert_eq!(error(b"P3 1 1 255\n0 0"), "The PPM image is truncated");
+        assert_eq!(error(b"P3 1 x 255\n"), "Invalid number 'x' in the PPM header");
+        assert_eq!(error(b"P3 1 1 0\n"), "Invalid PPM maximum value 0");
+        assert_eq!(error(b"P5 1 1 255\n\x00"), "Unsupported PPM format, expected P3 or P6");
+    }
+}
@@ -1 +1,22 @@
-pub mod ppm_writer;
\ No newline at end of file
+pub mod ppm_writer;
+pub mod image;
+pub mod image_encoder;
+pub mod pgm_writer;
+pub mod pfm_writer;
+pub mod png_writer;
+pub mod hdr_writer;
+pub mod exr_writer;
+pub mod zlib;
+pub mod image_loader;
+pub mod obj_loader;
+pub mod scene_file;
+
+pub use image::Image;
+pub use image_encoder::ImageEncoder;
+pub use ppm_writer::{PNMEncoding, PPMWriter};
+pub use pgm_writer::PGMWriter;
+pub use pfm_writer::PFMWriter;
+pub use png_writer::{PNGBitDepth, PNGWriter};
+pub use hdr_writer::HDRWriter;
+pub use exr_writer::{EXRPixelType, EXRWriter};
+pub use image_loader::load_image;
@@ -0,0 +1,542 @@
+use crate::engine::hittables::{MeshBuffers, MeshFace, TriangleMesh};
+use crate::engine::materials::{Dielectric, DiffuseLight, Lambertian, Material, Metal};
+use crate::engine::textures::{ImageTexture, Texture};
+use crate::vectors::{Color, Point, Vector};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+
+/// The refraction index used for transparent materials which don't specify one.
+const DEFAULT_REFRACTION_INDEX: f64 = 1.5;
+
+/// Illumination models which describe a transparent surface.
+const TRANSPARENT_ILLUMINATION_MODELS: [u32; 4] = [4, 6, 7, 9];
+
+/// Illumination models which describe a reflective surface.
+const REFLECTIVE_ILLUMINATION_MODELS: [u32; 3] = [3, 5, 8];
+
+/// An error which occurred while loading an OBJ file or one of its material libraries.
+#[derive(Debug)]
+pub enum ObjError {
+    Io { path: String, error: io::Error },
+    Parse { path: String, line: usize, message: String },
+
+    /// The file has no faces, so the mesh would be invisible.
+    Empty { path: String },
+}
+
+/// The properties of a single material in an MTL file.
+#[derive(Clone)]
+struct MtlProperties {
+    /// Kd
+    diffuse: Color,
+
+    /// map_Kd, replaces the diffuse color when set.
+    diffuse_texture: Option<Arc<dyn Texture>>,
+
+    /// Ks
+    specular: Color,
+
+    /// Ke
+    emission: Color,
+
+    /// Ns, the Phong exponent. Higher values mean a sharper reflection.
+    shininess: f64,
+
+    /// Ni
+    refraction_index: Option<f64>,
+
+    /// d (or 1 - Tr). 1 means fully opaque.
+    dissolve: f64,
+
+    /// illum
+    illumination_model: Option<u32>,
+}
+
+/// The buffer indices of a single corner of a face.
+#[derive(Copy, Clone, Debug)]
+struct FaceVertex {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+/// Keeps the state of an OBJ file while it's being parsed.
+struct ObjParser<'p> {
+    path: &'p str,
+    base_directory: Option<&'p Path>,
+    positions: Vec<Point>,
+    normals: Vec<Vector>,
+    uvs: Vec<(f64, f64)>,
+    faces: Vec<MeshFace>,
+    material_library: HashMap<String, Arc<dyn Material>>,
+    materials: Vec<Arc<dyn Material>>,
+    material_indices: HashMap<String, usize>,
+    current_material: usize,
+}
+
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, error } => write!(f, "Failed to read {}: {}", path, error),
+            ObjError::Parse { path, line, message } => write!(f, "{}:{}: {}", path, line, message),
+            ObjError::Empty { path } => write!(f, "{}: The mesh has no faces", path),
+        }
+    }
+}
+
+impl Error for ObjError {}
+
+impl Default for MtlProperties {
+    fn default() -> Self {
+        MtlProperties {
+            diffuse: Color::new(0.8, 0.8, 0.8),
+            diffuse_texture: None,
+            specular: Color::zeroes(),
+            emission: Color::zeroes(),
+            shininess: 0.0,
+            refraction_index: None,
+            dissolve: 1.0,
+            illumination_model: None,
+        }
+    }
+}
+
+impl MtlProperties {
+    /// Map the properties onto the closest of the engine's materials:
+    /// emissive surfaces become lights, transparent ones become dielectrics,
+    /// reflective ones become metals, and everything else is lambertian.
+    fn to_material(&self) -> Arc<dyn Material> {
+        let illumination_model = self.illumination_model.unwrap_or(0);
+
+        if self.emission.luminance() > 0.0 {
+            return Arc::new(DiffuseLight::new(self.emission));
+        }
+
+        if self.dissolve < 1.0 || TRANSPARENT_ILLUMINATION_MODELS.contains(&illumination_model) {
+            let refraction_index = self.refraction_index
+                .filter(|&index| index > 1.0)
+                .unwrap_or(DEFAULT_REFRACTION_INDEX);
+            return Arc::new(Dielectric::new(refraction_index));
+        }
+
+        let is_specular = self.specular.luminance() > 0.0 && self.specular.luminance() > self.diffuse.luminance();
+        if REFLECTIVE_ILLUMINATION_MODELS.contains(&illumination_model) || is_specular {
+            // Convert the Phong exponent into a roughness-like fuzz
+            let fuzz = (2.0 / (self.shininess.max(0.0) + 2.0)).sqrt();
+            return Arc::new(Metal::new(self.specular, fuzz));
+        }
+
+        match &self.diffuse_texture {
+            Some(texture) => Arc::new(Lambertian::with_texture(Arc::clone(texture))),
+            None => Arc::new(Lambertian::new(self.diffuse)),
+        }
+    }
+}
+
+impl<'p> ObjParser<'p> {
+    fn new(path: &'p str, base_directory: Option<&'p Path>) -> ObjParser<'p> {
+        // Faces which appear before any 'usemtl' statement use the default material.
+        let default_material: Arc<dyn Material> = Arc::new(Lambertian::new(MtlProperties::default().diffuse));
+
+        ObjParser {
+            path,
+            base_directory,
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            faces: Vec::new(),
+            material_library: HashMap::new(),
+            materials: vec![default_material],
+            material_indices: HashMap::new(),
+            current_material: 0,
+        }
+    }
+
+    fn parse(mut self, source: &str) -> Result<TriangleMesh, ObjError> {
+        for (line_index, line) in source.lines().enumerate() {
+            let line_number = line_index + 1;
+            let line = strip_comment(line);
+            let mut tokens = line.split_whitespace();
+
+            let keyword = match tokens.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+            let arguments: Vec<&str> = tokens.collect();
+
+            match keyword {
+                "v" => {
+                    let [x, y, z] = parse_floats(self.path, line_number, &arguments)?;
+                    self.positions.push(Point::new(x, y, z));
+                }
+                "vn" => {
+                    let [x, y, z] = parse_floats(self.path, line_number, &arguments)?;
+                    self.normals.push(Vector::new(x, y, z));
+                }
+                "vt" => {
+                    // Only u is required, v defaults to 0, and the optional w is ignored.
+                    let [u] = parse_floats(self.path, line_number, &arguments)?;
+                    let v = match arguments.len() {
+                        1 => 0.0,
+                        _ => parse_floats::<2>(self.path, line_number, &arguments)?[1],
+                    };
+                    self.uvs.push((u, v));
+                }
+                "f" => self.parse_face(line_number, &arguments)?,
+                "mtllib" => {
+                    for library in arguments {
+                        self.load_material_library(line_number, library)?;
+                    }
+                }
+                "usemtl" => {
+                    let name = line.trim_start()[keyword.len()..].trim();
+                    self.use_material(line_number, name)?;
+                }
+                // Everything else (objects, groups, smoothing groups, lines, ...) doesn't affect the mesh
+                _ => {}
+            }
+        }
+
+        if self.faces.is_empty() {
+            return Err(ObjError::Empty { path: self.path.to_string() });
+        }
+
+        let buffers = MeshBuffers {
+            positions: self.positions,
+            normals: self.normals,
+            uvs: self.uvs,
+            materials: self.materials,
+        };
+
+        Ok(TriangleMesh::new(buffers, self.faces))
+    }
+
+    /// Faces with more than 3 vertices are split into a fan of triangles.
+    fn parse_face(&mut self, line_number: usize, arguments: &[&str]) -> Result<(), ObjError> {
+        if arguments.len() < 3 {
+            return Err(parse_error(self.path, line_number, "A face must have at least 3 vertices".to_string()));
+        }
+
+        let mut vertices = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            vertices.push(self.parse_face_vertex(line_number, argument)?);
+        }
+
+        for i in 1..(vertices.len() - 1) {
+            let corners = [vertices[0], vertices[i], vertices[i + 1]];
+
+            // UVs and normals are used only if all of the corners have them
+            self.faces.push(MeshFace {
+                positions: corners.map(|corner| corner.position),
+                uvs: all_set(corners.map(|corner| corner.uv)),
+                normals: all_set(corners.map(|corner| corner.normal)),
+                material: self.current_material,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Parse a 'position/uv/normal' triplet, where the uv and normal are optional.
+    fn parse_face_vertex(&self, line_number: usize, argument: &str) -> Result<FaceVertex, ObjError> {
+        let mut parts = argument.split('/');
+
+        let position = parts.next().unwrap_or("");
+        let position = self.resolve_index(line_number, position, self.positions.len())?
+            .ok_or_else(|| parse_error(self.path, line_number, format!("Missing vertex index in '{}'", argument)))?;
+        let uv = self.resolve_index(line_number, parts.next().unwrap_or(""), self.uvs.len())?;
+        let normal = self.resolve_index(line_number, parts.next().unwrap_or(""), self.normals.len())?;
+
+        Ok(FaceVertex { position, uv, normal })
+    }
+
+    /// OBJ indices start at 1, and negative indices are relative to the end of the buffer.
+    fn resolve_index(&self, line_number: usize, index: &str, buffer_length: usize) -> Result<Option<usize>, ObjError> {
+        if index.is_empty() {
+            return Ok(None);
+        }
+
+        let index: i64 = index.parse()
+            .map_err(|_| parse_error(self.path, line_number, format!("Invalid index '{}'", index)))?;
+
+        let resolved = if index < 0 { buffer_length as i64 + index } else { index - 1 };
+        if resolved < 0 || resolved >= buffer_length as i64 {
+            return Err(parse_error(self.path, line_number, format!("Index {} is out of range", index)));
+        }
+
+        Ok(Some(resolved as usize))
+    }
+
+    fn use_material(&mut self, line_number: usize, name: &str) -> Result<(), ObjError> {
+        if let Some(&index) = self.material_indices.get(name) {
+            self.current_material = index;
+            return Ok(());
+        }
+
+        let material = self.material_library.get(name)
+            .ok_or_else(|| parse_error(self.path, line_number, format!("Unknown material '{}'", name)))?;
+
+        self.materials.push(Arc::clone(material));
+        self.current_material = self.materials.len() - 1;
+        self.material_indices.insert(name.to_string(), self.current_material);
+
+        Ok(())
+    }
+
+    fn load_material_library(&mut self, line_number: usize, library: &str) -> Result<(), ObjError> {
+        let library_path = match self.base_directory {
+            Some(base_directory) => base_directory.join(library),
+            None => return Err(parse_error(self.path, line_number, format!("Cannot locate material library '{}'", library))),
+        };
+
+        let library_path = library_path.to_string_lossy().to_string();
+        let source = read_file(&library_path)?;
+        self.material_library.extend(parse_mtl(&source, &library_path)?);
+
+        Ok(())
+    }
+}
+
+/// Load a mesh out of a Wavefront OBJ file. Material libraries are
+/// looked up relative to the directory of the OBJ file.
+pub fn load_obj(file_path: &str) -> Result<TriangleMesh, ObjError> {
+    let source = read_file(file_path)?;
+    let base_directory = Path::new(file_path).parent();
+
+    ObjParser::new(file_path, base_directory).parse(&source)
+}
+
+/// Parse a mesh out of the content of an OBJ file. Since there is no file, 'mtllib'
+/// statements are not supported, and all faces use the default material.
+pub fn parse_obj(source: &str, source_name: &str) -> Result<TriangleMesh, ObjError> {
+    ObjParser::new(source_name, None).parse(source)
+}
+
+/// Parse the materials of an MTL file, mapped by their names.
+pub fn parse_mtl(source: &str, source_name: &str) -> Result<HashMap<String, Arc<dyn Material>>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlProperties)> = None;
+
+    for (line_index, line) in source.lines().enumerate() {
+        let line_number = line_index + 1;
+        let line = strip_comment(line);
+        let mut tokens = line.split_whitespace();
+
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            if let Some((name, properties)) = current.take() {
+                materials.insert(name, properties.to_material());
+            }
+
+            let name = line.trim_start()[keyword.len()..].trim().to_string();
+            current = Some((name, MtlProperties::default()));
+            continue;
+        }
+
+        let properties = match current.as_mut() {
+            Some((_, properties)) => properties,
+            None => return Err(parse_error(source_name, line_number, format!("'{}' appears before 'newmtl'", keyword))),
+        };
+
+        match keyword {
+            "Kd" => properties.diffuse = parse_color(source_name, line_number, &arguments)?,
+            "Ks" => properties.specular = parse_color(source_name, line_number, &arguments)?,
+            "Ke" => properties.emission = parse_color(source_name, line_number, &arguments)?,
+            "map_Kd" => properties.diffuse_texture = Some(load_texture(source_name, line_number, &arguments)?),
+            "Ns" => properties.shininess = parse_float(source_name, line_number, &arguments)?,
+            "Ni" => properties.refraction_index = Some(parse_float(source_name, line_number, &arguments)?),
+            "d" => properties.dissolve = parse_float(source_name, line_number, &arguments)?,
+            "Tr" => properties.dissolve = 1.0 - parse_float(source_name, line_number, &arguments)?,
+            "illum" => {
+                let model = parse_float(source_name, line_number, &arguments)?;
+                properties.illumination_model = Some(model as u32);
+            }
+            // Everything else (ambient color, other texture maps, ...) has no equivalent in the engine
+            _ => {}
+        }
+    }
+
+    if let Some((name, properties)) = current.take() {
+        materials.insert(name, properties.to_material());
+    }
+
+    Ok(materials)
+}
+
+/// Load the image of a texture map statement, relative to the directory of the MTL file.
+/// Options (such as '-s 1 1 1') come before the file name, and are ignored.
+fn load_texture(source_name: &str, line: usize, arguments: &[&str]) -> Result<Arc<dyn Texture>, ObjError> {
+    let file_name = arguments.last()
+        .ok_or_else(|| parse_error(source_name, line, "Missing texture file name".to_string()))?;
+    let path = match Path::new(source_name).parent() {
+        Some(directory) => directory.join(file_name).to_string_lossy().to_string(),
+        None => file_name.to_string(),
+    };
+
+    let texture = ImageTexture::load(&path).map_err(|error| ObjError::Io { path, error })?;
+    Ok(Arc::new(texture))
+}
+
+fn all_set(indices: [Option<usize>; 3]) -> Option<[usize; 3]> {
+    match indices {
+        [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+        _ => None
+    }
+}
+
+fn read_file(file_path: &str) -> Result<String, ObjError> {
+    fs::read_to_string(file_path).map_err(|error| ObjError::Io { path: file_path.to_string(), error })
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find('#') {
+        Some(index) => &line[..index],
+        None => line
+    }
+}
+
+fn parse_error(path: &str, line: usize, message: String) -> ObjError {
+    ObjError::Parse { path: path.to_string(), line, message }
+}
+
+/// Parse exactly N leading numbers. Any extra numbers (such as the optional w
+/// coordinate of vertices) are ignored.
+fn parse_floats<const N: usize>(path: &str, line: usize, arguments: &[&str]) -> Result<[f64; N], ObjError> {
+    if arguments.len() < N {
+        return Err(parse_error(path, line, format!("Expected {} numbers, got {}", N, arguments.len())));
+    }
+
+    let mut result = [0.0; N];
+    for (value, argument) in result.iter_mut().zip(arguments) {
+        *value = argument.parse()
+            .map_err(|_| parse_error(path, line, format!("Invalid number '{}'", argument)))?;
+    }
+
+    Ok(result)
+}
+
+fn parse_float(path: &str, line: usize, arguments: &[&str]) -> Result<f64, ObjError> {
+    let [value] = parse_floats(path, line, arguments)?;
+    Ok(value)
+}
+
+/// A color is either 3 components, or a single value for gray.
+fn parse_color(path: &str, line: usize, arguments: &[&str]) -> Result<Color, ObjError> {
+    if arguments.len() == 1 {
+        let value = parse_float(path, line, arguments)?;
+        return Ok(Color::new(value, value, value));
+    }
+
+    let [red, green, blue] = parse_floats(path, line, arguments)?;
+    Ok(Color::new(red, green, blue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::hittables::{HitRecord, Hittable};
+    use crate::engine::Ray;
+
+    const SQUARE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    fn parse_error_line(source: &str) -> Option<usize> {
+        match parse_obj(source, "test.obj") {
+            Err(ObjError::Parse { line, .. }) => Some(line),
+            _ => None
+        }
+    }
+
+    fn hit_from_above(mesh: &TriangleMesh, x: f64, y: f64) -> Option<HitRecord> {
+        mesh.hit(&Ray::new(Point::new(x, y, 1.0), Vector::new(0.0, 0.0, -1.0)), 0.0, f64::INFINITY)
+    }
+
+    #[test]
+    fn polygons_are_split_into_triangles() {
+        let mesh = parse_obj(&format!("{}f 1 2 3 4\n", SQUARE), "test.obj").unwrap();
+        assert_eq!(mesh.faces_count(), 2);
+        assert!(hit_from_above(&mesh, 0.9, 0.1).is_some());
+        assert!(hit_from_above(&mesh, 0.1, 0.9).is_some());
+        assert!(hit_from_above(&mesh, 1.1, 0.5).is_none());
+    }
+
+    #[test]
+    fn negative_indices_are_relative_to_the_end() {
+        let mesh = parse_obj(&format!("{}f -4 -3 -2\n# comment\nf -4 -2 -1 # trailing\n", SQUARE), "test.obj").unwrap();
+        assert_eq!(mesh.faces_count(), 2);
+    }
+
+    #[test]
+    fn texture_coordinates_accept_one_to_three_values() {
+        let source = format!("{}vt 0.5\nvt 0.5 0.25\nvt 0.5 0.25 1\nf 1/1 2/1 3/1\nf 1/2 3/3 4/2\n", SQUARE);
+        let mesh = parse_obj(&source, "test.obj").unwrap();
+
+        assert_eq!(hit_from_above(&mesh, 0.9, 0.1).unwrap().uv, (0.5, 0.0));
+        assert_eq!(hit_from_above(&mesh, 0.1, 0.9).unwrap().uv, (0.5, 0.25));
+        assert_eq!(parse_error_line(&format!("{}vt\n", SQUARE)), Some(5));
+    }
+
+    #[test]
+    fn errors_point_at_the_line() {
+        assert_eq!(parse_error_line("v 0 0 0\nv 1 0\n"), Some(2));
+        assert_eq!(parse_error_line("v 0 0 zero\n"), Some(1));
+        assert_eq!(parse_error_line(&format!("{}\nf 1 2 5\n", SQUARE)), Some(6));
+        assert_eq!(parse_error_line(&format!("{}f 1 2\n", SQUARE)), Some(5));
+        assert_eq!(parse_error_line(&format!("{}f 1/1 2 3\n", SQUARE)), Some(5));
+        assert_eq!(parse_error_line(&format!("{}usemtl missing\n", SQUARE)), Some(5));
+        assert_eq!(parse_error_line(&format!("{}mtllib materials.mtl\n", SQUARE)), Some(5));
+    }
+
+    #[test]
+    fn indented_material_names_are_read_whole() {
+        match parse_obj(&format!("{}  usemtl red  paint \n", SQUARE), "test.obj") {
+            Err(ObjError::Parse { message, .. }) => assert_eq!(message, "Unknown material 'red  paint'"),
+            _ => panic!("Expected an unknown material error"),
+        }
+
+        let materials = parse_mtl("\tnewmtl red paint\n\t\tKd 1 0 0\n  newmtl lamp\n    Ke 4 4 4\n", "test.mtl").unwrap();
+        let mut names: Vec<&str> = materials.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["lamp", "red paint"]);
+    }
+
+    #[test]
+    fn meshes_without_faces_are_rejected() {
+        assert!(matches!(parse_obj(SQUARE, "test.obj"), Err(ObjError::Empty { .. })));
+        assert!(matches!(parse_obj("", "test.obj"), Err(ObjError::Empty { .. })));
+    }
+
+    #[test]
+    fn mtl_materials_are_mapped_by_name() {
+        let source = "newmtl red paint\nKd 1 0 0\n\nnewmtl lamp\nKe 4 4 4\nnewmtl glass # comment\nd 0.5\nNi 1.33\n";
+        let materials = parse_mtl(source, "test.mtl").unwrap();
+
+        let mut names: Vec<&str> = materials.keys().map(|name| name.as_str()).collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["glass", "lamp", "red paint"]);
+        assert!(materials["lamp"].is_emissive());
+        assert!(!materials["red paint"].is_emissive());
+    }
+
+    #[test]
+    fn mtl_errors_point_at_the_line() {
+        let error_line = |source: &str| match parse_mtl(source, "test.mtl") {
+            Err(ObjError::Parse { line, .. }) => Some(line),
+            _ => None
+        };
+
+        assert_eq!(error_line("Kd 1 1 1\n"), Some(1));
+        assert_eq!(error_line("newmtl a\nKd 1 1\n"), Some(2));
+        assert_eq!(error_line("newmtl a\n\nNs shiny\n"), Some(3));
+    }
+}
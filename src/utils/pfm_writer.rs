@@ -0,0 +1,47 @@
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::ImageEncoder;
+
+/// Used for writing images in Portable Float Map format, which keeps the
+/// linear colors as is, without clamping or gamma correction.
+/// The output format is as follows:
+///
+/// PF
+/// 3 2
+/// -1.0
+/// <RGB triplets of 32 bit floats>
+///
+/// PF => Means that pixels are represented by RGB floats.
+/// 3 2 => 3 columns and 2 rows.
+/// -1.0 => Negative scale means the floats are little endian.
+///
+/// Unlike the other Netpbm formats, the rows are written from the bottom to the top.
+#[derive(Default)]
+pub struct PFMWriter;
+
+
+impl PFMWriter {
+    pub fn new() -> PFMWriter {
+        PFMWriter
+    }
+}
+
+impl ImageEncoder for PFMWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        writeln!(buffer, "PF")?;
+        writeln!(buffer, "{} {}", image.width(), image.height())?;
+        writeln!(buffer, "-1.0")?;
+
+        for row in image.rows().rev() {
+            let mut bytes = Vec::with_capacity(row.len() * 12);
+            for color in row {
+                bytes.extend_from_slice(&(color.red as f32).to_le_bytes());
+                bytes.extend_from_slice(&(color.green as f32).to_le_bytes());
+                bytes.extend_from_slice(&(color.blue as f32).to_le_bytes());
+            }
+            buffer.write_all(&bytes)?;
+        }
+
+        Ok(())
+    }
+}
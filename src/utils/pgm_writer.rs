@@ -0,0 +1,65 @@
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::{ImageEncoder, to_display_byte, to_linear_byte};
+use crate::utils::ppm_writer::PNMEncoding;
+
+/// Used for writing grayscale images in Portable Gray Map format, which is
+/// useful for single channel outputs such as depth or ambient occlusion.
+/// Each pixel is converted to its luminance.
+///
+/// The format is the same as PPM's, except that the magic number is P2 (ASCII)
+/// or P5 (binary), and every pixel is represented by a single value instead of a triplet.
+pub struct PGMWriter {
+    encoding: PNMEncoding,
+
+    /// Whether to apply gamma correction. Should be disabled for values which
+    /// aren't colors, like depth, which should be written as is.
+    gamma_correction: bool,
+}
+
+
+impl PGMWriter {
+    pub fn new(encoding: PNMEncoding, gamma_correction: bool) -> PGMWriter {
+        PGMWriter { encoding, gamma_correction }
+    }
+
+    fn to_byte(&self, value: f64) -> u8 {
+        if self.gamma_correction {
+            return to_display_byte(value);
+        }
+
+        to_linear_byte(value)
+    }
+}
+
+impl Default for PGMWriter {
+    fn default() -> Self {
+        PGMWriter::new(PNMEncoding::Binary, true)
+    }
+}
+
+impl ImageEncoder for PGMWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        match self.encoding {
+            PNMEncoding::Ascii => writeln!(buffer, "P2")?,
+            PNMEncoding::Binary => writeln!(buffer, "P5")?,
+        }
+        writeln!(buffer, "{} {}", image.width(), image.height())?;
+        writeln!(buffer, "255")?;
+
+        for row in image.rows() {
+            let bytes: Vec<u8> = row.iter().map(|color| self.to_byte(color.luminance())).collect();
+
+            match self.encoding {
+                PNMEncoding::Ascii => {
+                    for byte in bytes {
+                        writeln!(buffer, "{}", byte)?;
+                    }
+                }
+                PNMEncoding::Binary => buffer.write_all(&bytes)?
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,190 @@
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::{ImageEncoder, clamp, to_display_bytes};
+use crate::utils::zlib::compress_stored;
+
+pub const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Color type 2 means every pixel is an RGB triplet.
+const RGB_COLOR_TYPE: u8 = 2;
+
+/// Every scanline starts with the filter applied to it. 0 means no filter.
+const NO_FILTER: u8 = 0;
+
+/// How many bits are used for every color component.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PNGBitDepth {
+    Eight,
+    Sixteen,
+}
+
+/// Used for writing images in Portable Network Graphics format.
+/// A PNG file is made of a signature followed by chunks, where each chunk is:
+///
+/// <length: u32> <type: 4 ascii letters> <data> <crc32 of type and data: u32>
+///
+/// IHDR => The image size and pixel format.
+/// IDAT => The scanlines, compressed with zlib.
+/// IEND => Marks the end of the image.
+///
+/// The scanlines are stored in zlib's uncompressed blocks, so no compression
+/// library is needed, at the cost of a bigger file.
+pub struct PNGWriter {
+    bit_depth: PNGBitDepth,
+}
+
+
+impl PNGWriter {
+    pub fn new(bit_depth: PNGBitDepth) -> PNGWriter {
+        PNGWriter { bit_depth }
+    }
+
+    fn get_scanlines(&self, image: &Image) -> Vec<u8> {
+        let bytes_per_pixel = match self.bit_depth {
+            PNGBitDepth::Eight => 3,
+            PNGBitDepth::Sixteen => 6,
+        };
+        let mut scanlines = Vec::with_capacity(image.height() * (image.width() * bytes_per_pixel + 1));
+
+        for row in image.rows() {
+            scanlines.push(NO_FILTER);
+
+            for &color in row {
+                match self.bit_depth {
+                    PNGBitDepth::Eight => scanlines.extend_from_slice(&to_display_bytes(color)),
+                    PNGBitDepth::Sixteen => {
+                        for component in [color.red, color.green, color.blue] {
+                            // Use square root for gamma 2.0 correction
+                            let value = (clamp(component.sqrt(), 0.0, 1.0) * 65535.0).round() as u16;
+                            scanlines.extend_from_slice(&value.to_be_bytes());
+                        }
+                    }
+                }
+            }
+        }
+
+        scanlines
+    }
+}
+
+impl Default for PNGWriter {
+    fn default() -> Self {
+        PNGWriter::new(PNGBitDepth::Eight)
+    }
+}
+
+impl ImageEncoder for PNGWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        buffer.write_all(&PNG_SIGNATURE)?;
+
+        let bit_depth = match self.bit_depth {
+            PNGBitDepth::Eight => 8,
+            PNGBitDepth::Sixteen => 16,
+        };
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&(image.width() as u32).to_be_bytes());
+        header.extend_from_slice(&(image.height() as u32).to_be_bytes());
+        // Bit depth, color type, compression method, filter method, interlace method.
+        header.extend_from_slice(&[bit_depth, RGB_COLOR_TYPE, 0, 0, 0]);
+        write_chunk(buffer, b"IHDR", &header)?;
+
+        write_chunk(buffer, b"IDAT", &compress_stored(&self.get_scanlines(image)))?;
+        write_chunk(buffer, b"IEND", &[])
+    }
+}
+
+fn write_chunk(buffer: &mut dyn Write, chunk_type: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    buffer.write_all(&(data.len() as u32).to_be_bytes())?;
+    buffer.write_all(chunk_type)?;
+    buffer.write_all(data)?;
+
+    let crc = crc32(&[chunk_type, data]);
+    buffer.write_all(&crc.to_be_bytes())
+}
+
+/// The CRC-32 checksum PNG chunks end with, calculated over all of the given parts.
+pub fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+
+    for part in parts {
+        for &byte in part.iter() {
+            crc ^= byte as u32;
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+            }
+        }
+    }
+
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::zlib::decompress;
+    use crate::vectors::Color;
+
+    /// Split an encoded PNG into its chunks' types and data, checking every chunk's CRC.
+    fn read_chunks(png: &[u8]) -> Vec<([u8; 4], Vec<u8>)> {
+        assert_eq!(png[..8], PNG_SIGNATURE);
+
+        let mut chunks = Vec::new();
+        let mut position = 8;
+        while position < png.len() {
+            let length = u32::from_be_bytes([png[position], png[position + 1], png[position + 2], png[position + 3]]) as usize;
+            let chunk_type = [png[position + 4], png[position + 5], png[position + 6], png[position + 7]];
+            let data = &png[position + 8..position + 8 + length];
+            let crc = &png[position + 8 + length..position + 12 + length];
+            assert_eq!(crc, crc32(&[&chunk_type, data]).to_be_bytes());
+
+            chunks.push((chunk_type, data.to_vec()));
+            position += 12 + length;
+        }
+
+        chunks
+    }
+
+    fn encode(image: &Image, bit_depth: PNGBitDepth) -> Vec<u8> {
+        let mut png = Vec::new();
+        PNGWriter::new(bit_depth).encode(image, &mut png).unwrap();
+        png
+    }
+
+    #[test]
+    fn crc32_known_values() {
+        assert_eq!(crc32(&[]), 0);
+        assert_eq!(crc32(&[b"123456789"]), 0xCBF4_3926);
+        assert_eq!(crc32(&[b"1234", b"", b"56789"]), 0xCBF4_3926);
+        assert_eq!(crc32(&[b"IEND"]), 0xAE42_6082);
+    }
+
+    #[test]
+    fn header_describes_the_image() {
+        let png = encode(&Image::new(2, 1), PNGBitDepth::Eight);
+        let chunks = read_chunks(&png);
+
+        let types: Vec<&[u8; 4]> = chunks.iter().map(|(chunk_type, _)| chunk_type).collect();
+        assert_eq!(types, vec![b"IHDR", b"IDAT", b"IEND"]);
+        assert_eq!(chunks[0].1, vec![0, 0, 0, 2, 0, 0, 0, 1, 8, RGB_COLOR_TYPE, 0, 0, 0]);
+        assert!(chunks[2].1.is_empty());
+        assert_eq!(png[png.len() - 4..], [0xAE, 0x42, 0x60, 0x82]);
+    }
+
+    #[test]
+    fn scanlines_hold_gamma_corrected_pixels() {
+        let pixels = vec![Color::new(0.0, 0.25, 1.0), Color::new(4.0, -1.0, 0.25)];
+        let image = Image::from_pixels(1, 2, pixels);
+
+        let chunks = read_chunks(&encode(&image, PNGBitDepth::Eight));
+        assert_eq!(decompress(&chunks[1].1).unwrap(), vec![NO_FILTER, 0, 128, 255, NO_FILTER, 255, 0, 128]);
+
+        let chunks = read_chunks(&encode(&image, PNGBitDepth::Sixteen));
+        assert_eq!(chunks[0].1[8], 16);
+        assert_eq!(
+            decompress(&chunks[1].1).unwrap(),
+            vec![NO_FILTER, 0, 0, 0x80, 0x00, 0xFF, 0xFF, NO_FILTER, 0xFF, 0xFF, 0, 0, 0x80, 0x00]
+        );
+    }
+}
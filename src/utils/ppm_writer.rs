@@ -1,11 +1,18 @@
-use std::io::Write;
-use crate::vectors::Color;
-use std::fs::File;
-
-const WRITE_ERROR_MESSAGE: &str = "Could not write to buffer.";
-const CREATE_FILE_ERROR_MESSAGE: &str = "Failed to create file";
+use std::io::{self, Write};
+use crate::utils::Image;
+use crate::utils::image_encoder::{ImageEncoder, to_display_bytes};
+
+/// Determines how the pixel values of Netpbm formats (PPM, PGM) are written.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PNMEncoding {
+    /// Each value is written as an ASCII number. Readable, but large and slow to write.
+    Ascii,
+
+    /// Each value is written as a single raw byte.
+    Binary,
+}
 
-/// Used for writing data in Portable Pixel Map format.
+/// Used for writing images in Portable Pixel Map format.
 /// The output format is as follows:
 ///
 /// P3
@@ -18,72 +25,64 @@ const CREATE_FILE_ERROR_MESSAGE: &str = "Failed to create file";
 /// 3 2 => 3 columns and 2 rows.
 /// 255 => Maximum color value.
 /// ... => RGB triplets.
+///
+/// With the binary encoding the magic number is P6, and the RGB triplets are
+/// written as raw bytes right after the maximum color value.
 pub struct PPMWriter {
-    buffer: Box<dyn Write>,
-    is_size_written: bool,
+    encoding: PNMEncoding,
 }
 
 
 impl PPMWriter {
-    pub fn get_file_writer(file_path: &str) -> PPMWriter {
-        let file_writer = File::create(file_path).expect(CREATE_FILE_ERROR_MESSAGE);
-        PPMWriter::new(Box::new(file_writer))
+    pub fn new() -> PPMWriter {
+        PPMWriter::with_encoding(PNMEncoding::Ascii)
     }
 
-    pub fn new(mut buffer: Box<dyn Write>) -> PPMWriter {
-        // 'P3' means the colors are represented by ASCII numbers
-        buffer.write(b"P3\n").expect(WRITE_ERROR_MESSAGE);
-
-        PPMWriter {
-            buffer,
-            is_size_written: false,
-        }
+    pub fn binary() -> PPMWriter {
+        PPMWriter::with_encoding(PNMEncoding::Binary)
     }
 
-    pub fn write_size(&mut self, height: usize, width: usize) {
-        if self.is_size_written {
-            panic!("Size has already been written and should not be written more than once.")
-        }
-
-        write!(self.buffer, "{} {}\n", width, height).expect(WRITE_ERROR_MESSAGE);
-
-        // 255 represents the maximum color
-        write!(self.buffer, "255\n").expect(WRITE_ERROR_MESSAGE);
-        self.is_size_written = true;
+    pub fn with_encoding(encoding: PNMEncoding) -> PPMWriter {
+        PPMWriter { encoding }
     }
+}
 
-    pub fn clamp(&self, number: f64, min_value: f64, max_value: f64) -> f64 {
-        if number > max_value {
-            return max_value;
-        }
-        if number < min_value {
-            return min_value
-        }
-
-        number
+impl Default for PPMWriter {
+    fn default() -> Self {
+        PPMWriter::new()
     }
+}
 
-    /// Write a single pixel to the final image
-    pub fn write_color(&mut self, color: Color, samples_count: usize) {
-        if !self.is_size_written {
-            panic!("Size hasn't been written yet. Please call the 'write_size' method before \
-            calling 'write_color' method.");
+impl ImageEncoder for PPMWriter {
+    fn encode(&self, image: &Image, buffer: &mut dyn Write) -> io::Result<()> {
+        match self.encoding {
+            // 'P3' means the colors are represented by ASCII numbers
+            PNMEncoding::Ascii => writeln!(buffer, "P3")?,
+            // 'P6' means the colors are represented by raw bytes
+            PNMEncoding::Binary => writeln!(buffer, "P6")?,
         }
+        writeln!(buffer, "{} {}", image.width(), image.height())?;
 
-        let color = color / samples_count as f64;
-
-        // Use square root for gamma 2.0 correction
-        let clamped_red = self.clamp(color.red.sqrt(), 0.0, 0.999);
-        let clamped_green = self.clamp(color.green.sqrt(), 0.0, 0.999);
-        let clamped_blue = self.clamp(color.blue.sqrt(), 0.0, 0.999);
-
-        let formatted_color = format!(
-            "{} {} {}",
-            (clamped_red * 256.0) as usize,
-            (clamped_green * 256.0) as usize,
-            (clamped_blue * 256.0)  as usize
-        );
+        // 255 represents the maximum color
+        writeln!(buffer, "255")?;
+
+        match self.encoding {
+            PNMEncoding::Ascii => {
+                for row in image.rows() {
+                    for &color in row {
+                        let [red, green, blue] = to_display_bytes(color);
+                        writeln!(buffer, "{} {} {}", red, green, blue)?;
+                    }
+                }
+            }
+            PNMEncoding::Binary => {
+                for row in image.rows() {
+                    let bytes: Vec<u8> = row.iter().flat_map(|&color| to_display_bytes(color)).collect();
+                    buffer.write_all(&bytes)?;
+                }
+            }
+        }
 
-        write!(self.buffer, "{}\n", formatted_color).expect(WRITE_ERROR_MESSAGE);
+        Ok(())
     }
-}
\ No newline at end of file
+}
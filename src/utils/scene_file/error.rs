@@ -0,0 +1,36 @@
+use crate::utils::scene_file::lexer::Position;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// An error which occurred while loading a scene file.
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io { path: String, error: io::Error },
+    Parse { path: String, line: usize, column: usize, message: String },
+}
+
+
+impl SceneFileError {
+    pub fn parse(path: &str, position: Position, message: String) -> SceneFileError {
+        SceneFileError::Parse {
+            path: path.to_string(),
+            line: position.line,
+            column: position.column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneFileError::Io { path, error } => write!(f, "Failed to read {}: {}", path, error),
+            SceneFileError::Parse { path, line, column, message } => {
+                write!(f, "{}:{}:{}: {}", path, line, column, message)
+            }
+        }
+    }
+}
+
+impl Error for SceneFileError {}
@@ -0,0 +1,208 @@
+use crate::utils::scene_file::error::SceneFileError;
+
+/// A location in the scene file. Both the line and the column start at 1.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum TokenKind {
+    Identifier(String),
+    Number(f64),
+    String(String),
+    OpenBrace,
+    CloseBrace,
+    OpenBracket,
+    CloseBracket,
+    Equals,
+    Comma,
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub position: Position,
+}
+
+/// Splits the scene file into tokens, keeping the position of each one of them
+/// for error reporting. Comments start with '#' and last until the end of the line.
+pub struct Lexer<'s> {
+    source_name: &'s str,
+    characters: std::iter::Peekable<std::str::Chars<'s>>,
+    position: Position,
+}
+
+
+impl Position {
+    pub fn new(line: usize, column: usize) -> Position {
+        Position { line, column }
+    }
+}
+
+impl<'s> Lexer<'s> {
+    pub fn new(source: &'s str, source_name: &'s str) -> Lexer<'s> {
+        Lexer {
+            source_name,
+            characters: source.chars().peekable(),
+            position: Position::new(1, 1),
+        }
+    }
+
+    /// Returns the tokens, along with the position of the end of the file.
+    pub fn tokenize(mut self) -> Result<(Vec<Token>, Position), SceneFileError> {
+        let mut tokens = Vec::new();
+
+        while let Some(&character) = self.characters.peek() {
+            let position = self.position;
+
+            let kind = match character {
+                _ if character.is_whitespace() => {
+                    self.advance();
+                    continue;
+                }
+                '#' => {
+                    while self.characters.peek().is_some_and(|&c| c != '\n') {
+                        self.advance();
+                    }
+                    continue;
+                }
+                '{' => self.single(TokenKind::OpenBrace),
+                '}' => self.single(TokenKind::CloseBrace),
+                '[' => self.single(TokenKind::OpenBracket),
+                ']' => self.single(TokenKind::CloseBracket),
+                '=' => self.single(TokenKind::Equals),
+                ',' => self.single(TokenKind::Comma),
+                '"' => self.read_string(position)?,
+                _ if character.is_ascii_digit() || character == '-' || character == '+' || character == '.' => {
+                    self.read_number(position)?
+                }
+                _ if character.is_alphabetic() || character == '_' => self.read_identifier(),
+                _ => return Err(self.error(position, format!("Unexpected character '{}'", character))),
+            };
+
+            tokens.push(Token { kind, position });
+        }
+
+        Ok((tokens, self.position))
+    }
+
+    fn advance(&mut self) -> Option<char> {
+        let character = self.characters.next()?;
+        if character == '\n' {
+            self.position.line += 1;
+            self.position.column = 1;
+        } else {
+            self.position.column += 1;
+        }
+
+        Some(character)
+    }
+
+    fn single(&mut self, kind: TokenKind) -> TokenKind {
+        self.advance();
+        kind
+    }
+
+    fn read_while<F: Fn(char) -> bool>(&mut self, predicate: F) -> String {
+        let mut result = String::new();
+        while let Some(&character) = self.characters.peek() {
+            if !predicate(character) {
+                break;
+            }
+            result.push(character);
+            self.advance();
+        }
+
+        result
+    }
+
+    fn read_identifier(&mut self) -> TokenKind {
+        TokenKind::Identifier(self.read_while(|c| c.is_alphanumeric() || c == '_'))
+    }
+
+    fn read_number(&mut self, position: Position) -> Result<TokenKind, SceneFileError> {
+        let text = self.read_while(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+');
+        match text.parse() {
+            Ok(number) => Ok(TokenKind::Number(number)),
+            Err(_) => Err(self.error(position, format!("Invalid number '{}'", text))),
+        }
+    }
+
+    fn read_string(&mut self, position: Position) -> Result<TokenKind, SceneFileError> {
+        // Skip the opening quote
+        self.advance();
+        let text = self.read_while(|c| c != '"' && c != '\n');
+
+        match self.advance() {
+            Some('"') => Ok(TokenKind::String(text)),
+            _ => Err(self.error(position, "Unterminated string".to_string())),
+        }
+    }
+
+    fn error(&self, position: Position, message: String) -> SceneFileError {
+        SceneFileError::parse(self.source_name, position, message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokenize(source: &str) -> Vec<(TokenKind, usize, usize)> {
+        let (tokens, _) = Lexer::new(source, "test.scene").tokenize().unwrap();
+        tokens.into_iter().map(|token| (token.kind, token.position.line, token.position.column)).collect()
+    }
+
+    fn error_position(source: &str) -> (usize, usize) {
+        match Lexer::new(source, "test.scene").tokenize() {
+            Err(SceneFileError::Parse { line, column, .. }) => (line, column),
+            other => panic!("Expected a parse error, got {:?}", other.map(|(tokens, _)| tokens)),
+        }
+    }
+
+    #[test]
+    fn tokens_keep_their_positions() {
+        let identifier = |name: &str| TokenKind::Identifier(name.to_string());
+
+        assert_eq!(
+            tokenize("render {\n  width = 400, # comment = 1\n\tpath = \"a b\" }"),
+            vec![
+                (identifier("render"), 1, 1),
+                (TokenKind::OpenBrace, 1, 8),
+                (identifier("width"), 2, 3),
+                (TokenKind::Equals, 2, 9),
+                (TokenKind::Number(400.0), 2, 11),
+                (TokenKind::Comma, 2, 14),
+                (identifier("path"), 3, 2),
+                (TokenKind::Equals, 3, 7),
+                (TokenKind::String("a b".to_string()), 3, 9),
+                (TokenKind::CloseBrace, 3, 15),
+            ]
+        );
+    }
+
+    #[test]
+    fn numbers() {
+        let numbers: Vec<TokenKind> = tokenize("[-1.5e3, +2, .5, 7]").into_iter()
+            .map(|(kind, _, _)| kind)
+            .filter(|kind| matches!(kind, TokenKind::Number(_)))
+            .collect();
+        assert_eq!(numbers, vec![TokenKind::Number(-1500.0), TokenKind::Number(2.0), TokenKind::Number(0.5), TokenKind::Number(7.0)]);
+    }
+
+    #[test]
+    fn end_position_is_after_the_last_character() {
+        let (_, end) = Lexer::new("a {\n}  ", "test.scene").tokenize().unwrap();
+        assert_eq!(end, Position::new(2, 4));
+    }
+
+    #[test]
+    fn errors_point_at_the_token() {
+        assert_eq!(error_position("render {\n  @"), (2, 3));
+        assert_eq!(error_position("a = 1.2.3"), (1, 5));
+        assert_eq!(error_position("a = 12x"), (1, 5));
+        assert_eq!(error_position("a {\n path = \"file.obj\n}"), (2, 9));
+    }
+}
@@ -0,0 +1,934 @@
+use crate::consts::{ADAPTIVE_MAX_SAMPLES_FACTOR, ASPECT_RATIO, MIN_IMAGE_SIZE};
+use crate::engine::camera::Camera;
+use crate::engine::hittables::{BoxShape, ConstantMedium, Hittable, HittableCollection, MovingSphere, Quad, Sphere, Transformed, Triangle, TriangleMesh};
+use crate::engine::materials::{Dielectric, DiffuseLight, Isotropic, Lambertian, Material, Metal};
+use crate::engine::integrators::IntegratorKind;
+use crate::engine::samplers::SamplerKind;
+use crate::engine::filters::FilterKind;
+use crate::engine::textures::{CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture};
+use crate::engine::{AdaptiveSampling, Background, RenderSettings, Rng};
+use crate::utils::obj_loader::load_obj;
+use crate::utils::scene_file::error::SceneFileError;
+use crate::utils::scene_file::lexer::{Lexer, Position};
+use crate::utils::scene_file::parser::{Item, Object, Parser, Value, ValueKind};
+use crate::vectors::{Color, Point, Transform, Vector};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Everything a scene file describes.
+pub struct SceneDescription<'a> {
+    pub world: HittableCollection<'a>,
+
+    /// Spheres, triangles, quads and boxes with an emissive material, which are sampled as lights.
+    /// Emissive meshes and moving spheres still emit light, but aren't sampled directly.
+    pub lights: HittableCollection<'a>,
+    pub camera: Camera,
+    pub settings: RenderSettings,
+}
+
+/// Builds the scene out of the parsed items.
+struct SceneLoader<'s> {
+    source_name: &'s str,
+    base_directory: Option<&'s Path>,
+    materials: HashMap<String, Arc<dyn Material>>,
+
+    /// Seeds the procedural textures which don't have a seed of their own, so they
+    /// change along with the render seed. Materials are loaded with a shared reference
+    /// to the loader, hence the cell.
+    rng: RefCell<Rng>,
+}
+
+/// Gives typed access to the properties of a block, and keeps track of which
+/// of them were used, so unknown (for example misspelled) properties are reported.
+struct PropertyReader<'r> {
+    source_name: &'r str,
+    object: &'r Object,
+    used: Vec<bool>,
+}
+
+
+/// Load a scene file. Relative paths in the file (such as meshes) are
+/// looked up relative to the directory of the scene file.
+pub fn load_scene_file<'a>(file_path: &str) -> Result<SceneDescription<'a>, SceneFileError> {
+    load_scene_file_with(file_path, |_| {})
+}
+
+/// Load a scene file, and adjust its render settings before the camera is built.
+/// Useful for overriding the image size, which the camera's aspect ratio depends on.
+pub fn load_scene_file_with<'a, F: FnOnce(&mut RenderSettings)>(file_path: &str, adjust_settings: F) -> Result<SceneDescription<'a>, SceneFileError> {
+    let source = fs::read_to_string(file_path)
+        .map_err(|error| SceneFileError::Io { path: file_path.to_string(), error })?;
+
+    parse_scene_file_with(&source, file_path, Path::new(file_path).parent(), adjust_settings)
+}
+
+pub fn parse_scene_file<'a>(source: &str, source_name: &str, base_directory: Option<&Path>) -> Result<SceneDescription<'a>, SceneFileError> {
+    parse_scene_file_with(source, source_name, base_directory, |_| {})
+}
+
+pub fn parse_scene_file_with<'a, F: FnOnce(&mut RenderSettings)>(
+    source: &str,
+    source_name: &str,
+    base_directory: Option<&Path>,
+    adjust_settings: F,
+) -> Result<SceneDescription<'a>, SceneFileError> {
+    let (tokens, end_position) = Lexer::new(source, source_name).tokenize()?;
+    let items = Parser::new(tokens, source_name, end_position).parse()?;
+
+    let mut loader = SceneLoader {
+        source_name,
+        base_directory,
+        materials: HashMap::new(),
+        rng: RefCell::new(Rng::new(0)),
+    };
+    loader.load(&items, end_position, adjust_settings)
+}
+
+impl<'s> SceneLoader<'s> {
+    /// Render settings, materials and the camera are loaded before the objects,
+    /// so the order of the items in the file doesn't matter.
+    fn load<'a, F: FnOnce(&mut RenderSettings)>(&mut self, items: &[Item], end_position: Position, adjust_settings: F) -> Result<SceneDescription<'a>, SceneFileError> {
+        let mut settings = match self.find_single(items, "render")? {
+            Some(object) => self.load_render_settings(object)?,
+            None => RenderSettings::default(),
+        };
+        adjust_settings(&mut settings);
+        *self.rng.get_mut() = Rng::new(settings.seed.unwrap_or(0));
+
+        for item in items.iter().filter(|item| item.object.type_name == "material") {
+            let name = item.name.as_ref()
+                .ok_or_else(|| self.error(item.object.position, "A material must have a name, as in 'material glass { ... }'"))?;
+            if self.materials.contains_key(name) {
+                return Err(self.error(item.object.position, &format!("Material '{}' is already defined", name)));
+            }
+
+            let mut reader = self.reader(&item.object);
+            let type_name = reader.identifier("type")?;
+            let material = self.load_material(&type_name, &mut reader)?;
+            self.materials.insert(name.clone(), material);
+        }
+
+        let camera = match self.find_single(items, "camera")? {
+            Some(object) => self.load_camera(object, &settings)?,
+            None => return Err(self.error(end_position, "The scene must have a camera")),
+        };
+
+        let mut world = HittableCollection::new();
+        let mut lights = HittableCollection::new();
+        // Meshes loaded more than once are shared, so instancing them doesn't copy their triangles
+        let mut meshes: HashMap<String, Arc<TriangleMesh>> = HashMap::new();
+        for item in items {
+            let object = &item.object;
+            let mut reader = self.reader(object);
+            match object.type_name.as_str() {
+                "material" | "render" | "camera" => continue,
+                "sphere" => {
+                    let center = reader.vector("center")?;
+                    let radius = reader.number("radius")?;
+                    let material = self.material_property(&mut reader)?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let is_emissive = material.is_emissive();
+                    place(Arc::new(Sphere::new(center, radius, material)), transform, is_emissive, &mut world, &mut lights);
+                }
+                "moving_sphere" => {
+                    let center0 = reader.vector("center0")?;
+                    let center1 = reader.vector("center1")?;
+                    let time0 = reader.optional_number("time0")?.unwrap_or(0.0);
+                    let time1 = reader.optional_number("time1")?.unwrap_or(1.0);
+                    let radius = reader.number("radius")?;
+                    let material = self.material_property(&mut reader)?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let sphere = MovingSphere::new(center0, center1, time0, time1, radius, material);
+                    place(Arc::new(sphere), transform, false, &mut world, &mut lights);
+                }
+                "triangle" => {
+                    let [v0, v1, v2] = reader.vectors("vertices")?;
+                    let normals = reader.optional_vectors("normals")?;
+                    let material = self.material_property(&mut reader)?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let is_emissive = material.is_emissive();
+                    let triangle = match normals {
+                        Some(normals) => Triangle::with_normals(v0, v1, v2, normals, material),
+                        None => Triangle::new(v0, v1, v2, material),
+                    };
+                    place(Arc::new(triangle), transform, is_emissive, &mut world, &mut lights);
+                }
+                "quad" => {
+                    let corner = reader.vector("corner")?;
+                    let u = reader.vector("u")?;
+                    let v = reader.vector("v")?;
+                    let material = self.material_property(&mut reader)?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let is_emissive = material.is_emissive();
+                    place(Arc::new(Quad::new(corner, u, v, material)), transform, is_emissive, &mut world, &mut lights);
+                }
+                "box" => {
+                    let minimum = reader.vector("min")?;
+                    let maximum = reader.vector("max")?;
+                    let material = self.material_property(&mut reader)?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let is_emissive = material.is_emissive();
+                    place(Arc::new(BoxShape::new(minimum, maximum, material)), transform, is_emissive, &mut world, &mut lights);
+                }
+                "medium" => {
+                    let boundary = reader.required("boundary")?;
+                    let density = reader.number("density")?;
+                    let albedo = self.texture_property(&mut reader, "albedo")?;
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    if !(density > 0.0 && density.is_finite()) {
+                        return Err(self.error(object.position, "The density of a medium must be positive and finite"));
+                    }
+                    let phase_function: Arc<dyn Material> = Arc::new(Isotropic::with_texture(albedo));
+                    match self.to_boundary(boundary, &phase_function)? {
+                        Boundary::Sphere(sphere) => place_medium(sphere, transform, density, phase_function, &mut world),
+                        Boundary::Box(box_shape) => place_medium(box_shape, transform, density, phase_function, &mut world),
+                    }
+                }
+                "mesh" => {
+                    let path = self.resolve_path(reader.string("path")?);
+                    let transform = self.transform_properties(&mut reader)?;
+                    reader.finish()?;
+
+                    let mesh = match meshes.get(&path) {
+                        Some(mesh) => Arc::clone(mesh),
+                        None => {
+                            let mesh = load_obj(&path)
+                                .map_err(|error| self.error(object.position, &format!("Failed to load mesh: {}", error)))?;
+                            let mesh = Arc::new(mesh);
+                            meshes.insert(path, Arc::clone(&mesh));
+                            mesh
+                        }
+                    };
+
+                    place(mesh, transform, false, &mut world, &mut lights);
+                }
+                other => return Err(self.error(object.position, &format!("Unknown item '{}'", other))),
+            }
+        }
+
+        Ok(SceneDescription { world, lights, camera, settings })
+    }
+
+    fn find_single<'i>(&self, items: &'i [Item], type_name: &str) -> Result<Option<&'i Object>, SceneFileError> {
+        let mut matching = items.iter().filter(|item| item.object.type_name == type_name);
+        let first = matching.next();
+
+        if let Some(duplicate) = matching.next() {
+            return Err(self.error(duplicate.object.position, &format!("The scene must have a single '{}'", type_name)));
+        }
+
+        Ok(first.map(|item| &item.object))
+    }
+
+    fn load_render_settings(&self, object: &Object) -> Result<RenderSettings, SceneFileError> {
+        let mut reader = self.reader(object);
+        let defaults = RenderSettings::default();
+
+        let width = reader.optional_count_at_least("width", MIN_IMAGE_SIZE)?.unwrap_or(defaults.width);
+        let height = match reader.optional_count_at_least("height", MIN_IMAGE_SIZE)? {
+            Some(height) => height,
+            None => ((width as f64 / ASPECT_RATIO) as usize).max(MIN_IMAGE_SIZE),
+        };
+
+        let samples_per_pixel = reader.optional_count_at_least("samples_per_pixel", 1)?.unwrap_or(defaults.samples_per_pixel);
+        let max_samples = reader.optional_count_at_least("max_samples", 1)?;
+        let adaptive = match reader.optional_number("noise_threshold")? {
+            Some(noise_threshold) if noise_threshold <= 0.0 => {
+                return Err(self.error(object.position, "The noise threshold must be positive"));
+            }
+            Some(noise_threshold) => Some(AdaptiveSampling {
+                noise_threshold,
+                max_samples: max_samples.unwrap_or(samples_per_pixel * ADAPTIVE_MAX_SAMPLES_FACTOR),
+            }),
+            None if max_samples.is_some() => {
+                return Err(self.error(object.position, "'max_samples' requires a 'noise_threshold'"));
+            }
+            None => defaults.adaptive,
+        };
+
+        let settings = RenderSettings {
+            width,
+            height,
+            samples_per_pixel,
+            max_depth: reader.optional_count_at_least("max_depth", 1)?.unwrap_or(defaults.max_depth),
+            background: match reader.take("background") {
+                Some(value) => self.to_background(value)?,
+                None => defaults.background,
+            },
+            integrator: match reader.take("integrator") {
+                Some(value) => self.to_integrator(value)?,
+                None => defaults.integrator,
+            },
+            sampler: match reader.take("sampler") {
+                Some(value) => self.to_sampler(value)?,
+                None => defaults.sampler,
+            },
+            filter: match reader.take("filter") {
+                Some(value) => self.to_filter(value)?,
+                None => defaults.filter,
+            },
+            threads_count: reader.optional_count("threads_count")?.unwrap_or(defaults.threads_count),
+            seed: reader.optional_count("seed")?.map(|seed| seed as u64),
+            adaptive,
+        };
+
+        reader.finish()?;
+        Ok(settings)
+    }
+
+    /// The aspect ratio defaults to the one of the rendered image, and the focus
+    /// distance defaults to the distance between look_from and look_at.
+    /// Without shutter times, the shutter is open only at time 0.
+    fn load_camera(&self, object: &Object, settings: &RenderSettings) -> Result<Camera, SceneFileError> {
+        let mut reader = self.reader(object);
+
+        let look_from = reader.vector("look_from")?;
+        let look_at = reader.vector("look_at")?;
+        let vup = reader.optional_vector("vup")?.unwrap_or_else(|| Vector::new(0.0, 1.0, 0.0));
+        let vertical_fov = reader.number("vertical_fov")?;
+        let aspect_ratio = reader.optional_number("aspect_ratio")?
+            .unwrap_or(settings.width as f64 / settings.height as f64);
+        let aperture = reader.optional_number("aperture")?.unwrap_or(0.0);
+        let focus_distance = reader.optional_number("focus_distance")?
+            .unwrap_or_else(|| (look_from - look_at).size());
+        let shutter_open = reader.optional_number("shutter_open")?.unwrap_or(0.0);
+        let shutter_close = reader.optional_number("shutter_close")?.unwrap_or(shutter_open);
+
+        reader.finish()?;
+        Ok(Camera::new(look_from, look_at, vup, vertical_fov, aspect_ratio, aperture, focus_distance)
+            .with_shutter(shutter_open, shutter_close))
+    }
+
+    fn load_material(&self, type_name: &str, reader: &mut PropertyReader) -> Result<Arc<dyn Material>, SceneFileError> {
+        let material: Arc<dyn Material> = match type_name {
+            "lambertian" => Arc::new(Lambertian::with_texture(self.texture_property(reader, "albedo")?)),
+            "metal" => {
+                let albedo = self.texture_property(reader, "albedo")?;
+                let fuzz = reader.optional_number("fuzz")?.unwrap_or(0.0);
+                Arc::new(Metal::with_texture(albedo, fuzz))
+            }
+            "dielectric" => Arc::new(Dielectric::new(reader.number("refraction_index")?)),
+            "diffuse_light" => {
+                let emit = reader.required("emit")?;
+                match reader.optional_number("intensity")? {
+                    Some(intensity) => {
+                        let color = to_color(self.source_name, emit)
+                            .map_err(|_| self.error(emit.position, "Only a color can be scaled by an intensity"))?;
+                        Arc::new(DiffuseLight::new(intensity * color))
+                    }
+                    None => Arc::new(DiffuseLight::with_texture(self.to_texture(emit)?)),
+                }
+            }
+            other => return Err(self.error(reader.object.position, &format!("Unknown material type '{}'", other))),
+        };
+
+        reader.finish()?;
+        Ok(material)
+    }
+
+    /// A material is either the name of a material item, or an inline material block.
+    fn material_property(&self, reader: &mut PropertyReader) -> Result<Arc<dyn Material>, SceneFileError> {
+        let value = reader.required("material")?;
+
+        match &value.kind {
+            ValueKind::Identifier(name) => self.materials.get(name)
+                .cloned()
+                .ok_or_else(|| self.error(value.position, &format!("Unknown material '{}'", name))),
+            ValueKind::Object(object) => {
+                let mut material_reader = self.reader(object);
+                self.load_material(&object.type_name, &mut material_reader)
+            }
+            other => Err(self.error(value.position, &format!("Expected a material, found {}", other.describe()))),
+        }
+    }
+
+    /// Objects are placed with the optional 'scale', 'rotate' and 'translate' properties, which are
+    /// applied in that order. The scale is either a single factor or a factor per axis, and the
+    /// rotation is given in degrees around the X, Y and Z axes, which are applied in that order too.
+    fn transform_properties(&self, reader: &mut PropertyReader) -> Result<Option<Transform>, SceneFileError> {
+        let scale = reader.take("scale");
+        let rotation = reader.optional_vector("rotate")?;
+        let offset = reader.optional_vector("translate")?;
+        if scale.is_none() && rotation.is_none() && offset.is_none() {
+            return Ok(None);
+        }
+
+        let mut transform = Transform::identity();
+        if let Some(scale) = scale {
+            let factors = match &scale.kind {
+                ValueKind::Number(factor) => Vector::new(*factor, *factor, *factor),
+                _ => to_vector(self.source_name, scale)?,
+            };
+            if factors.x == 0.0 || factors.y == 0.0 || factors.z == 0.0 {
+                return Err(self.error(scale.position, "Scale factors can't be 0"));
+            }
+            transform = transform.scale(factors);
+        }
+        if let Some(rotation) = rotation {
+            transform = transform.rotate_x(rotation.x).rotate_y(rotation.y).rotate_z(rotation.z);
+        }
+        if let Some(offset) = offset {
+            transform = transform.translate(offset);
+        }
+
+        Ok(Some(transform))
+    }
+
+    /// The boundary of a medium is a 'sphere { center, radius }' or a 'box { min, max }' block.
+    /// Boundaries have no material of their own, so they're given the medium's phase function.
+    fn to_boundary(&self, value: &Value, phase_function: &Arc<dyn Material>) -> Result<Boundary, SceneFileError> {
+        let object = match &value.kind {
+            ValueKind::Object(object) => object,
+            _ => return Err(self.error(value.position, "Expected a 'sphere { ... }' or 'box { ... }' boundary")),
+        };
+
+        let mut reader = self.reader(object);
+        let boundary = match object.type_name.as_str() {
+            "sphere" => {
+                let center = reader.vector("center")?;
+                let radius = reader.number("radius")?;
+                Boundary::Sphere(Sphere::new(center, radius, Arc::clone(phase_function)))
+            }
+            "box" => {
+                let minimum = reader.vector("min")?;
+                let maximum = reader.vector("max")?;
+                Boundary::Box(BoxShape::new(minimum, maximum, Arc::clone(phase_function)))
+            }
+            other => return Err(self.error(object.position, &format!("Unknown boundary type '{}'", other))),
+        };
+
+        reader.finish()?;
+        Ok(boundary)
+    }
+
+    fn texture_property(&self, reader: &mut PropertyReader, key: &str) -> Result<Arc<dyn Texture>, SceneFileError> {
+        self.to_texture(reader.required(key)?)
+    }
+
+    /// A texture is either a color, or a typed texture block. Checker textures are made of two textures.
+    fn to_texture(&self, value: &Value) -> Result<Arc<dyn Texture>, SceneFileError> {
+        let object = match &value.kind {
+            ValueKind::Array(_) => return Ok(Arc::new(SolidColor::new(to_color(self.source_name, value)?))),
+            ValueKind::Object(object) => object,
+            other => return Err(self.error(value.position, &format!("Expected a color or a texture, found {}", other.describe()))),
+        };
+
+        let mut reader = self.reader(object);
+        let texture: Arc<dyn Texture> = match object.type_name.as_str() {
+            "checker" => {
+                let scale = reader.optional_number("scale")?.unwrap_or(1.0);
+                let even = self.texture_property(&mut reader, "even")?;
+                let odd = self.texture_property(&mut reader, "odd")?;
+                Arc::new(CheckerTexture::new(scale, even, odd))
+            }
+            "image" => {
+                let path = self.resolve_path(reader.string("path")?);
+                let texture = ImageTexture::load(&path)
+                    .map_err(|error| self.error(object.position, &format!("Failed to load image '{}': {}", path, error)))?;
+                Arc::new(texture)
+            }
+            "noise" | "turbulence" => {
+                let scale = reader.optional_number("scale")?.unwrap_or(1.0);
+                let mut rng = self.texture_rng(&mut reader)?;
+                if object.type_name == "noise" {
+                    Arc::new(NoiseTexture::new(&mut rng, scale))
+                } else {
+                    Arc::new(NoiseTexture::turbulent(&mut rng, scale))
+                }
+            }
+            "marble" => {
+                let scale = reader.optional_number("scale")?.unwrap_or(1.0);
+                let mut marble = MarbleTexture::new(&mut self.texture_rng(&mut reader)?, scale);
+
+                let base = reader.optional_color("base")?;
+                let veins = reader.optional_color("veins")?;
+                if base.is_some() || veins.is_some() {
+                    let base = base.unwrap_or(Color::new(1.0, 1.0, 1.0));
+                    let veins = veins.unwrap_or(Color::zeroes());
+                    marble = marble.with_colors(base, veins);
+                }
+
+                let turbulence = reader.optional_number("turbulence")?;
+                let octaves = reader.optional_count("octaves")?;
+                if turbulence.is_some() || octaves.is_some() {
+                    marble = marble.with_turbulence(turbulence.unwrap_or(10.0), octaves.unwrap_or(7));
+                }
+
+                Arc::new(marble)
+            }
+            other => return Err(self.error(object.position, &format!("Unknown texture type '{}'", other))),
+        };
+
+        reader.finish()?;
+        Ok(texture)
+    }
+
+    /// Procedural textures with an explicit seed always look the same, while the
+    /// others are seeded by the scene's generator.
+    fn texture_rng(&self, reader: &mut PropertyReader) -> Result<Rng, SceneFileError> {
+        match reader.optional_count("seed")? {
+            Some(seed) => Ok(Rng::new(seed as u64)),
+            None => Ok(Rng::new(self.rng.borrow_mut().next_u64())),
+        }
+    }
+
+    /// Relative paths are relative to the directory of the scene file.
+    fn resolve_path(&self, path: String) -> String {
+        match self.base_directory {
+            Some(base_directory) => base_directory.join(&path).to_string_lossy().to_string(),
+            None => path,
+        }
+    }
+
+    fn to_background(&self, value: &Value) -> Result<Background, SceneFileError> {
+        match &value.kind {
+            ValueKind::Identifier(name) if name == "sky" => Ok(Background::sky()),
+            ValueKind::Identifier(name) if name == "black" => Ok(Background::black()),
+            ValueKind::Array(_) => Ok(Background::Solid(to_color(self.source_name, value)?)),
+            ValueKind::Object(object) if object.type_name == "gradient" => {
+                let mut reader = self.reader(object);
+                let bottom = reader.color("bottom")?;
+                let top = reader.color("top")?;
+                reader.finish()?;
+
+                Ok(Background::Gradient { bottom, top })
+            }
+            _ => Err(self.error(value.position, "Expected 'sky', 'black', a color or a 'gradient { ... }' block")),
+        }
+    }
+
+    fn to_integrator(&self, value: &Value) -> Result<IntegratorKind, SceneFileError> {
+        match &value.kind {
+            ValueKind::Identifier(name) => match name.as_str() {
+                "path_tracer" => Ok(IntegratorKind::PathTracer),
+                "naive_path_tracer" => Ok(IntegratorKind::NaivePathTracer),
+                "direct_lighting" => Ok(IntegratorKind::DirectLighting),
+                "ambient_occlusion" => Ok(IntegratorKind::AmbientOcclusion { distance: f64::INFINITY }),
+                other => Err(self.error(value.position, &format!("Unknown integrator '{}'", other))),
+            },
+            ValueKind::Object(object) if object.type_name == "ambient_occlusion" => {
+                let mut reader = self.reader(object);
+                let distance = reader.number("distance")?;
+                reader.finish()?;
+
+                Ok(IntegratorKind::AmbientOcclusion { distance })
+            }
+            _ => Err(self.error(value.position, "Expected an integrator name or an 'ambient_occlusion { ... }' block")),
+        }
+    }
+
+    fn to_sampler(&self, value: &Value) -> Result<SamplerKind, SceneFileError> {
+        match &value.kind {
+            ValueKind::Identifier(name) => match name.as_str() {
+                "independent" => Ok(SamplerKind::Independent),
+                "stratified" => Ok(SamplerKind::Stratified),
+                "halton" => Ok(SamplerKind::Halton),
+                "sobol" => Ok(SamplerKind::Sobol),
+                "blue_noise" => Ok(SamplerKind::BlueNoise),
+                other => Err(self.error(value.position, &format!("Unknown sampler '{}'", other))),
+            },
+            other => Err(self.error(value.position, &format!("Expected a sampler name, found {}", other.describe()))),
+        }
+    }
+
+    /// A filter is either a name, or a block which overrides some of its parameters,
+    /// such as 'mitchell { radius = 1.5, b = 0, c = 0.5 }'.
+    fn to_filter(&self, value: &Value) -> Result<FilterKind, SceneFileError> {
+        let unknown_filter = |name: &str| self.error(value.position, &format!("Unknown filter '{}'", name));
+        let object = match &value.kind {
+            ValueKind::Identifier(name) => return FilterKind::from_name(name).ok_or_else(|| unknown_filter(name)),
+            ValueKind::Object(object) => object,
+            other => return Err(self.error(value.position, &format!("Expected a filter, found {}", other.describe()))),
+        };
+
+        let mut filter = FilterKind::from_name(&object.type_name).ok_or_else(|| unknown_filter(&object.type_name))?;
+        let mut reader = self.reader(object);
+        match &mut filter {
+            FilterKind::Box { radius } | FilterKind::Tent { radius } | FilterKind::Lanczos { radius } => {
+                *radius = reader.optional_number("radius")?.unwrap_or(*radius);
+            }
+            FilterKind::Gaussian { radius, sigma } => {
+                *radius = reader.optional_number("radius")?.unwrap_or(*radius);
+                *sigma = reader.optional_number("sigma")?.unwrap_or(*sigma);
+                if *sigma <= 0.0 {
+                    return Err(self.error(object.position, "The sigma of a Gaussian filter must be positive"));
+                }
+            }
+            FilterKind::Mitchell { radius, b, c } => {
+                *radius = reader.optional_number("radius")?.unwrap_or(*radius);
+                *b = reader.optional_number("b")?.unwrap_or(*b);
+                *c = reader.optional_number("c")?.unwrap_or(*c);
+            }
+        }
+        reader.finish()?;
+
+        if filter.radius() <= 0.0 {
+            return Err(self.error(object.position, "The radius of a filter must be positive"));
+        }
+
+        Ok(filter)
+    }
+
+    fn reader<'r>(&'r self, object: &'r Object) -> PropertyReader<'r> {
+        PropertyReader {
+            source_name: self.source_name,
+            object,
+            used: vec![false; object.properties.len()],
+        }
+    }
+
+    fn error(&self, position: Position, message: &str) -> SceneFileError {
+        SceneFileError::parse(self.source_name, position, message.to_string())
+    }
+}
+
+impl<'r> PropertyReader<'r> {
+    /// Get the value of a property and mark it as used.
+    fn take(&mut self, key: &str) -> Option<&'r Value> {
+        let index = self.object.properties.iter().rposition(|property| property.key == key)?;
+        self.used[index] = true;
+
+        Some(&self.object.properties[index].value)
+    }
+
+    fn required(&mut self, key: &str) -> Result<&'r Value, SceneFileError> {
+        match self.take(key) {
+            Some(value) => Ok(value),
+            None => Err(SceneFileError::parse(
+                self.source_name,
+                self.object.position,
+                format!("'{}' is missing the '{}' property", self.object.type_name, key),
+            )),
+        }
+    }
+
+    fn number(&mut self, key: &str) -> Result<f64, SceneFileError> {
+        to_number(self.source_name, self.required(key)?)
+    }
+
+    fn optional_number(&mut self, key: &str) -> Result<Option<f64>, SceneFileError> {
+        self.take(key).map(|value| to_number(self.source_name, value)).transpose()
+    }
+
+    /// A whole, non-negative number.
+    fn optional_count(&mut self, key: &str) -> Result<Option<usize>, SceneFileError> {
+        self.take(key).map(|value| to_count(self.source_name, value)).transpose()
+    }
+
+    fn optional_count_at_least(&mut self, key: &str, minimum: usize) -> Result<Option<usize>, SceneFileError> {
+        let value = match self.take(key) {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+
+        let count = to_count(self.source_name, value)?;
+        if count < minimum {
+            return Err(SceneFileError::parse(
+                self.source_name,
+                value.position,
+                format!("'{}' must be at least {}, got {}", key, minimum, count),
+            ));
+        }
+
+        Ok(Some(count))
+    }
+
+    fn string(&mut self, key: &str) -> Result<String, SceneFileError> {
+        let value = self.required(key)?;
+        match &value.kind {
+            ValueKind::String(text) => Ok(text.clone()),
+            other => Err(type_error(self.source_name, value, "a string", other)),
+        }
+    }
+
+    fn identifier(&mut self, key: &str) -> Result<String, SceneFileError> {
+        let value = self.required(key)?;
+        match &value.kind {
+            ValueKind::Identifier(name) => Ok(name.clone()),
+            other => Err(type_error(self.source_name, value, "a name", other)),
+        }
+    }
+
+    fn vector(&mut self, key: &str) -> Result<Vector, SceneFileError> {
+        to_vector(self.source_name, self.required(key)?)
+    }
+
+    fn optional_vector(&mut self, key: &str) -> Result<Option<Vector>, SceneFileError> {
+        self.take(key).map(|value| to_vector(self.source_name, value)).transpose()
+    }
+
+    fn color(&mut self, key: &str) -> Result<Color, SceneFileError> {
+        to_color(self.source_name, self.required(key)?)
+    }
+
+    fn optional_color(&mut self, key: &str) -> Result<Option<Color>, SceneFileError> {
+        self.take(key).map(|value| to_color(self.source_name, value)).transpose()
+    }
+
+    /// A list of exactly 3 vectors, such as the vertices of a triangle.
+    fn vectors(&mut self, key: &str) -> Result<[Point; 3], SceneFileError> {
+        to_vectors(self.source_name, self.required(key)?)
+    }
+
+    fn optional_vectors(&mut self, key: &str) -> Result<Option<[Vector; 3]>, SceneFileError> {
+        self.take(key).map(|value| to_vectors(self.source_name, value)).transpose()
+    }
+
+    /// Make sure every property of the block was used.
+    fn finish(&self) -> Result<(), SceneFileError> {
+        for (property, &used) in self.object.properties.iter().zip(&self.used) {
+            if !used {
+                return Err(SceneFileError::parse(
+                    self.source_name,
+                    property.key_position,
+                    format!("Unknown property '{}' for '{}'", property.key, self.object.type_name),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The shapes which can bound a medium. They must be convex.
+enum Boundary {
+    Sphere(Sphere),
+    Box(BoxShape),
+}
+
+/// Add an object to the world, optionally transformed. Emissive objects are added to the lights as well.
+fn place<'a, H: Hittable<'a> + 'static>(
+    object: Arc<H>,
+    transform: Option<Transform>,
+    is_emissive: bool,
+    world: &mut HittableCollection<'a>,
+    lights: &mut HittableCollection<'a>,
+) {
+    match transform {
+        Some(transform) => {
+            if is_emissive {
+                lights.add(Box::new(Transformed::new(Arc::clone(&object), transform)));
+            }
+            world.add(Box::new(Transformed::new(object, transform)));
+        }
+        None => {
+            if is_emissive {
+                lights.add(Box::new(Arc::clone(&object)));
+            }
+            world.add(Box::new(object));
+        }
+    }
+}
+
+/// Add a medium to the world. The boundary is the one which is transformed rather than the medium,
+/// so that the distances the medium's density applies to are measured in world space.
+fn place_medium<'a, H: Hittable<'a> + 'static>(
+    boundary: H,
+    transform: Option<Transform>,
+    density: f64,
+    phase_function: Arc<dyn Material>,
+    world: &mut HittableCollection<'a>,
+) {
+    match transform {
+        Some(transform) => world.add(Box::new(ConstantMedium::new(Transformed::new(boundary, transform), density, phase_function))),
+        None => world.add(Box::new(ConstantMedium::new(boundary, density, phase_function))),
+    }
+}
+
+fn type_error(source_name: &str, value: &Value, expected: &str, found: &ValueKind) -> SceneFileError {
+    SceneFileError::parse(source_name, value.position, format!("Expected {}, found {}", expected, found.describe()))
+}
+
+fn to_number(source_name: &str, value: &Value) -> Result<f64, SceneFileError> {
+    match value.kind {
+        ValueKind::Number(number) => Ok(number),
+        ref other => Err(type_error(source_name, value, "a number", other)),
+    }
+}
+
+fn to_count(source_name: &str, value: &Value) -> Result<usize, SceneFileError> {
+    let number = to_number(source_name, value)?;
+    if number < 0.0 || number.fract() != 0.0 {
+        return Err(SceneFileError::parse(source_name, value.position, format!("Expected a whole non-negative number, found {}", number)));
+    }
+
+    Ok(number as usize)
+}
+
+fn to_numbers<const N: usize>(source_name: &str, value: &Value) -> Result<[f64; N], SceneFileError> {
+    let values = match &value.kind {
+        ValueKind::Array(values) if values.len() == N => values,
+        _ => {
+            let expected = format!("a list of {} numbers", N);
+            return Err(type_error(source_name, value, &expected, &value.kind));
+        }
+    };
+
+    let mut result = [0.0; N];
+    for (number, value) in result.iter_mut().zip(values) {
+        *number = to_number(source_name, value)?;
+    }
+
+    Ok(result)
+}
+
+fn to_vector(source_name: &str, value: &Value) -> Result<Vector, SceneFileError> {
+    let [x, y, z] = to_numbers(source_name, value)?;
+    Ok(Vector::new(x, y, z))
+}
+
+fn to_color(source_name: &str, value: &Value) -> Result<Color, SceneFileError> {
+    let [red, green, blue] = to_numbers(source_name, value)?;
+    Ok(Color::new(red, green, blue))
+}
+
+fn to_vectors(source_name: &str, value: &Value) -> Result<[Vector; 3], SceneFileError> {
+    match &value.kind {
+        ValueKind::Array(values) if values.len() == 3 => Ok([
+            to_vector(source_name, &values[0])?,
+            to_vector(source_name, &values[1])?,
+            to_vector(source_name, &values[2])?,
+        ]),
+        other => Err(type_error(source_name, value, "a list of 3 vectors", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CAMERA: &str = "camera { look_from = [0, 0, 1], look_at = [0, 0, 0], vertical_fov = 40 }\n";
+
+    fn parse(source: &str) -> Result<SceneDescription<'static>, SceneFileError> {
+        parse_scene_file(source, "test.scene", None)
+    }
+
+    fn error(source: &str) -> (usize, usize, String) {
+        match parse(source) {
+            Err(SceneFileError::Parse { line, column, message, .. }) => (line, column, message),
+            Err(other) => panic!("Expected a parse error, got {}", other),
+            Ok(_) => panic!("Expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn objects_and_settings_are_loaded() {
+        let source = format!(
+            "{}render {{ width = 40, samples_per_pixel = 4 }}\nmaterial red {{ type = lambertian, albedo = [1, 0, 0] }}\n\
+             sphere {{ center = [0, 0, 0], radius = 1, material = red }}\n\
+             sphere {{ center = [0, 3, 0], radius = 1, material = diffuse_light {{ emit = [4, 4, 4] }} }}\n",
+            CAMERA
+        );
+        let description = parse(&source).unwrap();
+
+        assert_eq!(description.world.len(), 2);
+        assert_eq!(description.lights.len(), 1);
+        assert_eq!((description.settings.width, description.settings.height), (40, 22));
+        assert_eq!(description.settings.samples_per_pixel, 4);
+    }
+
+    #[test]
+    fn image_size_must_be_at_least_2() {
+        assert_eq!(
+            error(&format!("{}render {{\n  width = 1\n}}", CAMERA)),
+            (3, 11, "'width' must be at least 2, got 1".to_string())
+        );
+        assert_eq!(
+            error(&format!("render {{ width = 64, height = 0 }}\n{}", CAMERA)),
+            (1, 31, "'height' must be at least 2, got 0".to_string())
+        );
+
+        // A height which is derived from a tiny width is rounded up.
+        let description = parse(&format!("{}render {{ width = 2 }}", CAMERA)).unwrap();
+        assert_eq!((description.settings.width, description.settings.height), (2, 2));
+    }
+
+    /// The fraction of rays through the center of the scene which scatter in it.
+    fn scattered_fraction(source: &str) -> f64 {
+        let description = parse(&format!("{}{}", CAMERA, source)).unwrap();
+        let rays_count = 4000;
+        let scattered_count = (0..rays_count)
+            .filter(|&index| {
+                let ray = crate::engine::Ray::new(Point::new(index as f64 * 1e-7, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+                description.world.hit(&ray, 0.001, f64::INFINITY).is_some()
+            })
+            .count();
+
+        scattered_count as f64 / rays_count as f64
+    }
+
+    #[test]
+    fn scaled_medium_keeps_its_density() {
+        // Rays cross 4 units of the medium, so 1 - e^-2 of them scatter.
+        let expected = 1.0 - (-2.0f64).exp();
+        let medium = |radius: f64, scale: &str| format!(
+            "medium {{ boundary = sphere {{ center = [0, 0, 0], radius = {} }}, density = 0.5, albedo = [1, 1, 1]{} }}",
+            radius, scale
+        );
+
+        let scaled = scattered_fraction(&medium(1.0, ", scale = 2"));
+        assert!((scaled - expected).abs() < 0.02, "expected {}, got {}", expected, scaled);
+
+        let larger = scattered_fraction(&medium(2.0, ""));
+        assert!((larger - expected).abs() < 0.02, "expected {}, got {}", expected, larger);
+    }
+
+    #[test]
+    fn sample_counts_and_depth_must_be_at_least_1() {
+        assert_eq!(
+            error(&format!("{}render {{ samples_per_pixel = 0 }}", CAMERA)),
+            (2, 30, "'samples_per_pixel' must be at least 1, got 0".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}render {{ max_depth = 0 }}", CAMERA)),
+            (2, 22, "'max_depth' must be at least 1, got 0".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}render {{ noise_threshold = 0.01, max_samples = 0 }}", CAMERA)),
+            (2, 48, "'max_samples' must be at least 1, got 0".to_string())
+        );
+
+        let description = parse(&format!("{}render {{ samples_per_pixel = 1, max_depth = 1 }}", CAMERA)).unwrap();
+        assert_eq!((description.settings.samples_per_pixel, description.settings.max_depth), (1, 1));
+    }
+
+    #[test]
+    fn errors_point_at_the_property() {
+        assert_eq!(error("render { width = 10 }"), (1, 22, "The scene must have a camera".to_string()));
+        assert_eq!(
+            error(&format!("{}sphere {{ center = [0, 0, 0], radius = 1, material = glass }}", CAMERA)),
+            (2, 53, "Unknown material 'glass'".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}sphere {{ center = [0, 0, 0], radius = 1, material = red,\n  raduis = 2 }}\nmaterial red {{ type = lambertian, albedo = [1, 0, 0] }}", CAMERA)),
+            (3, 3, "Unknown property 'raduis' for 'sphere'".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}sphere {{ center = 0, radius = 1, material = lambertian {{ albedo = [1, 0, 0] }} }}", CAMERA)),
+            (2, 19, "Expected a list of 3 numbers, found a number".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}render {{ samples_per_pixel = 2.5 }}", CAMERA)),
+            (2, 30, "Expected a whole non-negative number, found 2.5".to_string())
+        );
+        assert_eq!(
+            error(&format!("{}\n  cube {{ size = 1 }}", CAMERA)),
+            (3, 3, "Unknown item 'cube'".to_string())
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! A text format which describes everything needed for rendering: the camera,
+//! the render settings, the materials and the objects. For example:
+//!
+//! ```text
+//! # Comments start with '#'
+//! render { width = 400, height = 225, samples_per_pixel = 40, background = sky }
+//!
+//! camera {
+//!     look_from = [13, 2, 3]
+//!     look_at = [0, 0, 0]
+//!     vertical_fov = 20
+//!     aperture = 0.1
+//! }
+//!
+//! material ground { type = lambertian, albedo = [0.5, 0.5, 0.5] }
+//!
+//! sphere { center = [0, -1000, 0], radius = 1000, material = ground }
+//! sphere { center = [0, 1, 0], radius = 1, material = dielectric { refraction_index = 1.5 } }
+//! quad { corner = [-2, 0, -2], u = [4, 0, 0], v = [0, 4, 0], material = ground }
+//! box { min = [2, 0, 0], max = [3, 1, 1], material = ground }
+//! mesh { path = "teapot.obj", scale = 2, rotate = [0, 45, 0], translate = [0, 1, 0] }
+//! ```
+//!
+//! A `moving_sphere { center0, center1, time0, time1, radius, material }` moves between the two
+//! centers, and is blurred when the camera has `shutter_open` and `shutter_close` times.
+//! A `medium { boundary, density, albedo }` fills a `sphere { center, radius }` or `box { min, max }`
+//! boundary with fog or smoke.
+//! Materials are either referenced by name, or written inline as a typed block.
+//! The material types are `lambertian`, `metal`, `dielectric` and `diffuse_light`.
+//! Albedos and emitted colors are either a color or a texture block:
+//! `checker { scale, even, odd }`, `image { path }`, `noise { scale, seed }`, `turbulence { scale, seed }`
+//! or `marble { scale, turbulence, octaves, base, veins, seed }`. Procedural textures without
+//! a seed are seeded from the render seed.
+//! Every object can be transformed with the optional `scale`, `rotate` and `translate` properties,
+//! where the rotation is in degrees around each axis. A mesh used several times is loaded once.
+//! The render integrator is one of `path_tracer` (the default), `naive_path_tracer`,
+//! `direct_lighting` and `ambient_occlusion` (optionally `ambient_occlusion { distance = 10 }`).
+//! The render sampler is one of `independent` (the default), `stratified`, `halton`, `sobol`
+//! and `blue_noise`.
+//! The render filter is one of `box` (the default), `tent`, `gaussian`, `mitchell` and `lanczos`,
+//! and its parameters can be changed with a block, as in `mitchell { radius = 2, b = 0, c = 0.5 }`.
+//! A `noise_threshold` in the render settings enables adaptive sampling: noisy pixels get more
+//! samples, up to `max_samples` (4 times `samples_per_pixel` by default).
+
+pub mod error;
+pub mod lexer;
+pub mod parser;
+pub mod loader;
+
+pub use error::SceneFileError;
+pub use loader::{load_scene_file, load_scene_file_with, parse_scene_file, parse_scene_file_with, SceneDescription};
@@ -0,0 +1,269 @@
+use crate::utils::scene_file::error::SceneFileError;
+use crate::utils::scene_file::lexer::{Position, Token, TokenKind};
+
+/// A value assigned to a property.
+#[derive(Clone, Debug)]
+pub enum ValueKind {
+    Number(f64),
+    String(String),
+    Identifier(String),
+    Array(Vec<Value>),
+
+    /// A typed block, such as 'lambertian { albedo = [1, 0, 0] }'.
+    Object(Object),
+}
+
+#[derive(Clone, Debug)]
+pub struct Value {
+    pub kind: ValueKind,
+    pub position: Position,
+}
+
+/// A 'key = value' pair inside a block.
+#[derive(Clone, Debug)]
+pub struct Property {
+    pub key: String,
+    pub key_position: Position,
+    pub value: Value,
+}
+
+#[derive(Clone, Debug)]
+pub struct Object {
+    pub type_name: String,
+    pub properties: Vec<Property>,
+    pub position: Position,
+}
+
+/// A top level statement, such as 'camera { ... }' or 'material glass { ... }'.
+#[derive(Clone, Debug)]
+pub struct Item {
+    pub name: Option<String>,
+    pub object: Object,
+}
+
+/// Builds the items of a scene file out of its tokens, according to this grammar:
+///
+/// file := item*
+/// item := IDENTIFIER [IDENTIFIER] block
+/// block := '{' (property [','])* '}'
+/// property := IDENTIFIER '=' value
+/// value := NUMBER | STRING | IDENTIFIER [block] | '[' (value [','])* ']'
+pub struct Parser<'s> {
+    source_name: &'s str,
+    tokens: Vec<Token>,
+    index: usize,
+    end_position: Position,
+}
+
+
+impl ValueKind {
+    /// Describe the kind of the value, for error messages.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ValueKind::Number(_) => "a number",
+            ValueKind::String(_) => "a string",
+            ValueKind::Identifier(_) => "a name",
+            ValueKind::Array(_) => "a list",
+            ValueKind::Object(_) => "a block",
+        }
+    }
+}
+
+impl<'s> Parser<'s> {
+    pub fn new(tokens: Vec<Token>, source_name: &'s str, end_position: Position) -> Parser<'s> {
+        Parser { source_name, tokens, index: 0, end_position }
+    }
+
+    pub fn parse(mut self) -> Result<Vec<Item>, SceneFileError> {
+        let mut items = Vec::new();
+        while self.peek().is_some() {
+            items.push(self.parse_item()?);
+        }
+
+        Ok(items)
+    }
+
+    fn parse_item(&mut self) -> Result<Item, SceneFileError> {
+        let (type_name, position) = self.expect_identifier()?;
+
+        let name = match self.peek() {
+            Some(TokenKind::Identifier(_)) => Some(self.expect_identifier()?.0),
+            _ => None,
+        };
+
+        let properties = self.parse_block()?;
+        Ok(Item { name, object: Object { type_name, properties, position } })
+    }
+
+    fn parse_block(&mut self) -> Result<Vec<Property>, SceneFileError> {
+        self.expect(TokenKind::OpenBrace, "'{'")?;
+
+        let mut properties = Vec::new();
+        loop {
+            match self.peek() {
+                Some(TokenKind::CloseBrace) => {
+                    self.index += 1;
+                    return Ok(properties);
+                }
+                Some(TokenKind::Comma) => self.index += 1,
+                _ => {
+                    let (key, key_position) = self.expect_identifier()?;
+                    self.expect(TokenKind::Equals, "'='")?;
+                    let value = self.parse_value()?;
+
+                    properties.push(Property { key, key_position, value });
+                }
+            }
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value, SceneFileError> {
+        let token = self.next("a value")?;
+        let position = token.position;
+
+        let kind = match token.kind {
+            TokenKind::Number(number) => ValueKind::Number(number),
+            TokenKind::String(text) => ValueKind::String(text),
+            TokenKind::Identifier(name) => {
+                if self.peek() == Some(&TokenKind::OpenBrace) {
+                    let properties = self.parse_block()?;
+                    ValueKind::Object(Object { type_name: name, properties, position })
+                } else {
+                    ValueKind::Identifier(name)
+                }
+            }
+            TokenKind::OpenBracket => {
+                let mut values = Vec::new();
+                loop {
+                    match self.peek() {
+                        Some(TokenKind::CloseBracket) => {
+                            self.index += 1;
+                            break;
+                        }
+                        Some(TokenKind::Comma) => self.index += 1,
+                        _ => values.push(self.parse_value()?),
+                    }
+                }
+                ValueKind::Array(values)
+            }
+            other => return Err(self.error(position, format!("Expected a value, found {}", describe_token(&other)))),
+        };
+
+        Ok(Value { kind, position })
+    }
+
+    fn peek(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.index).map(|token| &token.kind)
+    }
+
+    fn next(&mut self, expected: &str) -> Result<Token, SceneFileError> {
+        match self.tokens.get(self.index) {
+            Some(token) => {
+                self.index += 1;
+                Ok(token.clone())
+            }
+            None => Err(self.error(self.end_position, format!("Expected {}, found the end of the file", expected))),
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind, expected: &str) -> Result<Position, SceneFileError> {
+        let token = self.next(expected)?;
+        if token.kind != kind {
+            return Err(self.error(token.position, format!("Expected {}, found {}", expected, describe_token(&token.kind))));
+        }
+
+        Ok(token.position)
+    }
+
+    fn expect_identifier(&mut self) -> Result<(String, Position), SceneFileError> {
+        let token = self.next("a name")?;
+        match token.kind {
+            TokenKind::Identifier(name) => Ok((name, token.position)),
+            other => Err(self.error(token.position, format!("Expected a name, found {}", describe_token(&other)))),
+        }
+    }
+
+    fn error(&self, position: Position, message: String) -> SceneFileError {
+        SceneFileError::parse(self.source_name, position, message)
+    }
+}
+
+fn describe_token(kind: &TokenKind) -> String {
+    match kind {
+        TokenKind::Identifier(name) => format!("'{}'", name),
+        TokenKind::Number(number) => format!("'{}'", number),
+        TokenKind::String(text) => format!("\"{}\"", text),
+        TokenKind::OpenBrace => "'{'".to_string(),
+        TokenKind::CloseBrace => "'}'".to_string(),
+        TokenKind::OpenBracket => "'['".to_string(),
+        TokenKind::CloseBracket => "']'".to_string(),
+        TokenKind::Equals => "'='".to_string(),
+        TokenKind::Comma => "','".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::scene_file::lexer::Lexer;
+
+    fn parse(source: &str) -> Result<Vec<Item>, SceneFileError> {
+        let (tokens, end_position) = Lexer::new(source, "test.scene").tokenize()?;
+        Parser::new(tokens, "test.scene", end_position).parse()
+    }
+
+    fn error(source: &str) -> (usize, usize, String) {
+        match parse(source) {
+            Err(SceneFileError::Parse { line, column, message, .. }) => (line, column, message),
+            other => panic!("Expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn items_properties_and_values() {
+        let items = parse("camera { fov = 20 }\nmaterial glass {\n  albedo = checker { even = [1, 0, 0], odd = white }\n}").unwrap();
+        assert_eq!(items.len(), 2);
+
+        assert_eq!(items[0].name, None);
+        assert_eq!(items[0].object.type_name, "camera");
+        assert!(matches!(items[0].object.properties[0].value.kind, ValueKind::Number(number) if number == 20.0));
+
+        let material = &items[1];
+        assert_eq!(material.name.as_deref(), Some("glass"));
+        assert_eq!(material.object.position, Position::new(2, 1));
+
+        let albedo = &material.object.properties[0];
+        assert_eq!(albedo.key, "albedo");
+        assert_eq!(albedo.key_position, Position::new(3, 3));
+        assert_eq!(albedo.value.position, Position::new(3, 12));
+
+        let checker = match &albedo.value.kind {
+            ValueKind::Object(object) => object,
+            other => panic!("Expected a block, got {}", other.describe()),
+        };
+        assert_eq!(checker.type_name, "checker");
+        assert!(matches!(&checker.properties[0].value.kind, ValueKind::Array(values) if values.len() == 3));
+        assert!(matches!(&checker.properties[1].value.kind, ValueKind::Identifier(name) if name == "white"));
+    }
+
+    #[test]
+    fn commas_are_optional() {
+        let items = parse("sphere { center = [0 1 2] radius = 1, , }").unwrap();
+        assert_eq!(items[0].object.properties.len(), 2);
+    }
+
+    #[test]
+    fn errors_point_at_the_unexpected_token() {
+        assert_eq!(error("sphere {\n  radius 1\n}"), (2, 10, "Expected '=', found '1'".to_string()));
+        assert_eq!(error("sphere { = 1 }"), (1, 10, "Expected a name, found '='".to_string()));
+        assert_eq!(error("sphere { radius = }"), (1, 19, "Expected a value, found '}'".to_string()));
+        assert_eq!(error("42 { }"), (1, 1, "Expected a name, found '42'".to_string()));
+        assert_eq!(error("sphere [ ]"), (1, 8, "Expected '{', found '['".to_string()));
+    }
+
+    #[test]
+    fn unexpected_end_of_file_points_at_the_end() {
+        assert_eq!(error("sphere {\n  radius = 1\n"), (3, 1, "Expected a name, found the end of the file".to_string()));
+        assert_eq!(error("sphere { center = [1, 2"), (1, 24, "Expected a value, found the end of the file".to_string()));
+    }
+}
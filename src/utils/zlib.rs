@@ -0,0 +1,434 @@
+/// Largest amount of data a single stored (uncompressed) deflate block can hold.
+const MAX_STORED_BLOCK_SIZE: usize = 65535;
+
+/// Compression method 8 (deflate) with a 32K window, and the fastest compression level.
+/// The check bits make the 16 bit header value divisible by 31.
+const ZLIB_HEADER: [u8; 2] = [0x78, 0x01];
+
+/// Wrap the data in a zlib stream made of stored deflate blocks.
+/// The data isn't actually compressed, but any zlib decoder can read the result.
+pub fn compress_stored(data: &[u8]) -> Vec<u8> {
+    let blocks_count = (data.len() / MAX_STORED_BLOCK_SIZE + 1).max(1);
+    let mut result = Vec::with_capacity(data.len() + blocks_count * 5 + 6);
+    result.extend_from_slice(&ZLIB_HEADER);
+
+    let mut chunks = data.chunks(MAX_STORED_BLOCK_SIZE).peekable();
+    if chunks.peek().is_none() {
+        // Even an empty stream needs a single final block.
+        write_stored_block(&mut result, &[], true);
+    }
+
+    while let Some(chunk) = chunks.next() {
+        let is_final = chunks.peek().is_none();
+        write_stored_block(&mut result, chunk, is_final);
+    }
+
+    result.extend_from_slice(&adler32(data).to_be_bytes());
+    result
+}
+
+/// A stored block is made of a 3 bit header (final flag + block type 00), padded to a byte,
+/// followed by the data length, its one's complement and the data itself.
+fn write_stored_block(result: &mut Vec<u8>, data: &[u8], is_final: bool) {
+    let length = data.len() as u16;
+
+    result.push(if is_final { 1 } else { 0 });
+    result.extend_from_slice(&length.to_le_bytes());
+    result.extend_from_slice(&(!length).to_le_bytes());
+    result.extend_from_slice(data);
+}
+
+/// The checksum zlib streams end with.
+pub fn adler32(data: &[u8]) -> u32 {
+    const MODULO: u32 = 65521;
+
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+
+    // 5552 is the largest amount of bytes which can be summed without overflowing.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MODULO;
+        b %= MODULO;
+    }
+
+    (b << 16) | a
+}
+
+/// Largest amount of bits a Huffman code can have in a deflate stream.
+const MAX_CODE_BITS: usize = 15;
+
+/// The base lengths (and extra bits) of length symbols 257 - 285.
+const LENGTH_BASES: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+/// The base distances (and extra bits) of distance symbols 0 - 29.
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// The order in which the code lengths of the code length alphabet are stored in dynamic blocks.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Reads the bits of a deflate stream, starting from the least significant bit of each byte.
+struct BitReader<'d> {
+    data: &'d [u8],
+    position: usize,
+    bit_buffer: u32,
+    bits_count: u32,
+}
+
+/// A canonical Huffman code, described by the amount of codes of each length,
+/// and the symbols ordered by their codes.
+struct Huffman {
+    counts: [u16; MAX_CODE_BITS + 1],
+    symbols: Vec<u16>,
+}
+
+
+/// Decompress a zlib stream, and verify its checksum.
+// `is_multiple_of` needs Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if data.len() < 6 {
+        return Err("The zlib stream is too short".to_string());
+    }
+    if data[0] & 0x0F != 8 || u16::from_be_bytes([data[0], data[1]]) % 31 != 0 {
+        return Err("Invalid zlib header".to_string());
+    }
+    if data[1] & 0x20 != 0 {
+        return Err("Preset dictionaries are not supported".to_string());
+    }
+
+    let mut reader = BitReader { data: &data[2..], position: 0, bit_buffer: 0, bits_count: 0 };
+    let result = inflate(&mut reader)?;
+
+    let checksum_start = 2 + reader.position;
+    let checksum = data.get(checksum_start..checksum_start + 4)
+        .ok_or_else(|| "The zlib stream is missing its checksum".to_string())?;
+    if u32::from_be_bytes([checksum[0], checksum[1], checksum[2], checksum[3]]) != adler32(&result) {
+        return Err("The zlib checksum doesn't match the data".to_string());
+    }
+
+    Ok(result)
+}
+
+/// Decompress raw deflate blocks, until the final one.
+fn inflate(reader: &mut BitReader) -> Result<Vec<u8>, String> {
+    let mut result = Vec::new();
+
+    loop {
+        let is_final = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored_block(reader, &mut result)?,
+            1 => {
+                let (lengths, distances) = fixed_codes();
+                inflate_huffman_block(reader, &mut result, &lengths, &distances)?;
+            }
+            2 => {
+                let (lengths, distances) = read_dynamic_codes(reader)?;
+                inflate_huffman_block(reader, &mut result, &lengths, &distances)?;
+            }
+            _ => return Err("Invalid deflate block type".to_string()),
+        }
+
+        if is_final {
+            return Ok(result);
+        }
+    }
+}
+
+fn inflate_stored_block(reader: &mut BitReader, result: &mut Vec<u8>) -> Result<(), String> {
+    reader.align_to_byte();
+    let header = reader.bytes(4)?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    let inverted_length = u16::from_le_bytes([header[2], header[3]]);
+    if length != !inverted_length {
+        return Err("Corrupted stored deflate block length".to_string());
+    }
+
+    result.extend_from_slice(reader.bytes(length as usize)?);
+    Ok(())
+}
+
+fn inflate_huffman_block(reader: &mut BitReader, result: &mut Vec<u8>, lengths: &Huffman, distances: &Huffman) -> Result<(), String> {
+    loop {
+        let symbol = lengths.decode(reader)? as usize;
+        match symbol {
+            0..=255 => result.push(symbol as u8),
+            256 => return Ok(()),
+            257..=285 => {
+                let index = symbol - 257;
+                let length = LENGTH_BASES[index] as usize + reader.bits(LENGTH_EXTRA_BITS[index] as u32)? as usize;
+
+                let distance_symbol = distances.decode(reader)? as usize;
+                if distance_symbol >= DISTANCE_BASES.len() {
+                    return Err("Invalid deflate distance symbol".to_string());
+                }
+                let distance = DISTANCE_BASES[distance_symbol] as usize
+                    + reader.bits(DISTANCE_EXTRA_BITS[distance_symbol] as u32)? as usize;
+                if distance > result.len() {
+                    return Err("Deflate distance reaches before the start of the data".to_string());
+                }
+
+                // The copied range may overlap the bytes it produces, so it's copied byte by byte.
+                let start = result.len() - distance;
+                for index in 0..length {
+                    result.push(result[start + index]);
+                }
+            }
+            _ => return Err("Invalid deflate length symbol".to_string()),
+        }
+    }
+}
+
+/// The codes of blocks which use the fixed Huffman codes, as defined by the deflate spec.
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+
+    (Huffman::new(&lengths), Huffman::new(&[5; 30]))
+}
+
+/// Dynamic blocks start with their code lengths, which are themselves Huffman coded.
+fn read_dynamic_codes(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let lengths_count = reader.bits(5)? as usize + 257;
+    let distances_count = reader.bits(5)? as usize + 1;
+    let code_lengths_count = reader.bits(4)? as usize + 4;
+
+    let mut code_length_lengths = [0u8; 19];
+    for &index in CODE_LENGTH_ORDER.iter().take(code_lengths_count) {
+        code_length_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_length_lengths);
+
+    let mut lengths = Vec::with_capacity(lengths_count + distances_count);
+    while lengths.len() < lengths_count + distances_count {
+        let symbol = code_length_code.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths.last().ok_or_else(|| "Repeating a code length before the first one".to_string())?;
+                (previous, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            18 => (0, 11 + reader.bits(7)? as usize),
+            _ => return Err("Invalid code length symbol".to_string()),
+        };
+
+        if lengths.len() + repeat > lengths_count + distances_count {
+            return Err("Too many code lengths in a dynamic deflate block".to_string());
+        }
+        lengths.resize(lengths.len() + repeat, value);
+    }
+
+    if lengths[256] == 0 {
+        return Err("The dynamic deflate block has no end of block code".to_string());
+    }
+
+    Ok((Huffman::new(&lengths[..lengths_count]), Huffman::new(&lengths[lengths_count..])))
+}
+
+impl<'d> BitReader<'d> {
+    fn bits(&mut self, count: u32) -> Result<u32, String> {
+        while self.bits_count < count {
+            let byte = *self.data.get(self.position).ok_or_else(|| "Unexpected end of the deflate stream".to_string())?;
+            self.position += 1;
+            self.bit_buffer |= (byte as u32) << self.bits_count;
+            self.bits_count += 8;
+        }
+
+        let value = self.bit_buffer & ((1u64 << count) - 1) as u32;
+        self.bit_buffer >>= count;
+        self.bits_count -= count;
+        Ok(value)
+    }
+
+    /// Drop the rest of the current byte.
+    fn align_to_byte(&mut self) {
+        self.bit_buffer = 0;
+        self.bits_count = 0;
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'d [u8], String> {
+        let bytes = self.data.get(self.position..self.position + count)
+            .ok_or_else(|| "Unexpected end of the deflate stream".to_string())?;
+        self.position += count;
+        Ok(bytes)
+    }
+}
+
+impl Huffman {
+    /// Build the code out of the code length of each symbol, where 0 means the symbol is unused.
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; MAX_CODE_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; MAX_CODE_BITS + 2];
+        for length in 1..=MAX_CODE_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+
+        let mut symbols = vec![0u16; offsets[MAX_CODE_BITS + 1] as usize];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+
+        Huffman { counts, symbols }
+    }
+
+    /// Read a code bit by bit. Codes of each length are consecutive numbers, so a code
+    /// of a given length is valid when it's below the first code of the next length.
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+
+        for length in 1..=MAX_CODE_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[length] as i32;
+            if code - first < count {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+
+        Err("Invalid Huffman code in the deflate stream".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Split a stream made by `compress_stored` back into its blocks' data and final flags.
+    fn read_stored_blocks(stream: &[u8]) -> Vec<(bool, Vec<u8>)> {
+        let mut blocks = Vec::new();
+        let mut position = 2;
+        loop {
+            let is_final = stream[position] == 1;
+            let length = u16::from_le_bytes([stream[position + 1], stream[position + 2]]);
+            let inverted_length = u16::from_le_bytes([stream[position + 3], stream[position + 4]]);
+            assert_eq!(length, !inverted_length);
+
+            let data_start = position + 5;
+            blocks.push((is_final, stream[data_start..data_start + length as usize].to_vec()));
+            position = data_start + length as usize;
+            if is_final {
+                assert_eq!(stream.len(), position + 4);
+                return blocks;
+            }
+        }
+    }
+
+    #[test]
+    fn adler32_known_values() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+        // Long enough for the sums to be reduced between chunks.
+        assert_eq!(adler32(&[0xFF; 100_000]), 0x149A_302C);
+    }
+
+    #[test]
+    fn empty_stream_has_a_single_final_block() {
+        assert_eq!(compress_stored(b""), vec![0x78, 0x01, 0x01, 0x00, 0x00, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x01]);
+    }
+
+    #[test]
+    fn stored_stream_holds_the_data_in_blocks() {
+        let data: Vec<u8> = (0..(2 * MAX_STORED_BLOCK_SIZE + 10)).map(|index| (index % 251) as u8).collect();
+        let stream = compress_stored(&data);
+
+        assert_eq!(stream[..2], ZLIB_HEADER);
+        assert_eq!((((stream[0] as u16) << 8) | stream[1] as u16) % 31, 0);
+
+        let blocks = read_stored_blocks(&stream);
+        let flags: Vec<bool> = blocks.iter().map(|(is_final, _)| *is_final).collect();
+        assert_eq!(flags, vec![false, false, true]);
+        let stored_data: Vec<u8> = blocks.into_iter().flat_map(|(_, data)| data).collect();
+        assert_eq!(stored_data, data);
+
+        assert_eq!(stream[stream.len() - 4..], adler32(&data).to_be_bytes());
+    }
+
+    #[test]
+    fn stored_stream_round_trip() {
+        let data: Vec<u8> = (0..(MAX_STORED_BLOCK_SIZE + 10)).map(|index| (index % 251) as u8).collect();
+        assert_eq!(decompress(&compress_stored(&data)).unwrap(), data);
+        assert_eq!(decompress(&compress_stored(b"")).unwrap(), b"");
+    }
+
+    #[test]
+    fn fixed_huffman_stream_is_inflated() {
+        // zlib.compress(b"hello hello hello!", 9), which uses back references.
+        let stream = [120, 218, 203, 72, 205, 201, 201, 87, 200, 64, 144, 138, 0, 64, 204, 6, 158];
+        assert_eq!(decompress(&stream).unwrap(), b"hello hello hello!");
+    }
+
+    #[test]
+    fn dynamic_huffman_stream_is_inflated() {
+        // zlib.compress(data, 9), which encodes the data in a single dynamic block.
+        let data: Vec<u8> = (0..2000).map(|index| b'a' + ((index * index + index / 3) % 5) as u8).collect();
+        let stream = [
+            120, 218, 237, 199, 65, 13, 0, 48, 16, 195, 48, 172, 237, 82, 254, 20, 14, 199, 164, 248, 231, 116, 121, 165,
+            29, 131, 196, 90, 107, 173, 181, 255, 245, 0, 105, 135, 5, 155,
+        ];
+        assert_eq!((stream[2] >> 1) & 0b11, 2);
+        assert_eq!(decompress(&stream).unwrap(), data);
+    }
+
+    #[test]
+    fn stream_of_several_blocks_is_inflated() {
+        // A fixed block, an empty stored block from a full flush, and a final fixed block.
+        let stream = [
+            120, 218, 74, 203, 44, 42, 46, 81, 72, 202, 201, 79, 206, 214, 81, 0, 0, 0, 0, 255, 255, 75, 203, 44, 42, 46,
+            81, 72, 202, 201, 79, 206, 214, 81, 40, 78, 77, 206, 207, 75, 129, 240, 0, 16, 36, 13, 230,
+        ];
+        assert_eq!(decompress(&stream).unwrap(), b"first block, first block, second block");
+    }
+
+    #[test]
+    fn invalid_streams_are_rejected() {
+        let stream = compress_stored(b"some data");
+
+        assert_eq!(decompress(&stream[..5]).unwrap_err(), "The zlib stream is too short");
+
+        let mut bad_header = stream.clone();
+        bad_header[1] = 0x02;
+        assert_eq!(decompress(&bad_header).unwrap_err(), "Invalid zlib header");
+
+        let mut bad_checksum = stream.clone();
+        *bad_checksum.last_mut().unwrap() ^= 1;
+        assert_eq!(decompress(&bad_checksum).unwrap_err(), "The zlib checksum doesn't match the data");
+
+        assert_eq!(decompress(&stream[..stream.len() - 2]).unwrap_err(), "The zlib stream is missing its checksum");
+        assert_eq!(decompress(&stream[..10]).unwrap_err(), "Unexpected end of the deflate stream");
+
+        let mut bad_length = stream.clone();
+        bad_length[5] ^= 1;
+        assert_eq!(decompress(&bad_length).unwrap_err(), "Corrupted stored deflate block length");
+
+        let mut bad_block_type = stream;
+        bad_block_type[2] = 0b111;
+        assert_eq!(decompress(&bad_block_type).unwrap_err(), "Invalid deflate block type");
+    }
+}
@@ -17,6 +17,15 @@ impl Color {
     pub fn zeroes() -> Color {
         Color::new(0.0, 0.0, 0.0)
     }
+
+    /// The perceived brightness of the color, using the Rec. 709 weights.
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.red + 0.7152 * self.green + 0.0722 * self.blue
+    }
+
+    pub fn max_element(&self) -> f64 {
+        self.red.max(self.green).max(self.blue)
+    }
 }
 
 implement_random_function!(Color, red, green, blue);
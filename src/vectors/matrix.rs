@@ -0,0 +1,202 @@
+use crate::vectors::{Point, Vector};
+use std::ops::Mul;
+
+/// A 4x4 matrix of an affine transformation in homogeneous coordinates,
+/// stored in row-major order. Points are treated as (x, y, z, 1) and
+/// vectors as (x, y, z, 0), so vectors aren't affected by translations.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Matrix {
+    pub elements: [[f64; 4]; 4],
+}
+
+
+impl Matrix {
+    pub fn new(elements: [[f64; 4]; 4]) -> Matrix {
+        Matrix { elements }
+    }
+
+    pub fn identity() -> Matrix {
+        Matrix::new([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn translation(offset: Vector) -> Matrix {
+        Matrix::new([
+            [1.0, 0.0, 0.0, offset.x],
+            [0.0, 1.0, 0.0, offset.y],
+            [0.0, 0.0, 1.0, offset.z],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn scaling(factors: Vector) -> Matrix {
+        Matrix::new([
+            [factors.x, 0.0, 0.0, 0.0],
+            [0.0, factors.y, 0.0, 0.0],
+            [0.0, 0.0, factors.z, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// A counter-clockwise rotation around the given axis (when looking from
+    /// the tip of the axis towards the origin), by an angle in degrees.
+    pub fn rotation(axis: Vector, degrees: f64) -> Matrix {
+        let axis = axis.unit();
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        let (x, y, z) = (axis.x, axis.y, axis.z);
+
+        // Rodrigues' rotation formula
+        Matrix::new([
+            [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y, 0.0],
+            [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x, 0.0],
+            [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        let mut result = [[0.0; 4]; 4];
+        for (row, values) in self.elements.iter().enumerate() {
+            for (column, &value) in values.iter().enumerate() {
+                result[column][row] = value;
+            }
+        }
+
+        Matrix::new(result)
+    }
+
+    /// The determinant of the upper-left 3x3 part, which is how much the transformation scales volumes.
+    pub fn linear_determinant(&self) -> f64 {
+        let m = &self.elements;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Invert the matrix with Gauss-Jordan elimination, or return None if it's singular
+    /// (for example a scaling by 0).
+    pub fn inverse(&self) -> Option<Matrix> {
+        let mut matrix = self.elements;
+        let mut inverse = Matrix::identity().elements;
+
+        for column in 0..4 {
+            // Partial pivoting: use the row with the largest value in this column
+            let pivot = (column..4)
+                .max_by(|&a, &b| matrix[a][column].abs().total_cmp(&matrix[b][column].abs()))
+                .unwrap();
+            if matrix[pivot][column].abs() < 1e-12 {
+                return None;
+            }
+            matrix.swap(column, pivot);
+            inverse.swap(column, pivot);
+
+            let scale = 1.0 / matrix[column][column];
+            for i in 0..4 {
+                matrix[column][i] *= scale;
+                inverse[column][i] *= scale;
+            }
+
+            for row in 0..4 {
+                let factor = matrix[row][column];
+                if row == column || factor == 0.0 {
+                    continue;
+                }
+                for i in 0..4 {
+                    matrix[row][i] -= factor * matrix[column][i];
+                    inverse[row][i] -= factor * inverse[column][i];
+                }
+            }
+        }
+
+        Some(Matrix::new(inverse))
+    }
+
+    /// Transform a point, which is affected by translations.
+    pub fn transform_point(&self, point: Point) -> Point {
+        let m = &self.elements;
+        Point::new(
+            m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+            m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+            m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3],
+        )
+    }
+
+    /// Transform a direction, which isn't affected by translations.
+    pub fn transform_vector(&self, vector: Vector) -> Vector {
+        let m = &self.elements;
+        Vector::new(
+            m[0][0] * vector.x + m[0][1] * vector.y + m[0][2] * vector.z,
+            m[1][0] * vector.x + m[1][1] * vector.y + m[1][2] * vector.z,
+            m[2][0] * vector.x + m[2][1] * vector.y + m[2][2] * vector.z,
+        )
+    }
+}
+
+impl Default for Matrix {
+    fn default() -> Self {
+        Matrix::identity()
+    }
+}
+
+/// Matrix multiplication: (a * b) applies b first and then a.
+impl Mul for Matrix {
+    type Output = Matrix;
+
+    fn mul(self, other: Matrix) -> Matrix {
+        let mut result = [[0.0; 4]; 4];
+        for (row, values) in result.iter_mut().enumerate() {
+            for (column, value) in values.iter_mut().enumerate() {
+                *value = (0..4).map(|i| self.elements[row][i] * other.elements[i][column]).sum();
+            }
+        }
+
+        Matrix::new(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &Matrix, b: &Matrix) {
+        for (row_a, row_b) in a.elements.iter().zip(&b.elements) {
+            for (value_a, value_b) in row_a.iter().zip(row_b) {
+                assert!((value_a - value_b).abs() < 1e-9, "expected {:?}, got {:?}", b, a);
+            }
+        }
+    }
+
+    #[test]
+    fn inverse_undoes_the_matrix() {
+        // A shear along with a rotation, a non-uniform scaling and a translation.
+        let shear = Matrix::new([[1.0, 0.5, 0.0, 0.0], [0.0, 1.0, 0.0, 0.0], [0.3, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+        let matrix = Matrix::translation(Vector::new(1.0, -2.0, 3.0))
+            * Matrix::rotation(Vector::new(1.0, 1.0, 0.0), 30.0)
+            * Matrix::scaling(Vector::new(2.0, 0.5, -3.0))
+            * shear;
+        let inverse = matrix.inverse().unwrap();
+
+        assert_close(&(matrix * inverse), &Matrix::identity());
+        assert_close(&(inverse * matrix), &Matrix::identity());
+        assert!((matrix.linear_determinant() * inverse.linear_determinant() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn inverse_needs_pivoting() {
+        // A zero on the diagonal, which only row swapping can handle.
+        let swap = Matrix::new([[0.0, 1.0, 0.0, 0.0], [1.0, 0.0, 0.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+        assert_close(&swap.inverse().unwrap(), &swap);
+    }
+
+    #[test]
+    fn singular_matrix_has_no_inverse() {
+        assert!(Matrix::scaling(Vector::new(1.0, 0.0, 1.0)).inverse().is_none());
+        let flat = Matrix::new([[1.0, 2.0, 3.0, 0.0], [2.0, 4.0, 6.0, 0.0], [0.0, 0.0, 1.0, 0.0], [0.0, 0.0, 0.0, 1.0]]);
+        assert!(flat.inverse().is_none());
+    }
+}
@@ -1,7 +1,11 @@
 pub mod color;
 pub mod vector;
 mod point;
+pub mod matrix;
+pub mod transform;
 
 pub use color::Color;
 pub use vector::Vector;
 pub use point::Point;
+pub use matrix::Matrix;
+pub use transform::Transform;
@@ -0,0 +1,167 @@
+use crate::vectors::{Matrix, Point, Vector};
+
+/// An affine transformation together with its inverse, which is needed for
+/// transforming rays into object space and normals back into world space.
+///
+/// Transforms are built by chaining, where each step is applied after the previous ones:
+///
+/// ```text
+/// Transform::scaling(Vector::new(2.0, 2.0, 2.0)).rotate_y(45.0).translate(Vector::new(0.0, 1.0, 0.0))
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+    matrix: Matrix,
+    inverse: Matrix,
+}
+
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform { matrix: Matrix::identity(), inverse: Matrix::identity() }
+    }
+
+    /// Create a transform out of an invertible matrix, or None when the matrix is singular.
+    pub fn from_matrix(matrix: Matrix) -> Option<Transform> {
+        Some(Transform { matrix, inverse: matrix.inverse()? })
+    }
+
+    pub fn translation(offset: Vector) -> Transform {
+        Transform { matrix: Matrix::translation(offset), inverse: Matrix::translation(-offset) }
+    }
+
+    /// # Panics
+    ///
+    /// If one of the factors is 0, since the transform must be invertible.
+    /// Use `from_matrix` with `Matrix::scaling` for factors which aren't known to be non-zero.
+    pub fn scaling(factors: Vector) -> Transform {
+        if factors.x == 0.0 || factors.y == 0.0 || factors.z == 0.0 {
+            panic!("Scaling factors {:?} can't be inverted, since one of them is 0.", factors);
+        }
+
+        let inverse_factors = Vector::new(1.0 / factors.x, 1.0 / factors.y, 1.0 / factors.z);
+        Transform { matrix: Matrix::scaling(factors), inverse: Matrix::scaling(inverse_factors) }
+    }
+
+    /// A rotation around the given axis by an angle in degrees, following the right-hand rule.
+    pub fn rotation(axis: Vector, degrees: f64) -> Transform {
+        let matrix = Matrix::rotation(axis, degrees);
+        // The inverse of a rotation matrix is its transpose
+        Transform { matrix, inverse: matrix.transpose() }
+    }
+
+    /// Apply the other transform after this one.
+    pub fn then(&self, other: &Transform) -> Transform {
+        Transform {
+            matrix: other.matrix * self.matrix,
+            inverse: self.inverse * other.inverse,
+        }
+    }
+
+    pub fn translate(&self, offset: Vector) -> Transform {
+        self.then(&Transform::translation(offset))
+    }
+
+    /// # Panics
+    ///
+    /// If one of the factors is 0, like `scaling`.
+    pub fn scale(&self, factors: Vector) -> Transform {
+        self.then(&Transform::scaling(factors))
+    }
+
+    pub fn rotate(&self, axis: Vector, degrees: f64) -> Transform {
+        self.then(&Transform::rotation(axis, degrees))
+    }
+
+    pub fn rotate_x(&self, degrees: f64) -> Transform {
+        self.rotate(Vector::new(1.0, 0.0, 0.0), degrees)
+    }
+
+    pub fn rotate_y(&self, degrees: f64) -> Transform {
+        self.rotate(Vector::new(0.0, 1.0, 0.0), degrees)
+    }
+
+    pub fn rotate_z(&self, degrees: f64) -> Transform {
+        self.rotate(Vector::new(0.0, 0.0, 1.0), degrees)
+    }
+
+    pub fn inverse(&self) -> Transform {
+        Transform { matrix: self.inverse, inverse: self.matrix }
+    }
+
+    pub fn matrix(&self) -> &Matrix {
+        &self.matrix
+    }
+
+    pub fn point(&self, point: Point) -> Point {
+        self.matrix.transform_point(point)
+    }
+
+    pub fn vector(&self, vector: Vector) -> Vector {
+        self.matrix.transform_vector(vector)
+    }
+
+    /// Normals are transformed by the inverse transpose, so they stay perpendicular to
+    /// the surface under non-uniform scaling. The result isn't a unit vector.
+    pub fn normal(&self, normal: Vector) -> Vector {
+        self.inverse.transpose().transform_vector(normal)
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform::identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Vector, b: Vector) {
+        assert!((a - b).size() < 1e-9, "expected {:?}, got {:?}", b, a);
+    }
+
+    #[test]
+    fn chained_steps_are_applied_in_order() {
+        let point = Point::new(1.0, 0.0, 0.0);
+        let scale = Vector::new(2.0, 2.0, 2.0);
+        let offset = Vector::new(1.0, 0.0, 0.0);
+
+        assert_close(Transform::scaling(scale).translate(offset).point(point), Point::new(3.0, 0.0, 0.0));
+        assert_close(Transform::translation(offset).scale(scale).point(point), Point::new(4.0, 0.0, 0.0));
+        // Rotating (1, 0, 0) by 90 degrees around y gives (0, 0, -1), which is then moved.
+        assert_close(Transform::identity().rotate_y(90.0).translate(offset).point(point), Point::new(1.0, 0.0, -1.0));
+        assert_close(Transform::translation(offset).rotate_y(90.0).point(point), Point::new(0.0, 0.0, -2.0));
+    }
+
+    #[test]
+    fn inverse_undoes_the_chain() {
+        let transform = Transform::scaling(Vector::new(2.0, 1.0, 0.5)).rotate_x(30.0).rotate_z(-60.0).translate(Vector::new(1.0, 2.0, 3.0));
+        let point = Point::new(0.3, -1.2, 4.0);
+
+        assert_close(transform.inverse().point(transform.point(point)), point);
+        assert_close(transform.point(transform.inverse().point(point)), point);
+        assert_close(transform.inverse().vector(transform.vector(point)), point);
+        let from_matrix = Transform::from_matrix(*transform.matrix()).unwrap();
+        assert_close(from_matrix.inverse().point(transform.point(point)), point);
+        assert!(Transform::from_matrix(Matrix::scaling(Vector::new(0.0, 1.0, 1.0))).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "can't be inverted")]
+    fn scaling_by_0_panics() {
+        Transform::scaling(Vector::new(1.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn normals_stay_perpendicular_to_tangents() {
+        let transform = Transform::scaling(Vector::new(3.0, 1.0, 0.5)).rotate_z(25.0);
+        let normal = Vector::new(1.0, 1.0, 0.0);
+        // Both tangents are perpendicular to the normal before the transformation.
+        for tangent in [Vector::new(1.0, -1.0, 0.0), Vector::new(0.0, 0.0, 1.0)] {
+            assert!(transform.normal(normal).dot(transform.vector(tangent)).abs() < 1e-9);
+        }
+        // Unlike the normal transformed as a plain vector, which isn't.
+        assert!(transform.vector(normal).dot(transform.vector(Vector::new(1.0, -1.0, 0.0))).abs() > 0.1);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::engine::Rng;
+use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+use std::ops::Index;
+
 /// Represents a vector in the 3D space.
 #[derive(Copy, Clone, Debug)]
 pub struct Vector {
@@ -16,32 +20,42 @@ impl Vector {
         Vector::new(0.0, 0.0, 0.0)
     }
 
-    pub fn random_in_unit_sphere() -> Vector {
-        loop {
-            let random_vector = Vector::random(-1.0, 1.0);
-            if random_vector.size_squared() <= 1.0 {
-                return random_vector;
-            }
-        }
+    /// A uniformly distributed point inside the unit sphere. Every sample uses exactly three
+    /// random numbers (two for the direction and one for the distance from the center),
+    /// instead of rejection sampling, so samplers can assign them fixed dimensions.
+    pub fn random_in_unit_sphere(rng: &mut Rng) -> Vector {
+        let direction = Vector::random_unit_vector(rng);
+        rng.float().cbrt() * direction
     }
 
-    pub fn random_in_unit_disk() -> Vector {
-        loop {
-            let mut random_vector = Vector::random(-1.0, 1.0);
-            random_vector.z = 0.0;
-
-            if random_vector.size() <= 1.0 {
-                return random_vector;
-            }
+    /// A uniformly distributed point inside the unit disk on the XY plane, made of two random
+    /// numbers with Shirley and Chiu's concentric mapping, which keeps nearby numbers nearby.
+    pub fn random_in_unit_disk(rng: &mut Rng) -> Vector {
+        let a = 2.0 * rng.float() - 1.0;
+        let b = 2.0 * rng.float() - 1.0;
+        if a == 0.0 && b == 0.0 {
+            return Vector::zeroes();
         }
+
+        let (radius, angle) = if a.abs() > b.abs() {
+            (a, FRAC_PI_4 * (b / a))
+        } else {
+            (b, FRAC_PI_2 - FRAC_PI_4 * (a / b))
+        };
+        Vector::new(radius * angle.cos(), radius * angle.sin(), 0.0)
     }
 
     pub fn reflect(self, normal: Vector) -> Vector {
         self - 2.0 * self.dot(normal) * normal
     }
 
-    pub fn random_unit_vector() -> Vector {
-        Vector::random_in_unit_sphere().unit()
+    /// A uniformly distributed direction, made of two random numbers.
+    pub fn random_unit_vector(rng: &mut Rng) -> Vector {
+        let z = 1.0 - 2.0 * rng.float();
+        let phi = 2.0 * PI * rng.float();
+        let radius = (1.0 - z * z).max(0.0).sqrt();
+
+        Vector::new(radius * phi.cos(), radius * phi.sin(), z)
     }
 
     pub fn refract(self, normal: Vector, refraction_ratio: f64) -> Vector {
@@ -51,6 +65,30 @@ impl Vector {
 
         perp_ray + parallel_ray
     }
+
+    /// Element-wise minimum of two vectors.
+    pub fn min(self, other: Vector) -> Vector {
+        Vector::new(self.x.min(other.x), self.y.min(other.y), self.z.min(other.z))
+    }
+
+    /// Element-wise maximum of two vectors.
+    pub fn max(self, other: Vector) -> Vector {
+        Vector::new(self.x.max(other.x), self.y.max(other.y), self.z.max(other.z))
+    }
+}
+
+/// Access the vector elements by axis index: 0 => x, 1 => y, 2 => z.
+impl Index<usize> for Vector {
+    type Output = f64;
+
+    fn index(&self, axis: usize) -> &Self::Output {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("Axis index {} is out of range, expected 0, 1 or 2.", axis)
+        }
+    }
 }
 
 implement_random_function!(Vector, x, y, z);